@@ -1,143 +1,1143 @@
+use crate::config::{Config, LIST_RATIO_MAX, LIST_RATIO_MIN};
+use crate::event_socket::EventSocket;
+use crate::github;
+use crate::matcher::Matcher;
+use crate::report;
 use crate::runner::{
-    control_runner, discover_runners, get_runner_logs, refresh_runners, Runner, RunnerStatus,
+    agent_settings, compare_versions, control_runner, deregister_blocker, discover_runners,
+    duplicate_service_names, format_size, get_runner_log_context, get_runner_logs, github_repo,
+    is_version_outdated, max_version, newest_version, prune_blocker, read_runner_config,
+    refresh_runners, AgentSettings, CommandFailure, DiscoveryOptions, JournalFormat, LogPriority,
+    ProbeReport, Runner, RunnerStatus, SystemCommandRunner, UnitCache, PRUNE_WORK_ACTION,
 };
+use crate::worker::{spawn_worker, BulkLimits, WorkerCommand, WorkerResponse};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
-use sysinfo::System;
+use std::time::{Duration, Instant};
+use sysinfo::{Disks, Networks, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// How often runner statuses are refreshed
+pub const REFRESH_INTERVAL_MS: u64 = 1000;
+
+/// Refresh interval once nobody has touched the keyboard for a while
+pub const IDLE_REFRESH_INTERVAL_MS: u64 = 10_000;
+
+/// No runner update for this many refresh intervals means the worker is probably blocked
+const STALL_INTERVALS: u32 = 3;
+
+/// How long a work folder scan is shown before the details view rescans it
+const WORK_SCAN_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Lines shown above a log search match when its context is expanded
+const LOG_CONTEXT_LEAD_IN: usize = 5;
+
+/// How long a runner's row stays highlighted after its status changes
+const STATUS_FLASH: Duration = Duration::from_secs(2);
+
+/// A flapping runner rings the bell at most this often
+const BELL_DEBOUNCE: Duration = Duration::from_secs(60);
+
+/// How far back systemd restart counts are kept to tell a restart loop
+const FLAPPING_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// Automatic restarts within `FLAPPING_WINDOW` that make a runner flapping
+const FLAPPING_RESTARTS: u32 = 2;
+
+/// A busy runner's processes are walked for the headroom estimate at most this
+/// often, unless a list column already needs them every tick
+const JOB_USAGE_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Threads running `on_failure`/`on_recover`; the `runner-` prefix keeps a panic
+/// from tearing down the terminal
+const HOOK_THREAD_NAME: &str = "runner-hook";
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppMode {
     Normal,
-    Logs,
     Help,
+    Confirm,
+    /// Selected runner's details fill the main area
+    Detail,
+    /// Typing a runner list filter
+    Filter,
+    /// Typing a search through the logs
+    LogSearch,
+    /// First-run introduction, shown until dismissed once
+    Welcome,
+    /// Scrollable command output over the current view
+    Output,
+    /// Typing labels to add to or remove from the selected runner
+    Labels,
+}
+
+/// Top-level views, switched with Tab/Shift+Tab or the number keys
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tab {
+    Runners,
+    Logs,
+    Stats,
+    Events,
+    /// Versions, labels and agent settings compared across runners
+    Fleet,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 5] = [Tab::Runners, Tab::Logs, Tab::Stats, Tab::Events, Tab::Fleet];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Runners => "Runners",
+            Tab::Logs => "Logs",
+            Tab::Stats => "Stats",
+            Tab::Events => "Events",
+            Tab::Fleet => "Fleet",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|&tab| tab == self).unwrap_or(0)
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// How much detail each runner gets in the list pane
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListDensity {
+    /// Status symbol and `repo/name`, one runner per row
+    Normal,
+    /// Like normal, with the status text right-aligned at the end of the row
+    Status,
+    /// Short `repo/number` labels packed into several columns
+    Compact,
+    /// One runner per row in aligned columns, chosen with `list_columns`
+    Verbose,
+}
+
+impl ListDensity {
+    pub fn next(self) -> Self {
+        match self {
+            ListDensity::Normal => ListDensity::Status,
+            ListDensity::Status => ListDensity::Compact,
+            ListDensity::Compact => ListDensity::Verbose,
+            ListDensity::Verbose => ListDensity::Normal,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ListDensity::Normal => "normal",
+            ListDensity::Status => "status",
+            ListDensity::Compact => "compact",
+            ListDensity::Verbose => "verbose",
+        }
+    }
+}
+
+/// A column of the verbose runner list, chosen with `list_columns`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListColumn {
+    /// Status text: idle, busy, failed, ...
+    Status,
+    Repo,
+    /// `repo/name`, or just the name when `Repo` is shown too
+    Name,
+    /// How long the runner's processes have been up
+    Uptime,
+    Version,
+    /// CPU and memory used by the runner's processes, including running jobs
+    Cpu,
+    Mem,
+    /// Whether a job is executing
+    Busy,
+}
+
+impl ListColumn {
+    pub fn heading(self) -> &'static str {
+        match self {
+            ListColumn::Status => "STATUS",
+            ListColumn::Repo => "REPO",
+            ListColumn::Name => "NAME",
+            ListColumn::Uptime => "UPTIME",
+            ListColumn::Version => "VERSION",
+            ListColumn::Cpu => "CPU",
+            ListColumn::Mem => "MEM",
+            ListColumn::Busy => "BUSY",
+        }
+    }
+
+    /// Filled in from the process table rather than the runner itself
+    fn needs_processes(self) -> bool {
+        matches!(self, ListColumn::Uptime | ListColumn::Cpu | ListColumn::Mem)
+    }
+}
+
+/// What a runner's processes (listener, worker and job steps) are using
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcessUsage {
+    /// Percent of one core, summed over the processes
+    pub cpu: f32,
+    pub memory: u64,
+    /// Unix time the oldest of them started
+    pub started: u64,
+}
+
+/// How far back the logs tab reaches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogWindow {
+    /// The newest lines, however old they are
+    Tail,
+    LastFiveMinutes,
+    LastHour,
+    Today,
+}
+
+impl LogWindow {
+    pub fn next(self) -> Self {
+        match self {
+            LogWindow::Tail => LogWindow::LastFiveMinutes,
+            LogWindow::LastFiveMinutes => LogWindow::LastHour,
+            LogWindow::LastHour => LogWindow::Today,
+            LogWindow::Today => LogWindow::Tail,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogWindow::Tail => "last lines",
+            LogWindow::LastFiveMinutes => "last 5m",
+            LogWindow::LastHour => "last 1h",
+            LogWindow::Today => "today",
+        }
+    }
+
+    /// The window as a journalctl `--since` value
+    pub fn since(self) -> Option<&'static str> {
+        match self {
+            LogWindow::Tail => None,
+            LogWindow::LastFiveMinutes => Some("5 min ago"),
+            LogWindow::LastHour => Some("1 hour ago"),
+            LogWindow::Today => Some("today"),
+        }
+    }
+}
+
+/// A program to run in the foreground while the TUI is suspended
+pub struct SuspendRequest {
+    /// What to call it in the status bar
+    pub label: String,
+    pub command: Command,
+}
+
+/// Command output shown in the output overlay
+#[derive(Debug, Clone)]
+pub struct OutputView {
+    pub title: String,
+    /// One-line verdict shown above the output
+    pub summary: String,
+    pub ok: bool,
+    pub lines: Vec<String>,
+    /// First output line shown
+    pub scroll: usize,
+}
+
+impl OutputView {
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// Scroll down, stopping at the last line.
+    pub fn scroll_down(&mut self, lines: usize) {
+        let last_line = self.lines.len().saturating_sub(1);
+        self.scroll = (self.scroll + lines).min(last_line);
+    }
+}
+
+/// Usage levels (%) above which a resource shows yellow, and red
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub warn: f64,
+    pub alert: f64,
+}
+
+/// How long a resource has been above its alert level, and whether that was reported
+#[derive(Debug, Default)]
+pub struct Alarm {
+    pub over_since: Option<Instant>,
+    pub raised: bool,
+}
+
+impl Alarm {
+    /// Track one sample; returns true when the level has just been exceeded for `sustain`.
+    fn update(&mut self, over: bool, sustain: Duration, now: Instant) -> bool {
+        if !over {
+            *self = Alarm::default();
+            return false;
+        }
+        let since = *self.over_since.get_or_insert(now);
+        if !self.raised && now.duration_since(since) >= sustain {
+            self.raised = true;
+            return true;
+        }
+        false
+    }
 }
 
-/// Messages sent from main thread to background worker
-#[derive(Debug)]
-pub enum WorkerCommand {
-    Refresh,
-    ControlRunner { runner_index: usize, action: String },
-    Shutdown,
+/// A timestamped entry in the in-memory action log
+#[derive(Debug, Clone)]
+pub struct ActionLogEntry {
+    pub at: Instant,
+    pub message: String,
+}
+
+/// A control action waiting for the user to confirm it
+#[derive(Debug, Clone)]
+pub struct PendingConfirm {
+    pub prompt: String,
+    pub action: String,
+    /// Text typed so far when the action needs `word` spelled out, rather than `y`
+    pub typed: Option<String>,
+    /// What must be typed: `yes`, or the runner's name for changes that can't be undone
+    pub word: String,
+    /// A further confirmation to ask for once this one is given
+    pub next: Option<Box<PendingConfirm>>,
+    /// Runners a bulk action applies to; None means the selected one
+    pub targets: Option<Vec<usize>>,
+    /// First target shown in the dialog's list
+    pub scroll: usize,
+}
+
+/// Word the user must type to confirm an action that aborts a running job
+pub const TYPED_CONFIRMATION: &str = "yes";
+
+/// Space used on one mounted filesystem
+#[derive(Debug, Clone)]
+pub struct DiskUsage {
+    pub mount_point: String,
+    pub used: u64,
+    pub total: u64,
 }
 
-/// Messages sent from background worker to main thread
-#[derive(Debug)]
-pub enum WorkerResponse {
-    RunnersUpdated(Vec<Runner>),
-    ActionComplete { message: String },
+/// Throughput of one network interface since the previous sample
+#[derive(Debug, Clone)]
+pub struct NetworkRate {
+    pub interface: String,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
 }
 
 #[derive(Debug, Clone)]
 pub struct SystemStats {
     pub cpu_usage: f32,
+    /// Usage of each logical CPU, in percent
+    pub cpu_cores: Vec<f32>,
     pub memory_used: u64,
     pub memory_total: u64,
+    pub memory_available: u64,
+    pub swap_used: u64,
+    pub swap_total: u64,
     pub load_avg: [f64; 3],
+    /// Seconds since the host booted
+    pub uptime_secs: u64,
+    /// Boot time as seconds since the Unix epoch
+    pub boot_time: u64,
+    /// Only refreshed while the stats tab is showing
+    pub disks: Vec<DiskUsage>,
+    pub networks: Vec<NetworkRate>,
 }
 
 impl Default for SystemStats {
     fn default() -> Self {
         Self {
             cpu_usage: 0.0,
+            cpu_cores: Vec::new(),
             memory_used: 0,
             memory_total: 1,
+            memory_available: 0,
+            swap_used: 0,
+            swap_total: 0,
             load_avg: [0.0, 0.0, 0.0],
+            uptime_secs: 0,
+            boot_time: 0,
+            disks: Vec::new(),
+            networks: Vec::new(),
         }
     }
 }
 
 pub struct App {
     pub runners: Vec<Runner>,
+    /// Service names shared by several runners; see `duplicate_service_names`
+    pub duplicate_services: HashSet<String>,
     pub selected: usize,
     pub system_stats: SystemStats,
     pub should_quit: bool,
+    pub tab: Tab,
     pub mode: AppMode,
+    /// Mode to return to when help or output closes; also picks which help comes first
+    pub previous_mode: AppMode,
     pub status_message: Option<String>,
+    /// Only runners matching this search text are listed
+    pub filter: String,
+    /// Parse the filter as a regex rather than a glob/substring
+    pub regex_filter: bool,
+    /// Compiled `filter`, or why it couldn't be compiled
+    filter_matcher: Option<std::result::Result<Matcher, String>>,
     pub logs: Vec<String>,
     pub log_scroll: usize,
+    /// Keep the logs view pinned to the newest line as logs grow
+    pub log_follow: bool,
+    /// Text searched for in the logs, case-insensitively
+    pub log_search: String,
+    /// Index in `logs` of the search match being looked at
+    pub log_match: Option<usize>,
+    /// While showing the logs around a match, the matched line; live updates pause
+    pub log_context: Option<String>,
+    /// First help line shown on the help screen
+    pub help_scroll: usize,
+    /// Contents of the output overlay
+    pub output: Option<OutputView>,
+    /// Settings read from each runner directory for the Fleet tab, by path
+    pub agent_settings: HashMap<PathBuf, AgentSettings>,
+    /// First runner column shown in the Fleet tab
+    pub fleet_scroll: usize,
+    /// Label edits being typed, e.g. `gpu -old`
+    pub label_input: String,
+    /// Why the typed label edits can't be applied
+    pub label_error: Option<String>,
+    pub list_density: ListDensity,
+    /// Columns of the verbose list
+    pub list_columns: Vec<ListColumn>,
+    /// Column the list is sorted by, or None for discovery order
+    pub sort_by: Option<ListColumn>,
+    pub sort_descending: bool,
+    /// Per runner directory: usage of its processes, sampled while a verbose
+    /// list column shows it
+    pub runner_usage: HashMap<PathBuf, ProcessUsage>,
+    /// What one job is taken to need for the headroom estimate; a zero field is
+    /// filled in from `observed_job_usage`
+    pub job_usage: ProcessUsage,
+    /// Average usage of a busy runner, as of the last scan that found one
+    pub observed_job_usage: Option<ProcessUsage>,
+    /// When the process table was last walked, to space out job usage samples
+    job_usage_sampled_at: Option<Instant>,
+    pub log_window: LogWindow,
+    pub log_priority: LogPriority,
+    /// `journalctl -o` format the logs are fetched in
+    pub journal_format: JournalFormat,
+    /// Width of the list pane as a percentage of the main area
+    pub list_ratio: u16,
+    /// Whether the details pane shares the Runners tab with the list
+    pub show_details: bool,
+    /// Service names of runners kept at the top of the list
+    pub pinned: HashSet<String>,
+    /// Run control actions on the main thread instead of the worker
+    pub sync_actions: bool,
+    /// Log how long each refresh phase took to the Events tab
+    pub profile: bool,
+    /// Command to run once the event loop can hand over the terminal
+    pub pending_suspend: Option<SuspendRequest>,
+    /// Set when the event loop should refresh now instead of on its timer,
+    /// bypassing cached service states
+    pub refresh_requested: bool,
+    /// Why status or logs are limited on this host, from `runner::missing_tools`
+    pub missing_tools: Vec<String>,
+    /// Whether busy runners can be told from idle ones (pgrep is installed)
+    pub busy_detection: bool,
+    /// Configured command for Ctrl-Z, instead of a plain shell
+    pub suspend_command: Option<String>,
+    /// State changed since the last draw, so the screen needs redrawing
+    pub dirty: bool,
+    /// No input for `idle_after`; refreshes slow down until the next key
+    pub idle: bool,
+    /// Inactivity before going idle, or None to always refresh at full rate
+    pub idle_after: Option<Duration>,
+    pub dim_when_idle: bool,
+    /// Show absolute paths rather than `~/...`
+    pub full_paths: bool,
+    /// Draw with ASCII symbols only
+    pub ascii: bool,
+    /// Ring the terminal bell when a runner fails
+    pub bell: bool,
+    /// Commands run when a runner fails or recovers; see `expand_hook`
+    pub on_failure: Option<String>,
+    pub on_recover: Option<String>,
+    /// Hook threads report how their command exited here
+    hook_tx: Sender<String>,
+    hook_rx: Receiver<String>,
+    /// Set when the bell should ring on the next frame
+    pub ring_bell: bool,
+    /// Per runner directory: when it last rang the bell
+    last_bell: HashMap<PathBuf, Instant>,
+    /// Shade usage bars along their length; ignored in ASCII mode
+    pub gradient_bars: bool,
+    pub cpu_thresholds: Thresholds,
+    /// Also used for swap and disks
+    pub mem_thresholds: Thresholds,
+    /// How long CPU or memory must stay above the alert level to raise an alarm
+    pub alert_after: Duration,
+    pub alert_notify: bool,
+    pub cpu_alarm: Alarm,
+    pub mem_alarm: Alarm,
+    /// Directories runners were discovered under; empty when not known
+    pub runner_roots: Vec<PathBuf>,
+    /// Concurrency and pacing for bulk actions
+    pub bulk_limits: BulkLimits,
+    /// Latest queued run count per repo directory, when a GitHub token is available
+    pub queued_runs: HashMap<String, usize>,
+    /// Per repo directory: how long queued runs have outnumbered idle runners
+    pub backlogs: HashMap<String, Alarm>,
+    pub backlog_after: Duration,
+    /// Queued runs allowed beyond the idle runner count before it counts as a backlog
+    pub backlog_margin: usize,
+    /// Stop runners idle (up, no job) this long; None leaves them running
+    pub reap_after: Option<Duration>,
+    /// Only log the runners the reaper would stop
+    pub reap_dry_run: bool,
+    /// Per runner directory: how long the runner has been idle
+    idle_runners_since: HashMap<PathBuf, Alarm>,
+    /// Per runner directory: until when its row is highlighted for a status change
+    flash_until: HashMap<PathBuf, Instant>,
+    /// Per runner directory: systemd restart counts seen within `FLAPPING_WINDOW`
+    restart_samples: HashMap<PathBuf, VecDeque<(Instant, u32)>>,
+    /// Per runner directory: the control action sent to the worker and not yet finished
+    pending_actions: HashMap<PathBuf, String>,
+    /// Runner directories marked with Space for the next start/stop/restart
+    marked: HashSet<PathBuf>,
+    pub pending_confirm: Option<PendingConfirm>,
+    /// Latest actions/runner release, when a GitHub token is available
+    pub latest_release: Option<String>,
+    /// True between sending a refresh request and receiving updated runners
+    pub refreshing: bool,
+    refresh_requested_at: Instant,
+    /// When runner statuses were last applied from the worker
+    pub last_update: Instant,
+    /// Recent action results and background errors, oldest first
+    pub action_log: VecDeque<ActionLogEntry>,
+    /// Recent CPU usage samples in percent, oldest first
+    pub cpu_history: VecDeque<u64>,
+    /// Recent 1-minute load averages scaled by 100, oldest first
+    pub load_history: VecDeque<u64>,
     system: System,
+    disks: Disks,
+    networks: Networks,
+    host_sampled_at: Instant,
     command_tx: Sender<WorkerCommand>,
     response_rx: Receiver<WorkerResponse>,
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
-        let runners = discover_runners()?;
-        let mut system = System::new_all();
-        system.refresh_all();
+    pub fn new(options: &DiscoveryOptions) -> Result<Self> {
+        let mut runners = discover_runners(options)?;
+        // Discovery leaves status to a batched refresh; show real states from the start
+        refresh_runners(
+            &SystemCommandRunner,
+            &mut runners,
+            &UnitCache::load(&SystemCommandRunner),
+        );
+        let mut app = Self::from_runners(runners)?;
+        app.runner_roots = options.roots()?;
+        // No config file yet means this is the first launch
+        if Config::path().is_some_and(|path| !path.exists()) {
+            app.mode = AppMode::Welcome;
+        }
+        Ok(app)
+    }
 
-        let system_stats = Self::collect_system_stats(&system);
+    /// Build the app around an already discovered runner list and start the worker.
+    pub fn from_runners(runners: Vec<Runner>) -> Result<Self> {
+        let config = Config::load()?;
 
         // Create channels for background worker communication
         let (command_tx, command_rx) = mpsc::channel();
         let (response_tx, response_rx) = mpsc::channel();
 
-        // Spawn background worker thread
-        let runners_clone = runners.clone();
-        std::thread::spawn(move || {
-            worker_thread(runners_clone, command_rx, response_tx);
-        });
+        // Look up the latest published runner release once, and keep an eye on
+        // queued runs, if we can reach GitHub
+        if let Some(token) = github::token() {
+            let repos = github_repos(&runners);
+            if !repos.is_empty() {
+                let queue_tx = response_tx.clone();
+                let interval = Duration::from_secs(config.queue_poll_secs.max(1));
+                let token = token.clone();
+                std::thread::spawn(move || poll_queue_depth(&token, &repos, interval, &queue_tx));
+            }
 
-        Ok(Self {
+            let release_tx = response_tx.clone();
+            std::thread::spawn(move || {
+                if let Ok(version) = github::latest_runner_version(&token) {
+                    let _ = release_tx.send(WorkerResponse::LatestRunnerVersion(version));
+                }
+            });
+        }
+
+        let events = config
+            .event_socket()
+            .map(|path| EventSocket::bind(&path))
+            .transpose()?;
+
+        // Spawn supervised background worker thread
+        spawn_worker(
+            runners.clone(),
+            command_rx,
+            response_tx,
+            Duration::from_secs(config.service_cache_secs),
+            events,
+        )?;
+
+        Ok(Self::configured(runners, config, command_tx, response_rx))
+    }
+
+    /// Build the app for a single rendered frame, as `--once` does: configured
+    /// like `from_runners`, but without the worker, GitHub polling or event socket.
+    pub fn for_render(runners: Vec<Runner>) -> Result<Self> {
+        let config = Config::load()?;
+        let (command_tx, _) = mpsc::channel();
+        let (_, response_rx) = mpsc::channel();
+        Ok(Self::configured(runners, config, command_tx, response_rx))
+    }
+
+    /// The app with host stats sampled and `config` applied, talking to a worker
+    /// over the given channels.
+    fn configured(
+        runners: Vec<Runner>,
+        config: Config,
+        command_tx: Sender<WorkerCommand>,
+        response_rx: Receiver<WorkerResponse>,
+    ) -> Self {
+        let mut system = System::new_all();
+        system.refresh_all();
+        let system_stats = Self::collect_system_stats(&system);
+
+        let mut app = Self::new_with(runners, command_tx, response_rx, system_stats);
+        // Keep the primed System so the first CPU usage sample has a baseline
+        app.system = system;
+        app.disks = Disks::new_with_refreshed_list();
+        app.networks = Networks::new_with_refreshed_list();
+        app.list_ratio = config.list_ratio;
+        app.show_details = config.show_details;
+        app.pinned = config.pinned.iter().cloned().collect();
+        app.list_columns = config.list_columns.clone();
+        app.idle_after =
+            (config.idle_after_secs > 0).then(|| Duration::from_secs(config.idle_after_secs));
+        app.dim_when_idle = config.dim_when_idle;
+        app.full_paths = config.full_paths;
+        app.ascii = config.ascii;
+        app.gradient_bars = config.gradient_bars;
+        app.journal_format = config.journal_format;
+        app.cpu_thresholds = config.cpu_thresholds();
+        app.mem_thresholds = config.mem_thresholds();
+        app.alert_after = Duration::from_secs(config.alert_after_secs);
+        app.alert_notify = config.alert_notify;
+        app.job_usage = ProcessUsage {
+            cpu: config.job_cpu_percent.max(0.0) as f32,
+            memory: config.job_memory_mb.saturating_mul(1024 * 1024),
+            started: 0,
+        };
+        app.backlog_after = Duration::from_secs(config.backlog_after_secs);
+        app.reap_after = (config.reap_idle_after_secs > 0)
+            .then(|| Duration::from_secs(config.reap_idle_after_secs));
+        app.reap_dry_run = config.reap_dry_run;
+        app.backlog_margin = config.backlog_margin;
+        app.bulk_limits = config.bulk_limits();
+        app.suspend_command = config.suspend_command;
+        app.on_failure = config.on_failure;
+        app.on_recover = config.on_recover;
+        app
+    }
+
+    /// Build the app from explicit parts without discovering runners or spawning threads.
+    ///
+    /// Commands go to `command_tx` and worker responses are read from `response_rx`.
+    pub fn new_with(
+        runners: Vec<Runner>,
+        command_tx: Sender<WorkerCommand>,
+        response_rx: Receiver<WorkerResponse>,
+        system_stats: SystemStats,
+    ) -> Self {
+        let defaults = Config::default();
+        let (hook_tx, hook_rx) = mpsc::channel();
+        Self {
+            duplicate_services: duplicate_service_names(&runners),
             runners,
             selected: 0,
             system_stats,
             should_quit: false,
+            tab: Tab::Runners,
             mode: AppMode::Normal,
+            previous_mode: AppMode::Normal,
             status_message: None,
+            filter: String::new(),
+            regex_filter: false,
+            filter_matcher: None,
             logs: Vec::new(),
             log_scroll: 0,
-            system,
+            log_follow: true,
+            log_search: String::new(),
+            log_match: None,
+            log_context: None,
+            help_scroll: 0,
+            output: None,
+            agent_settings: HashMap::new(),
+            fleet_scroll: 0,
+            label_input: String::new(),
+            label_error: None,
+            list_density: ListDensity::Normal,
+            list_columns: defaults.list_columns.clone(),
+            sort_by: None,
+            sort_descending: false,
+            runner_usage: HashMap::new(),
+            log_window: LogWindow::Tail,
+            log_priority: LogPriority::All,
+            journal_format: JournalFormat::default(),
+            list_ratio: defaults.list_ratio,
+            show_details: defaults.show_details,
+            pinned: HashSet::new(),
+            sync_actions: false,
+            profile: false,
+            pending_suspend: None,
+            refresh_requested: false,
+            missing_tools: Vec::new(),
+            busy_detection: true,
+            suspend_command: None,
+            dirty: true,
+            idle: false,
+            idle_after: None,
+            dim_when_idle: false,
+            full_paths: false,
+            ascii: false,
+            bell: false,
+            on_failure: None,
+            on_recover: None,
+            hook_tx,
+            hook_rx,
+            ring_bell: false,
+            last_bell: HashMap::new(),
+            gradient_bars: defaults.gradient_bars,
+            cpu_thresholds: defaults.cpu_thresholds(),
+            mem_thresholds: defaults.mem_thresholds(),
+            alert_after: Duration::from_secs(defaults.alert_after_secs),
+            alert_notify: false,
+            cpu_alarm: Alarm::default(),
+            mem_alarm: Alarm::default(),
+            runner_roots: Vec::new(),
+            bulk_limits: defaults.bulk_limits(),
+            queued_runs: HashMap::new(),
+            backlogs: HashMap::new(),
+            backlog_after: Duration::from_secs(defaults.backlog_after_secs),
+            reap_after: None,
+            reap_dry_run: defaults.reap_dry_run,
+            idle_runners_since: HashMap::new(),
+            flash_until: HashMap::new(),
+            restart_samples: HashMap::new(),
+            job_usage: ProcessUsage::default(),
+            observed_job_usage: None,
+            job_usage_sampled_at: None,
+            pending_actions: HashMap::new(),
+            marked: HashSet::new(),
+            backlog_margin: defaults.backlog_margin,
+            pending_confirm: None,
+            latest_release: None,
+            refreshing: false,
+            refresh_requested_at: Instant::now(),
+            last_update: Instant::now(),
+            action_log: VecDeque::new(),
+            cpu_history: VecDeque::new(),
+            load_history: VecDeque::new(),
+            system: System::new(),
+            disks: Disks::new(),
+            networks: Networks::new(),
+            host_sampled_at: Instant::now(),
             command_tx,
             response_rx,
-        })
+        }
     }
 
     fn collect_system_stats(system: &System) -> SystemStats {
         let load_avg = System::load_average();
         SystemStats {
             cpu_usage: system.global_cpu_usage(),
+            cpu_cores: system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
             memory_used: system.used_memory(),
             memory_total: system.total_memory(),
+            memory_available: system.available_memory(),
+            swap_used: system.used_swap(),
+            swap_total: system.total_swap(),
             load_avg: [load_avg.one, load_avg.five, load_avg.fifteen],
+            uptime_secs: System::uptime(),
+            boot_time: System::boot_time(),
+            disks: Vec::new(),
+            networks: Vec::new(),
+        }
+    }
+
+    const HISTORY_LEN: usize = 120;
+
+    /// Sample host resources and append to the history buffers.
+    fn sample_host(&mut self) {
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+        let mut stats = Self::collect_system_stats(&self.system);
+
+        // Interface counters are deltas since the previous refresh
+        self.networks.refresh();
+        let elapsed = self.host_sampled_at.elapsed().as_secs_f64().max(0.001);
+        self.host_sampled_at = Instant::now();
+        stats.networks = network_rates(&self.networks, elapsed);
+
+        // Disk usage changes slowly and is only shown on the stats tab
+        stats.disks = if self.tab == Tab::Stats {
+            self.disks.refresh();
+            disk_usage(&self.disks)
+        } else {
+            std::mem::take(&mut self.system_stats.disks)
+        };
+
+        // Walking the process table is the expensive part, so only while it's
+        // shown or a running job can teach the headroom estimate what one needs
+        if self.shows_process_usage() || self.learns_job_usage() {
+            self.system.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                ProcessRefreshKind::new()
+                    .with_cpu()
+                    .with_memory()
+                    .with_exe(UpdateKind::OnlyIfNotSet)
+                    .with_cwd(UpdateKind::Always),
+            );
+            let processes = self
+                .system
+                .processes()
+                .values()
+                .filter(|p| p.thread_kind().is_none())
+                .map(|p| ProcessSample {
+                    exe: p.exe(),
+                    cwd: p.cwd(),
+                    cpu: p.cpu_usage(),
+                    memory: p.memory(),
+                    start_time: p.start_time(),
+                });
+            self.runner_usage = usage_by_runner(&self.runners, processes);
+            self.job_usage_sampled_at = Some(Instant::now());
+            if let Some(usage) = average_job_usage(&self.runners, &self.runner_usage) {
+                self.observed_job_usage = Some(usage);
+            }
+        } else {
+            self.runner_usage.clear();
         }
+
+        push_history(&mut self.cpu_history, stats.cpu_usage.round() as u64);
+        push_history(
+            &mut self.load_history,
+            (stats.load_avg[0] * 100.0).round() as u64,
+        );
+        self.system_stats = stats;
+        self.check_alarms(Instant::now());
+    }
+
+    /// Whether the list currently has a column filled in from the process table
+    fn shows_process_usage(&self) -> bool {
+        let shown = self.list_density == ListDensity::Verbose
+            && self.list_columns.iter().any(|c| c.needs_processes());
+        shown || self.sort_by.is_some_and(|c| c.needs_processes())
+    }
+
+    /// Whether a busy runner could fill in a per-job figure left to be observed,
+    /// and the last sample is old enough to take another
+    fn learns_job_usage(&self) -> bool {
+        (self.job_usage.cpu == 0.0 || self.job_usage.memory == 0)
+            && self.runners.iter().any(|r| r.busy)
+            && self
+                .job_usage_sampled_at
+                .is_none_or(|at| at.elapsed() >= JOB_USAGE_SAMPLE_INTERVAL)
+    }
+
+    /// Roughly how many more jobs the host's free CPU and memory could take at
+    /// once, or None until there's a per-job figure to go by.
+    pub fn headroom(&self) -> Option<usize> {
+        let observed = self.observed_job_usage.unwrap_or_default();
+        let per_job = ProcessUsage {
+            cpu: if self.job_usage.cpu > 0.0 {
+                self.job_usage.cpu
+            } else {
+                observed.cpu
+            },
+            memory: if self.job_usage.memory > 0 {
+                self.job_usage.memory
+            } else {
+                observed.memory
+            },
+            started: 0,
+        };
+        headroom(&self.system_stats, per_job)
+    }
+
+    /// Raise CPU/memory alarms that have been over their alert level long enough.
+    fn check_alarms(&mut self, now: Instant) {
+        let stats = &self.system_stats;
+        let cpu = stats.cpu_usage as f64;
+        let mem = memory_percent(stats);
+
+        let mut raised = Vec::new();
+        if self
+            .cpu_alarm
+            .update(cpu > self.cpu_thresholds.alert, self.alert_after, now)
+        {
+            raised.push(format!("Host CPU {:.0}%", cpu));
+        }
+        if self
+            .mem_alarm
+            .update(mem > self.mem_thresholds.alert, self.alert_after, now)
+        {
+            raised.push(format!("Host memory {:.0}%", mem));
+        }
+
+        for alert in raised {
+            let message = format!("{} for {}s", alert, self.alert_after.as_secs());
+            if self.alert_notify {
+                notify_desktop(&message);
+            }
+            self.log_action(message.clone());
+            self.status_message = Some(message);
+            self.dirty = true;
+        }
+    }
+
+    /// Runners in `repo` that are up and not executing a job.
+    pub fn idle_runners(&self, repo: &str) -> usize {
+        self.runners
+            .iter()
+            .filter(|r| r.repo == repo && r.status == RunnerStatus::Active && !r.busy)
+            .count()
+    }
+
+    /// Apply a queue depth sample, flagging the repo once its backlog has lasted `backlog_after`.
+    fn record_queue_depth(&mut self, repo: String, queued: usize, now: Instant) {
+        let idle = self.idle_runners(&repo);
+        let over = queued > idle + self.backlog_margin;
+        if self
+            .backlogs
+            .entry(repo.clone())
+            .or_default()
+            .update(over, self.backlog_after, now)
+        {
+            let message = format!(
+                "{}: {} queued runs for {} idle runners for {}s",
+                repo,
+                queued,
+                idle,
+                self.backlog_after.as_secs()
+            );
+            self.log_action(message.clone());
+            self.status_message = Some(message);
+        }
+        self.queued_runs.insert(repo, queued);
+        self.dirty = true;
+    }
+
+    /// Stop runners that have been up without a job for `reap_after`, or in a dry
+    /// run only log which ones would be stopped. Each runner is acted on once per
+    /// idle stretch; it has to pick up a job or stop before it counts again.
+    fn reap_idle_runners(&mut self, now: Instant) {
+        let Some(after) = self.reap_after else {
+            return;
+        };
+        // Every runner would look idle, including ones in the middle of a job
+        if !self.busy_detection {
+            self.reap_after = None;
+            self.log_action(
+                "Idle runner reaping is off: pgrep not found, so busy runners can't be told \
+                 from idle ones"
+                    .to_string(),
+            );
+            return;
+        }
+        let mut reap = Vec::new();
+        for (index, runner) in self.runners.iter().enumerate() {
+            let idle = runner.status == RunnerStatus::Active && !runner.busy;
+            if self
+                .idle_runners_since
+                .entry(runner.path.clone())
+                .or_default()
+                .update(idle, after, now)
+            {
+                reap.push(index);
+            }
+        }
+        let listed: HashSet<&PathBuf> = self.runners.iter().map(|r| &r.path).collect();
+        self.idle_runners_since
+            .retain(|path, _| listed.contains(path));
+
+        for index in reap {
+            let name = self.runners[index].display_name();
+            let idle_for = format!("{}s", after.as_secs());
+            if self.reap_dry_run {
+                self.log_action(format!(
+                    "Idle for {}: would stop {} (reap_dry_run is on)",
+                    idle_for, name
+                ));
+                continue;
+            }
+            let command = WorkerCommand::ControlRunner {
+                runner_index: index,
+                action: "stop".to_string(),
+            };
+            let message = if self.command_tx.send(command).is_ok() {
+                self.mark_pending(index, "stop");
+                format!("Idle for {}: stopping {}", idle_for, name)
+            } else {
+                format!(
+                    "Idle for {}: could not stop {}, worker unavailable",
+                    idle_for, name
+                )
+            };
+            self.log_action(message.clone());
+            self.status_message = Some(message);
+        }
+    }
+
+    /// Whether `repo` has had more queued runs than idle runners for `backlog_after`.
+    pub fn is_backlogged(&self, repo: &str) -> bool {
+        self.backlogs.get(repo).is_some_and(|alarm| alarm.raised)
+    }
+
+    /// Whether CPU or memory has been over its alert level for `alert_after`.
+    pub fn alarm_raised(&self) -> bool {
+        self.cpu_alarm.raised || self.mem_alarm.raised
+    }
+
+    /// How long to wait between refreshes, depending on whether anyone is watching
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_millis(if self.idle {
+            IDLE_REFRESH_INTERVAL_MS
+        } else {
+            REFRESH_INTERVAL_MS
+        })
+    }
+
+    /// Update `idle` from the time since the last input. Returns true when waking up.
+    pub fn update_idle(&mut self, since_input: Duration) -> bool {
+        let was_idle = self.idle;
+        self.idle = self.idle_after.is_some_and(|after| since_input >= after);
+        self.dirty |= was_idle != self.idle;
+        was_idle && !self.idle
     }
 
     /// Request a background refresh of runner statuses.
-    pub fn refresh(&mut self) {
+    ///
+    /// `force` has the worker query the service backend even if its cached
+    /// states are still fresh.
+    pub fn refresh(&mut self, force: bool) {
+        self.dirty = true;
+
         // Send refresh command to background worker (non-blocking)
-        if self.command_tx.send(WorkerCommand::Refresh).is_err() {
+        if self
+            .command_tx
+            .send(WorkerCommand::Refresh { force })
+            .is_err()
+        {
             self.status_message = Some("Warning: Worker thread unavailable".to_string());
+        } else if !self.refreshing {
+            self.refreshing = true;
+            self.refresh_requested_at = Instant::now();
         }
 
         // Refresh system stats (lightweight operation)
-        self.system.refresh_cpu_usage();
-        self.system.refresh_memory();
-        self.system_stats = Self::collect_system_stats(&self.system);
+        self.sample_host();
 
-        // Refresh logs if in log mode (file I/O, could be optimized later)
-        if self.mode == AppMode::Logs {
+        // Refresh logs if the logs tab is showing (file I/O, could be optimized later)
+        if self.tab == Tab::Logs {
             self.refresh_logs();
         }
     }
 
+    /// Ask the event loop for a refresh right away, e.g. after starting a runner
+    /// from another terminal.
+    pub fn request_refresh(&mut self) {
+        self.refresh_requested = true;
+        self.status_message = Some("Refreshing…".to_string());
+    }
+
     /// Poll for updates from the background worker (non-blocking).
     pub fn poll_worker_updates(&mut self) {
+        while let Ok(message) = self.hook_rx.try_recv() {
+            self.log_action(message);
+            self.dirty = true;
+        }
         loop {
-            match self.response_rx.try_recv() {
+            let response = self.response_rx.try_recv();
+            if !matches!(response, Err(TryRecvError::Empty)) {
+                self.dirty = true;
+            }
+            match response {
                 Ok(WorkerResponse::RunnersUpdated(updated_runners)) => {
+                    self.note_status_changes(&updated_runners, Instant::now());
                     // Update runners while preserving selection
                     self.runners = updated_runners;
+                    self.duplicate_services = duplicate_service_names(&self.runners);
+                    self.refreshing = false;
+                    self.last_update = Instant::now();
                     // Ensure selection is still valid
-                    if self.selected >= self.runners.len() && !self.runners.is_empty() {
-                        self.selected = self.runners.len() - 1;
+                    if self.selected >= self.runners.len() {
+                        self.selected = self.runners.len().saturating_sub(1);
                     }
+                    self.ensure_selection_visible();
+                    let listed: HashSet<&PathBuf> = self.runners.iter().map(|r| &r.path).collect();
+                    self.pending_actions.retain(|path, _| listed.contains(path));
+                    self.marked.retain(|path| listed.contains(path));
+                    if self.tab == Tab::Fleet {
+                        // Picks up runners discovered since, and reconfigured ones
+                        self.load_agent_settings();
+                    }
+                    self.reap_idle_runners(Instant::now());
                 }
-                Ok(WorkerResponse::ActionComplete { message }) => {
+                Ok(WorkerResponse::ActionComplete { message, failure }) => {
+                    self.log_action(message.clone());
                     self.status_message = Some(message);
+                    if let Some(failure) = failure {
+                        self.show_failure(&failure);
+                    }
+                }
+                Ok(WorkerResponse::WorkerRestarted { error }) => {
+                    // Actions in flight report to the worker that just died
+                    self.pending_actions.clear();
+                    self.log_action(format!("Worker panicked: {}", error));
+                    self.status_message = Some("Worker restarted after error".to_string());
+                }
+                Ok(WorkerResponse::ActionFinished(path)) => {
+                    self.pending_actions.remove(&path);
+                }
+                Ok(WorkerResponse::RefreshTimed(timings)) => {
+                    if self.profile {
+                        self.log_action(timings.describe());
+                    }
+                }
+                Ok(WorkerResponse::LatestRunnerVersion(version)) => {
+                    self.latest_release = Some(version);
+                }
+                Ok(WorkerResponse::QueueDepth { repo, queued }) => {
+                    self.record_queue_depth(repo, queued, Instant::now());
+                }
+                Ok(WorkerResponse::LabelsEdited { runner, result }) => {
+                    self.labels_edited(runner, result);
+                }
+                Ok(WorkerResponse::Probed { runner, report }) => {
+                    self.probed(runner, report);
                 }
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => {
+                    self.refreshing = false;
                     self.status_message =
                         Some("ERROR: Background worker crashed. Data may be stale.".to_string());
                     break;
@@ -146,178 +1146,3040 @@ impl App {
         }
     }
 
+    /// Returns true if a refresh request has gone unanswered for several refresh intervals.
+    ///
+    /// Measured from the request rather than the last update, so waking from idle
+    /// with old data isn't mistaken for a stall.
+    pub fn is_refresh_stalled(&self) -> bool {
+        self.refreshing
+            && self.refresh_requested_at.elapsed() > self.refresh_interval() * STALL_INTERVALS
+    }
+
+    const SPINNER_INTERVAL_MS: u128 = 100;
+
+    /// Index into the spinner animation, advancing while a refresh is in flight.
+    pub fn spinner_frame(&self) -> usize {
+        (self.refresh_requested_at.elapsed().as_millis() / Self::SPINNER_INTERVAL_MS) as usize
+    }
+
+    const ACTION_LOG_CAPACITY: usize = 200;
+
+    /// Append a message to the action log, dropping the oldest entries past capacity.
+    pub fn log_action(&mut self, message: String) {
+        if self.action_log.len() >= Self::ACTION_LOG_CAPACITY {
+            self.action_log.pop_front();
+        }
+        self.action_log.push_back(ActionLogEntry {
+            at: Instant::now(),
+            message,
+        });
+    }
+
     const LOG_LINES: usize = 100;
+    /// Upper bound on lines kept when logs are limited by time instead
+    const LOG_WINDOW_MAX_LINES: usize = 5000;
 
     pub fn refresh_logs(&mut self) {
+        if self.log_context.is_some() {
+            return;
+        }
+        let (lines, since) = match self.log_window.since() {
+            Some(since) => (Self::LOG_WINDOW_MAX_LINES, Some(since)),
+            None => (Self::LOG_LINES, None),
+        };
         if let Some(runner) = self.selected_runner() {
-            if let Ok(logs) = get_runner_logs(runner, Self::LOG_LINES) {
-                self.logs = logs;
+            if let Ok(logs) =
+                get_runner_logs(runner, lines, since, self.log_priority, self.journal_format)
+            {
+                self.apply_logs(logs);
             }
         }
     }
 
-    pub fn selected_runner(&self) -> Option<&Runner> {
-        self.runners.get(self.selected)
+    /// Replace the log lines, keeping the view on the same content unless following.
+    fn apply_logs(&mut self, logs: Vec<String>) {
+        let last_line = logs.len().saturating_sub(1);
+        // The tail window drops old lines off the top as new ones arrive
+        let dropped = log_lines_dropped(&self.logs, &logs);
+        self.log_scroll = if self.log_follow {
+            last_line
+        } else {
+            self.log_scroll
+                .saturating_sub(dropped.unwrap_or(0))
+                .min(last_line)
+        };
+        self.log_match = match (self.log_match, dropped) {
+            (Some(index), Some(dropped)) => index.checked_sub(dropped),
+            _ => None,
+        };
+        self.logs = logs;
     }
 
-    pub fn select_next(&mut self) {
-        if !self.runners.is_empty() {
-            self.selected = (self.selected + 1) % self.runners.len();
-        }
+    /// Re-read logs immediately instead of waiting for the next refresh tick.
+    pub fn reload_logs(&mut self) {
+        self.log_context = None;
+        self.refresh_logs();
+        self.status_message = Some("Logs refreshed".to_string());
     }
 
-    pub fn select_previous(&mut self) {
-        if !self.runners.is_empty() {
-            self.selected = self
-                .selected
-                .checked_sub(1)
-                .unwrap_or(self.runners.len() - 1);
+    /// The version runners are compared against: the newest in the fleet or on GitHub.
+    pub fn reference_version(&self) -> Option<&str> {
+        match (
+            newest_version(&self.runners),
+            self.latest_release.as_deref(),
+        ) {
+            (Some(fleet), Some(release)) => Some(max_version(fleet, release)),
+            (fleet, release) => fleet.or(release),
         }
     }
 
-    pub fn scroll_logs_up(&mut self) {
-        self.log_scroll = self.log_scroll.saturating_sub(1);
+    /// Returns true if the runner is far enough behind the reference version to need updating.
+    pub fn is_outdated(&self, runner: &Runner) -> bool {
+        match (runner.version.as_deref(), self.reference_version()) {
+            (Some(version), Some(reference)) => is_version_outdated(version, reference),
+            _ => false,
+        }
     }
 
-    pub fn scroll_logs_down(&mut self) {
-        if self.log_scroll < self.logs.len().saturating_sub(1) {
-            self.log_scroll += 1;
-        }
+    /// Whether another runner resolves to the same service as `runner`
+    pub fn has_duplicate_service(&self, runner: &Runner) -> bool {
+        self.duplicate_services.contains(&runner.service_name)
     }
 
-    pub fn start_selected(&mut self) {
-        self.control_selected_runner("start");
+    /// Status bar warning when some runners share a service name
+    pub fn duplicate_service_warning(&self) -> Option<String> {
+        let affected = self
+            .runners
+            .iter()
+            .filter(|r| self.has_duplicate_service(r))
+            .count();
+        (affected > 0).then(|| {
+            format!(
+                "Warning: {} runners share a service name; actions on them are ambiguous",
+                affected
+            )
+        })
     }
 
-    pub fn stop_selected(&mut self) {
-        self.control_selected_runner("stop");
+    /// Status bar warning when host tools the status or logs come from are missing
+    pub fn missing_tools_warning(&self) -> Option<String> {
+        (!self.missing_tools.is_empty())
+            .then(|| format!("Warning: {}", self.missing_tools.join("; ")))
     }
 
-    pub fn restart_selected(&mut self) {
-        self.control_selected_runner("restart");
+    /// Selected runner, unless the filter currently hides it
+    pub fn selected_runner(&self) -> Option<&Runner> {
+        self.runners
+            .get(self.selected)
+            .filter(|r| self.matches_filter(r))
     }
 
-    fn control_selected_runner(&mut self, action: &str) {
-        // Show pending status immediately
-        let mut capitalized = action.to_string();
-        if let Some(first) = capitalized.get_mut(0..1) {
-            first.make_ascii_uppercase();
+    fn matches_filter(&self, runner: &Runner) -> bool {
+        match &self.filter_matcher {
+            None => true,
+            Some(Ok(matcher)) => matcher.matches(runner),
+            Some(Err(_)) => false,
         }
+    }
 
-        // Send command to background worker
-        if self
-            .command_tx
-            .send(WorkerCommand::ControlRunner {
-                runner_index: self.selected,
-                action: action.to_string(),
-            })
-            .is_err()
-        {
-            self.status_message = Some("Error: Worker thread unavailable".to_string());
-            return;
+    /// Why the current filter can't be used, e.g. an unfinished regex
+    pub fn filter_error(&self) -> Option<&str> {
+        match &self.filter_matcher {
+            Some(Err(error)) => Some(error),
+            _ => None,
         }
-
-        self.status_message = Some(format!("{}ing runner...", capitalized));
     }
 
-    pub fn toggle_logs(&mut self) {
-        if self.mode == AppMode::Logs {
-            self.mode = AppMode::Normal;
-            self.logs.clear();
-            self.log_scroll = 0;
+    fn update_filter(&mut self) {
+        self.filter_matcher = if self.filter.is_empty() {
+            None
         } else {
-            self.mode = AppMode::Logs;
-            self.refresh_logs();
-            // Scroll to bottom
-            self.log_scroll = self.logs.len().saturating_sub(1);
-        }
+            Some(Matcher::search(&self.filter, self.regex_filter).map_err(|e| format!("{:#}", e)))
+        };
+        self.ensure_selection_visible();
     }
 
-    pub fn toggle_help(&mut self) {
-        self.mode = if self.mode == AppMode::Help {
-            AppMode::Normal
+    /// Indices of the runners that pass the filter, pinned ones first, otherwise
+    /// in sort order, or list order when unsorted
+    pub fn visible_runners(&self) -> Vec<usize> {
+        let mut visible: Vec<usize> = (0..self.runners.len())
+            .filter(|&i| self.matches_filter(&self.runners[i]))
+            .collect();
+        if let Some(column) = self.sort_by {
+            visible.sort_by(|&a, &b| {
+                let order = self.compare_by(column, &self.runners[a], &self.runners[b]);
+                if self.sort_descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+        }
+        // Stable, so pinned and unpinned runners each keep their order
+        visible.sort_by_key(|&i| !self.is_pinned(&self.runners[i]));
+        visible
+    }
+
+    pub fn is_pinned(&self, runner: &Runner) -> bool {
+        self.pinned.contains(&runner.service_name)
+    }
+
+    /// Pin the selected runner to the top of the list, or unpin it, and save the pins.
+    pub fn toggle_pin_selected(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            self.status_message = Some("No runner selected".to_string());
+            return;
+        };
+        let name = runner.display_name();
+        let service = runner.service_name.clone();
+        let pinned = if self.pinned.remove(&service) {
+            false
         } else {
-            AppMode::Help
+            self.pinned.insert(service);
+            true
         };
+
+        let mut saved: Vec<String> = self.pinned.iter().cloned().collect();
+        saved.sort_unstable();
+        self.status_message = Some(match Config::update(|c| c.pinned = saved) {
+            Ok(()) if pinned => format!("Pinned {}", name),
+            Ok(()) => format!("Unpinned {}", name),
+            Err(e) => format!("Error: could not save pins: {}", e),
+        });
     }
 
-    pub fn counts(&self) -> (usize, usize, usize) {
-        let active = self
-            .runners
-            .iter()
-            .filter(|r| r.status == RunnerStatus::Active)
-            .count();
-        let failed = self
-            .runners
-            .iter()
-            .filter(|r| r.status == RunnerStatus::Failed)
-            .count();
-        let total = self.runners.len();
-        (active, failed, total)
+    /// Move the selection to the first listed runner if the filter hides it.
+    fn ensure_selection_visible(&mut self) {
+        if self.selected_runner().is_none() {
+            if let Some(&first) = self.visible_runners().first() {
+                self.selected = first;
+            }
+        }
     }
-}
 
-impl Drop for App {
-    fn drop(&mut self) {
-        // Signal worker thread to shutdown
-        let _ = self.command_tx.send(WorkerCommand::Shutdown);
+    pub fn select_next(&mut self) {
+        let visible = self.visible_runners();
+        if !visible.is_empty() {
+            let next = match visible.iter().position(|&i| i == self.selected) {
+                Some(pos) => (pos + 1) % visible.len(),
+                None => 0,
+            };
+            self.selected = visible[next];
+        }
+        self.scan_work_selected();
     }
-}
 
-/// Background worker thread that handles runner refresh and control operations.
-fn worker_thread(
-    mut runners: Vec<Runner>,
-    command_rx: Receiver<WorkerCommand>,
-    response_tx: Sender<WorkerResponse>,
-) {
-    use std::time::Duration;
+    pub fn select_previous(&mut self) {
+        let visible = self.visible_runners();
+        if !visible.is_empty() {
+            let previous = match visible.iter().position(|&i| i == self.selected) {
+                Some(pos) => pos.checked_sub(1).unwrap_or(visible.len() - 1),
+                None => visible.len() - 1,
+            };
+            self.selected = visible[previous];
+        }
+        self.scan_work_selected();
+    }
 
-    loop {
-        // Wait for command with timeout to allow periodic refresh
-        match command_rx.recv_timeout(Duration::from_millis(100)) {
-            Ok(WorkerCommand::Refresh) => {
-                // Refresh all runners
-                refresh_runners(&mut runners);
+    /// Highlight runners whose status differs from the last refresh, ring the
+    /// bell for those that just failed, and run the failure and recovery hooks.
+    fn note_status_changes(&mut self, updated: &[Runner], now: Instant) {
+        self.record_restarts(updated, now);
+        self.flash_until.retain(|_, until| *until > now);
+        self.last_bell
+            .retain(|_, rang| now.duration_since(*rang) < BELL_DEBOUNCE);
+        for runner in updated {
+            let Some(before) = self
+                .runners
+                .iter()
+                .find(|r| r.path == runner.path)
+                .map(|r| r.status.clone())
+            else {
+                continue;
+            };
+            if before == runner.status {
+                continue;
+            }
+            if runner.status == RunnerStatus::Failed {
+                self.run_hook("on_failure", self.on_failure.clone(), runner);
+            } else if before == RunnerStatus::Failed {
+                self.run_hook("on_recover", self.on_recover.clone(), runner);
+            }
+            self.flash_until
+                .insert(runner.path.clone(), now + STATUS_FLASH);
+            if self.bell
+                && runner.status == RunnerStatus::Failed
+                && !self.last_bell.contains_key(&runner.path)
+            {
+                self.last_bell.insert(runner.path.clone(), now);
+                self.ring_bell = true;
+            }
+        }
+    }
 
-                // Send updated runners back to main thread
-                let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
+    /// Add each runner's systemd restart count to its history, noting runners that
+    /// just started flapping.
+    ///
+    /// The count resets when the unit is started by hand, so a drop starts over.
+    fn record_restarts(&mut self, updated: &[Runner], now: Instant) {
+        let listed: HashSet<&PathBuf> = updated.iter().map(|r| &r.path).collect();
+        self.restart_samples.retain(|path, _| listed.contains(path));
+        for runner in updated {
+            let Some(count) = runner.service_state.as_ref().and_then(|s| s.restarts) else {
+                self.restart_samples.remove(&runner.path);
+                continue;
+            };
+            let was_flapping = self.is_flapping(runner);
+            let samples = self.restart_samples.entry(runner.path.clone()).or_default();
+            if samples.back().is_some_and(|&(_, last)| count < last) {
+                samples.clear();
             }
-            Ok(WorkerCommand::ControlRunner {
-                runner_index,
-                action,
-            }) => {
-                // Execute control action with bounds checking
-                let message = if let Some(runner) = runners.get(runner_index).cloned() {
-                    match control_runner(&runner, &action) {
-                        Ok(msg) => msg,
-                        Err(e) => format!("Error: {}", e),
-                    }
+            samples.push_back((now, count));
+            // Keep one sample from before the window to measure across all of it
+            while samples
+                .get(1)
+                .is_some_and(|&(at, _)| now.duration_since(at) >= FLAPPING_WINDOW)
+            {
+                samples.pop_front();
+            }
+            if !was_flapping && self.is_flapping(runner) {
+                self.log_action(format!(
+                    "{} is flapping: systemd restarted it {} times in {} minutes",
+                    runner.display_name(),
+                    self.recent_restarts(runner),
+                    FLAPPING_WINDOW.as_secs() / 60
+                ));
+            }
+        }
+    }
+
+    /// Automatic restarts of `runner` seen within `FLAPPING_WINDOW`
+    pub fn recent_restarts(&self, runner: &Runner) -> u32 {
+        match self.restart_samples.get(&runner.path) {
+            Some(samples) => match (samples.front(), samples.back()) {
+                (Some(&(_, first)), Some(&(_, last))) => last - first,
+                _ => 0,
+            },
+            None => 0,
+        }
+    }
+
+    /// Restarts per hour over the tracked history, once it spans some time.
+    pub fn restart_rate(&self, runner: &Runner) -> Option<f64> {
+        let samples = self.restart_samples.get(&runner.path)?;
+        let (&(first_at, _), &(last_at, _)) = (samples.front()?, samples.back()?);
+        let span = last_at.duration_since(first_at).as_secs_f64();
+        (span > 0.0).then(|| self.recent_restarts(runner) as f64 * 3600.0 / span)
+    }
+
+    /// systemd keeps restarting the runner's unit: it looks active between crashes,
+    /// so its status alone hides the problem.
+    pub fn is_flapping(&self, runner: &Runner) -> bool {
+        self.recent_restarts(runner) >= FLAPPING_RESTARTS
+    }
+
+    /// Run a status hook for `runner` on its own thread, logging how it exited.
+    ///
+    /// The command's output is discarded so it can't draw over the TUI.
+    fn run_hook(&mut self, name: &str, template: Option<String>, runner: &Runner) {
+        let Some(template) = template else {
+            return;
+        };
+        let line = expand_hook(&template, runner);
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(&line)
+            .env("RUNNER_NAME", runner.display_name())
+            .env("RUNNER_DIR", &runner.path)
+            .env("RUNNER_SERVICE", &runner.service_name)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        let label = format!("{} hook for {}/{}", name, runner.repo, runner.name);
+        let hook_tx = self.hook_tx.clone();
+        let reported = label.clone();
+        let spawned = std::thread::Builder::new()
+            .name(HOOK_THREAD_NAME.to_string())
+            .spawn(move || {
+                let message = match command.status() {
+                    Ok(status) if status.success() => format!("{} exited with 0", reported),
+                    Ok(status) => format!("Error: {} {}", reported, status),
+                    Err(e) => format!("Error: could not run {}: {}", reported, e),
+                };
+                let _ = hook_tx.send(message);
+            });
+        if let Err(e) = spawned {
+            self.log_action(format!("Error: could not start {}: {}", label, e));
+        }
+    }
+
+    /// True for a short while after the runner's status changed
+    pub fn is_flashing(&self, runner: &Runner) -> bool {
+        self.flash_until
+            .get(&runner.path)
+            .is_some_and(|until| *until > Instant::now())
+    }
+
+    /// Jump to the next failed or missing runner in the list, wrapping around.
+    pub fn select_next_problem(&mut self) {
+        self.select_problem(true);
+    }
+
+    /// Jump to the previous failed or missing runner in the list, wrapping around.
+    pub fn select_previous_problem(&mut self) {
+        self.select_problem(false);
+    }
+
+    fn select_problem(&mut self, forwards: bool) {
+        let visible = self.visible_runners();
+        let count = visible.len();
+        // Walk the list from the selection, wrapping around, ending back on the selection
+        let current = visible.iter().position(|&i| i == self.selected);
+        let start = current.unwrap_or(if forwards { count.saturating_sub(1) } else { 0 });
+        let target = (1..=count)
+            .map(|step| {
+                if forwards {
+                    visible[(start + step) % count]
+                } else {
+                    visible[(start + count - step) % count]
+                }
+            })
+            .find(|&i| {
+                matches!(
+                    self.runners[i].status,
+                    RunnerStatus::Failed | RunnerStatus::NotFound
+                )
+            });
+        match target {
+            Some(i) => {
+                self.selected = i;
+                self.scan_work_selected();
+            }
+            None => {
+                self.status_message = Some("No failed or missing runners in the list".to_string())
+            }
+        }
+    }
+
+    /// Start typing a list filter, keeping the current text for editing.
+    pub fn start_filter(&mut self) {
+        self.mode = AppMode::Filter;
+    }
+
+    pub fn push_filter_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.update_filter();
+    }
+
+    pub fn pop_filter_char(&mut self) {
+        self.filter.pop();
+        self.update_filter();
+    }
+
+    /// Keep the filter and return to the list.
+    pub fn accept_filter(&mut self) {
+        self.mode = AppMode::Normal;
+    }
+
+    /// Drop the filter and return to the list.
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.update_filter();
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn start_log_search(&mut self) {
+        self.log_search.clear();
+        self.log_match = None;
+        self.mode = AppMode::LogSearch;
+    }
+
+    /// Add to the search, jumping to the first match from the top of the view.
+    pub fn push_log_search_char(&mut self, c: char) {
+        self.log_search.push(c);
+        self.log_match = None;
+        self.jump_to_log_match(self.log_scroll, true);
+    }
+
+    pub fn pop_log_search_char(&mut self) {
+        self.log_search.pop();
+        self.log_match = None;
+        self.jump_to_log_match(self.log_scroll, true);
+    }
+
+    /// Keep the search and return to the logs; `n`/`N` step through the matches.
+    pub fn accept_log_search(&mut self) {
+        self.mode = AppMode::Normal;
+        if !self.log_search.is_empty() && self.log_match.is_none() {
+            self.status_message = Some(format!("No logs match \"{}\"", self.log_search));
+        }
+    }
+
+    pub fn cancel_log_search(&mut self) {
+        self.log_search.clear();
+        self.log_match = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Whether `line` contains the log search text
+    pub fn is_log_match(&self, line: &str) -> bool {
+        !self.log_search.is_empty()
+            && line
+                .to_lowercase()
+                .contains(&self.log_search.to_lowercase())
+    }
+
+    pub fn next_log_match(&mut self) {
+        let from = self.log_match.map_or(self.log_scroll, |i| i + 1);
+        self.jump_to_log_match(from, true);
+    }
+
+    pub fn previous_log_match(&mut self) {
+        let from = self
+            .log_match
+            .unwrap_or(self.log_scroll)
+            .checked_sub(1)
+            .unwrap_or(self.logs.len().saturating_sub(1));
+        self.jump_to_log_match(from, false);
+    }
+
+    /// Select the nearest match from `from`, searching forwards or backwards and
+    /// wrapping around, and scroll it to the top of the view.
+    fn jump_to_log_match(&mut self, from: usize, forwards: bool) {
+        if self.log_search.is_empty() || self.logs.is_empty() {
+            return;
+        }
+        let len = self.logs.len();
+        let from = from % len;
+        let found = (0..len)
+            .map(|step| {
+                if forwards {
+                    (from + step) % len
                 } else {
-                    format!(
-                        "Error: Runner index {} out of bounds (have {} runners)",
-                        runner_index,
-                        runners.len()
-                    )
+                    (from + len - step) % len
+                }
+            })
+            .find(|&i| self.is_log_match(&self.logs[i]));
+        if let Some(index) = found {
+            self.log_match = Some(index);
+            self.log_scroll = index;
+            self.log_follow = false;
+        }
+    }
+
+    /// Fetch a wider window of logs around the current match, or go back to live logs.
+    pub fn expand_log_context(&mut self) {
+        if self.log_context.take().is_some() {
+            self.log_match = None;
+            self.log_follow = true;
+            self.refresh_logs();
+            self.status_message = Some("Back to live logs".to_string());
+            return;
+        }
+        let Some(line) = self.log_match.and_then(|i| self.logs.get(i)).cloned() else {
+            self.status_message = Some("Search the logs with / first".to_string());
+            return;
+        };
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        match get_runner_log_context(runner, &line, self.log_priority, self.journal_format) {
+            Ok(logs) => self.show_log_context(line, logs),
+            Err(e) => self.status_message = Some(format!("Error: {}", e)),
+        }
+    }
+
+    /// Replace the logs with `logs`, fetched around the matched `line`, and hold them.
+    fn show_log_context(&mut self, line: String, logs: Vec<String>) {
+        let at = logs.iter().position(|l| *l == line);
+        self.status_message = Some(format!(
+            "Showing {} lines around the match; e returns to live logs",
+            logs.len()
+        ));
+        self.logs = logs;
+        self.log_match = at;
+        // Leave some lead-in above the match
+        self.log_scroll = at.map_or(0, |i| i.saturating_sub(LOG_CONTEXT_LEAD_IN));
+        self.log_follow = false;
+        self.log_context = Some(line);
+    }
+
+    pub fn scroll_logs_up(&mut self) {
+        self.log_scroll = self.log_scroll.saturating_sub(1);
+        self.log_follow = false;
+    }
+
+    pub fn scroll_logs_down(&mut self) {
+        let last_line = self.logs.len().saturating_sub(1);
+        if self.log_scroll < last_line {
+            self.log_scroll += 1;
+        }
+        // Scrolling back to the bottom resumes following
+        self.log_follow = self.log_scroll >= last_line;
+    }
+
+    pub fn start_selected(&mut self) {
+        if !self.control_marked("start") {
+            self.control_selected_runner("start");
+        }
+    }
+
+    pub fn stop_selected(&mut self) {
+        if !self.control_marked("stop") && !self.confirm_if_busy("stop") {
+            self.control_selected_runner("stop");
+        }
+    }
+
+    pub fn restart_selected(&mut self) {
+        if !self.control_marked("restart") && !self.confirm_if_busy("restart") {
+            self.control_selected_runner("restart");
+        }
+    }
+
+    pub fn install_selected(&mut self) {
+        self.control_selected_runner("install");
+    }
+
+    pub fn uninstall_selected(&mut self) {
+        if self.confirm_if_busy("uninstall") {
+            return;
+        }
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let prompt = format!(
+            "Uninstall the service for {}? The runner will be stopped.",
+            runner.display_name()
+        );
+        self.request_confirm(prompt, "uninstall");
+    }
+
+    /// Empty the selected runner's work folder after confirming; the runner must be stopped.
+    pub fn prune_work_selected(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let name = runner.display_name();
+        if let Some(reason) = prune_blocker(runner) {
+            self.status_message = Some(format!("Cannot prune {}: {}", name, reason));
+            return;
+        }
+        let size = match runner.work.as_ref() {
+            Some(work) => format!(" ({} in job directories)", format_size(work.bytes)),
+            None => String::new(),
+        };
+        let prompt = format!("Delete everything in the work folder of {}{}?", name, size);
+        self.request_confirm(prompt, PRUNE_WORK_ACTION);
+    }
+
+    /// Deregister the selected runner from GitHub with `config.sh remove`.
+    ///
+    /// This can't be undone from here, so it asks twice, the second time for the
+    /// runner's name, and only for a stopped runner whose service is uninstalled.
+    pub fn deregister_selected(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let name = runner.display_name();
+        if let Some(reason) = deregister_blocker(runner) {
+            self.status_message = Some(format!("Cannot deregister {}: {}", name, reason));
+            return;
+        }
+
+        let retype = PendingConfirm {
+            prompt: format!(
+                "This removes {} from GitHub for good; it needs a new registration token \
+                 to come back.",
+                name
+            ),
+            action: "deregister".to_string(),
+            typed: Some(String::new()),
+            word: name.clone(),
+            next: None,
+            targets: None,
+            scroll: 0,
+        };
+        self.pending_confirm = Some(PendingConfirm {
+            prompt: format!("Deregister {} from GitHub with config.sh remove?", name),
+            action: "deregister".to_string(),
+            typed: None,
+            word: TYPED_CONFIRMATION.to_string(),
+            next: Some(Box::new(retype)),
+            targets: None,
+            scroll: 0,
+        });
+        self.mode = AppMode::Confirm;
+    }
+
+    /// Restart every failed runner in the list, a wave at a time, after confirming.
+    pub fn restart_failed(&mut self) {
+        let failed: Vec<usize> = self
+            .visible_runners()
+            .into_iter()
+            .filter(|&i| self.runners[i].status == RunnerStatus::Failed)
+            .collect();
+        if failed.is_empty() {
+            self.status_message = Some("No failed runners to restart".to_string());
+            return;
+        }
+        let prompt = format!(
+            "Restart {} failed runner(s), {} at a time?",
+            failed.len(),
+            self.bulk_limits.concurrency.max(1)
+        );
+        self.request_confirm(prompt, "restart");
+        if let Some(pending) = self.pending_confirm.as_mut() {
+            pending.targets = Some(failed);
+        }
+    }
+
+    /// Mark the selected runner for the next start/stop/restart, or unmark it.
+    pub fn toggle_mark_selected(&mut self) {
+        let Some(path) = self.selected_runner().map(|r| r.path.clone()) else {
+            return;
+        };
+        if !self.marked.remove(&path) {
+            self.marked.insert(path);
+        }
+        self.status_message = Some(match self.marked.len() {
+            0 => "No runners marked".to_string(),
+            count => format!("{} marked: s/x/r act on all of them, Esc clears", count),
+        });
+    }
+
+    pub fn clear_marks(&mut self) {
+        if !self.marked.is_empty() {
+            self.marked.clear();
+            self.status_message = Some("Marks cleared".to_string());
+        }
+    }
+
+    pub fn is_marked(&self, runner: &Runner) -> bool {
+        self.marked.contains(&runner.path)
+    }
+
+    pub fn marked_count(&self) -> usize {
+        self.marked.len()
+    }
+
+    /// Ask to apply `action` to every marked runner. False when none are marked.
+    fn control_marked(&mut self, action: &str) -> bool {
+        let targets: Vec<usize> = (0..self.runners.len())
+            .filter(|&i| self.is_marked(&self.runners[i]))
+            .collect();
+        if targets.is_empty() {
+            return false;
+        }
+        let busy = targets.iter().filter(|&&i| self.runners[i].busy).count();
+        let mut verb = action.to_string();
+        if let Some(first) = verb.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+        let aborts_jobs = busy > 0 && action != "start";
+        let mut prompt = format!(
+            "{} {} marked runner(s), {} at a time?",
+            verb,
+            targets.len(),
+            self.bulk_limits.concurrency.max(1)
+        );
+        if aborts_jobs {
+            prompt.push_str(&format!(" {} of them RUNNING A JOB will abort it.", busy));
+        }
+        self.request_confirm(prompt, action);
+        if let Some(pending) = self.pending_confirm.as_mut() {
+            pending.targets = Some(targets);
+            // Same as for a single busy runner: make them type it
+            if aborts_jobs {
+                pending.typed = Some(String::new());
+            }
+        }
+        true
+    }
+
+    /// Apply `action` to several runners, in waves sized by `bulk_limits`.
+    fn control_many(&mut self, targets: Vec<usize>, action: &str) {
+        if self.sync_actions {
+            // One at a time while blocking the UI is already as gentle as it gets
+            let mut failed = 0;
+            for &index in &targets {
+                let Some(runner) = self.runners.get(index) else {
+                    continue;
+                };
+                let message = match control_runner(&SystemCommandRunner, runner, action) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        failed += 1;
+                        format!("Error: {}", e)
+                    }
                 };
+                self.log_action(message);
+            }
+            self.status_message = Some(format!(
+                "Bulk {}: {} runners, {} failed",
+                action,
+                targets.len(),
+                failed
+            ));
+            if self.command_tx.send(WorkerCommand::ReloadUnits).is_ok() {
+                self.refreshing = true;
+                self.refresh_requested_at = Instant::now();
+            }
+            return;
+        }
+
+        let count = targets.len();
+        let command = WorkerCommand::ControlMany {
+            runner_indices: targets.clone(),
+            action: action.to_string(),
+            limits: self.bulk_limits,
+        };
+        self.status_message = Some(if self.command_tx.send(command).is_ok() {
+            for index in targets {
+                self.mark_pending(index, action);
+            }
+            format!("Bulk {}: {} runners...", action, count)
+        } else {
+            "Error: Worker thread unavailable".to_string()
+        });
+    }
+
+    /// Ask the user to confirm an action before sending it to the worker.
+    fn request_confirm(&mut self, prompt: String, action: &str) {
+        self.pending_confirm = Some(PendingConfirm {
+            prompt,
+            action: action.to_string(),
+            typed: None,
+            word: TYPED_CONFIRMATION.to_string(),
+            next: None,
+            targets: None,
+            scroll: 0,
+        });
+        self.mode = AppMode::Confirm;
+    }
+
+    /// If the selected runner is executing a job, ask for a typed confirmation
+    /// before an action that would abort it. Returns true if one was requested.
+    fn confirm_if_busy(&mut self, action: &str) -> bool {
+        let Some(runner) = self.selected_runner().filter(|r| r.busy) else {
+            return false;
+        };
+        let doing = match action {
+            "stop" => "stopping",
+            "restart" => "restarting",
+            _ => "uninstalling",
+        };
+        let prompt = format!(
+            "{}/{} is RUNNING A JOB — {} will abort it. Continue?",
+            runner.repo, runner.name, doing
+        );
+        self.pending_confirm = Some(PendingConfirm {
+            prompt,
+            action: action.to_string(),
+            typed: Some(String::new()),
+            word: TYPED_CONFIRMATION.to_string(),
+            next: None,
+            targets: None,
+            scroll: 0,
+        });
+        self.mode = AppMode::Confirm;
+        true
+    }
+
+    /// Whether the pending confirmation needs a typed word rather than `y`
+    pub fn confirm_needs_typing(&self) -> bool {
+        self.pending_confirm
+            .as_ref()
+            .is_some_and(|pending| pending.typed.is_some())
+    }
+
+    /// Scroll the list of runners a bulk action will affect.
+    pub fn scroll_confirm_down(&mut self, lines: usize) {
+        if let Some(pending) = self.pending_confirm.as_mut() {
+            let last = pending
+                .targets
+                .as_ref()
+                .map_or(0, |t| t.len().saturating_sub(1));
+            pending.scroll = (pending.scroll + lines).min(last);
+        }
+    }
+
+    pub fn scroll_confirm_up(&mut self, lines: usize) {
+        if let Some(pending) = self.pending_confirm.as_mut() {
+            pending.scroll = pending.scroll.saturating_sub(lines);
+        }
+    }
+
+    pub fn push_confirm_char(&mut self, c: char) {
+        if let Some(typed) = self.pending_confirm.as_mut().and_then(|p| p.typed.as_mut()) {
+            typed.push(c);
+        }
+    }
+
+    pub fn pop_confirm_char(&mut self) {
+        if let Some(typed) = self.pending_confirm.as_mut().and_then(|p| p.typed.as_mut()) {
+            typed.pop();
+        }
+    }
 
-                // Refresh runners after control action
-                refresh_runners(&mut runners);
+    /// Confirm if the typed word is right, otherwise cancel.
+    pub fn submit_typed_confirm(&mut self) {
+        let accepted = self
+            .pending_confirm
+            .as_ref()
+            .and_then(|p| Some((p.typed.as_deref()?, p.word.as_str())))
+            .is_some_and(|(typed, word)| typed.trim().eq_ignore_ascii_case(word));
+        if accepted {
+            self.confirm();
+        } else {
+            self.cancel_confirm();
+        }
+    }
 
-                // Always send response
-                let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
-                let _ = response_tx.send(WorkerResponse::ActionComplete { message });
+    pub fn confirm(&mut self) {
+        self.mode = AppMode::Normal;
+        if let Some(pending) = self.pending_confirm.take() {
+            if let Some(next) = pending.next {
+                self.pending_confirm = Some(*next);
+                self.mode = AppMode::Confirm;
+                return;
             }
-            Ok(WorkerCommand::Shutdown) => {
-                // Exit worker thread
-                break;
+            match pending.targets {
+                Some(targets) => self.control_many(targets, &pending.action),
+                None => self.control_selected_runner(&pending.action),
             }
-            Err(mpsc::RecvTimeoutError::Timeout) => {
-                // No command received, continue loop
+        }
+    }
+
+    pub fn cancel_confirm(&mut self) {
+        self.mode = AppMode::Normal;
+        self.pending_confirm = None;
+        self.status_message = Some("Cancelled".to_string());
+    }
+
+    fn control_selected_runner(&mut self, action: &str) {
+        let Some(runner) = self.selected_runner() else {
+            self.status_message = Some("No runner selected".to_string());
+            return;
+        };
+
+        if self.sync_actions {
+            // Blocks the UI until the action finishes
+            let (message, failure) = match control_runner(&SystemCommandRunner, runner, action) {
+                Ok(message) => (message, None),
+                Err(e) => (
+                    format!("Error: {}", e),
+                    e.downcast_ref::<CommandFailure>().cloned(),
+                ),
+            };
+            self.log_action(message.clone());
+            self.status_message = Some(message);
+            if let Some(failure) = failure {
+                self.show_failure(&failure);
             }
-            Err(mpsc::RecvTimeoutError::Disconnected) => {
-                // Main thread dropped, exit
-                break;
+
+            // Install/uninstall may have changed which units exist
+            if self.command_tx.send(WorkerCommand::ReloadUnits).is_ok() {
+                self.refreshing = true;
+                self.refresh_requested_at = Instant::now();
             }
+            return;
         }
+
+        // Show pending status immediately
+        let mut capitalized = action.to_string();
+        if let Some(first) = capitalized.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+
+        // Send command to background worker
+        if self
+            .command_tx
+            .send(WorkerCommand::ControlRunner {
+                runner_index: self.selected,
+                action: action.to_string(),
+            })
+            .is_err()
+        {
+            self.status_message = Some("Error: Worker thread unavailable".to_string());
+            return;
+        }
+
+        self.mark_pending(self.selected, action);
+        self.status_message = Some(format!("{}ing runner...", capitalized));
+    }
+
+    /// Remember that `action` is in flight for a runner, until the worker reports it done.
+    fn mark_pending(&mut self, index: usize, action: &str) {
+        if let Some(runner) = self.runners.get(index) {
+            self.pending_actions
+                .insert(runner.path.clone(), action.to_string());
+        }
+    }
+
+    /// The control action still running for `runner`, if any
+    pub fn pending_action(&self, runner: &Runner) -> Option<&str> {
+        self.pending_actions.get(&runner.path).map(String::as_str)
+    }
+
+    pub fn toggle_logs(&mut self) {
+        if self.tab == Tab::Logs {
+            self.set_tab(Tab::Runners);
+        } else {
+            self.set_tab(Tab::Logs);
+        }
+    }
+
+    pub fn set_tab(&mut self, tab: Tab) {
+        if tab == self.tab {
+            return;
+        }
+        if self.tab == Tab::Logs {
+            self.logs.clear();
+            self.log_scroll = 0;
+            self.log_match = None;
+            self.log_context = None;
+        }
+        self.tab = tab;
+        self.mode = AppMode::Normal;
+        if tab == Tab::Logs {
+            // Start at the bottom and follow new output
+            self.log_follow = true;
+            self.refresh_logs();
+        }
+        if tab == Tab::Stats {
+            self.disks.refresh();
+            self.system_stats.disks = disk_usage(&self.disks);
+        }
+        if tab == Tab::Fleet {
+            self.load_agent_settings();
+        }
+    }
+
+    /// Re-read every runner's `.runner` and package platform for the Fleet tab.
+    pub fn load_agent_settings(&mut self) {
+        self.agent_settings = self
+            .runners
+            .iter()
+            .map(|r| (r.path.clone(), agent_settings(&r.path)))
+            .collect();
+    }
+
+    /// Show earlier runners in the Fleet tab.
+    pub fn scroll_fleet_left(&mut self) {
+        self.fleet_scroll = self.fleet_scroll.saturating_sub(1);
+    }
+
+    /// Show later runners in the Fleet tab, keeping at least the last one in view.
+    pub fn scroll_fleet_right(&mut self) {
+        let last = self.visible_runners().len().saturating_sub(1);
+        self.fleet_scroll = (self.fleet_scroll + 1).min(last);
+    }
+
+    pub fn next_tab(&mut self) {
+        self.set_tab(self.tab.next());
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.set_tab(self.tab.previous());
+    }
+
+    /// Runners as the list shows them: filtered, pinned first, in sort order.
+    fn shown_runners(&self) -> Vec<Runner> {
+        self.visible_runners()
+            .into_iter()
+            .map(|i| self.runners[i].clone())
+            .collect()
+    }
+
+    /// Write the runners the list shows to a timestamped CSV file in the working directory.
+    pub fn export_csv(&mut self) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = std::path::PathBuf::from(format!("runners-{}.csv", timestamp));
+        let runners = self.shown_runners();
+
+        self.status_message = Some(match report::write_csv(&runners, &path) {
+            Ok(()) => format!("Exported {} runners to {}", runners.len(), path.display()),
+            Err(e) => format!("Error: {}", e),
+        });
+    }
+
+    /// Switch between the newest log lines and the last 5m / 1h / today.
+    pub fn cycle_log_window(&mut self) {
+        self.log_window = self.log_window.next();
+        self.log_context = None;
+        self.status_message = Some(if cfg!(target_os = "macos") {
+            format!(
+                "Log window: {} (macOS runner logs always show the last lines)",
+                self.log_window.as_str()
+            )
+        } else {
+            format!("Log window: {}", self.log_window.as_str())
+        });
+        self.log_follow = true;
+        self.refresh_logs();
+    }
+
+    /// Switch between all log lines, warnings and up, and errors only.
+    pub fn cycle_log_priority(&mut self) {
+        self.log_priority = self.log_priority.next();
+        self.log_context = None;
+        self.status_message = Some(format!("Showing {}", self.log_priority.as_str()));
+        self.log_follow = true;
+        self.refresh_logs();
+    }
+
+    /// Ask the worker to check the selected runner's registration with GitHub.
+    pub fn check_registration_selected(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let Some(token) = github::token() else {
+            self.status_message =
+                Some("Set GITHUB_TOKEN to check runner registrations".to_string());
+            return;
+        };
+        let name = runner.display_name();
+        let command = WorkerCommand::CheckRegistration {
+            runner_index: self.selected,
+            token,
+        };
+        self.status_message = Some(if self.command_tx.send(command).is_ok() {
+            format!("Checking {} with GitHub...", name)
+        } else {
+            "Error: Worker thread unavailable".to_string()
+        });
+    }
+
+    /// Check, without changing anything, that the selected runner's backend answers.
+    pub fn probe_selected(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let name = runner.display_name();
+        let command = WorkerCommand::Probe {
+            runner_index: self.selected,
+        };
+        self.status_message = Some(if self.command_tx.send(command).is_ok() {
+            format!("Testing {}...", name)
+        } else {
+            "Error: Worker thread unavailable".to_string()
+        });
+    }
+
+    /// Show what a connection test found, unless something else is on screen.
+    fn probed(&mut self, runner: String, report: ProbeReport) {
+        let view = OutputView {
+            title: format!(" Connection test: {} ", runner),
+            summary: format!("[{}] {}", report.backend, report.summary),
+            ok: report.ok,
+            lines: report.transcript,
+            scroll: 0,
+        };
+        self.log_action(format!("Tested {}: {}", runner, view.summary));
+        if matches!(
+            self.mode,
+            AppMode::Normal | AppMode::Detail | AppMode::Output
+        ) {
+            self.show_output(view);
+        } else {
+            self.status_message = Some(format!("Tested {}: {}", runner, view.summary));
+        }
+    }
+
+    /// Show the selected runner's `.runner` file, secrets redacted.
+    pub fn show_runner_config(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let title = format!(" .runner: {} ", runner.display_name());
+        let view = match read_runner_config(&runner.path) {
+            Ok(lines) => OutputView {
+                title,
+                summary: runner.path.join(".runner").display().to_string(),
+                ok: true,
+                lines,
+                scroll: 0,
+            },
+            Err(e) => OutputView {
+                title,
+                summary: format!("{:#}", e),
+                ok: false,
+                lines: Vec::new(),
+                scroll: 0,
+            },
+        };
+        self.show_output(view);
+    }
+
+    /// Start typing label edits for the selected runner.
+    pub fn start_label_edit(&mut self) {
+        if self.selected_runner().is_none() {
+            return;
+        }
+        if github::token().is_none() {
+            self.status_message = Some("Set GITHUB_TOKEN to edit runner labels".to_string());
+            return;
+        }
+        self.previous_mode = self.mode;
+        self.label_input.clear();
+        self.label_error = None;
+        self.mode = AppMode::Labels;
+    }
+
+    pub fn push_label_char(&mut self, c: char) {
+        self.label_input.push(c);
+        self.label_error = None;
+    }
+
+    pub fn pop_label_char(&mut self) {
+        self.label_input.pop();
+        self.label_error = None;
+    }
+
+    pub fn cancel_label_edit(&mut self) {
+        self.mode = self.previous_mode;
+    }
+
+    /// Send the typed label edits to GitHub, or say why they can't be sent.
+    pub fn apply_label_edit(&mut self) {
+        let edits = match github::parse_label_edits(&self.label_input) {
+            Ok(edits) => edits,
+            Err(e) => {
+                self.label_error = Some(format!("{:#}", e));
+                return;
+            }
+        };
+        let Some(token) = github::token() else {
+            self.label_error = Some("GITHUB_TOKEN is not set".to_string());
+            return;
+        };
+        let Some(runner) = self.selected_runner() else {
+            self.mode = self.previous_mode;
+            return;
+        };
+        let name = runner.display_name();
+        let command = WorkerCommand::EditLabels {
+            runner_index: self.selected,
+            token,
+            edits,
+        };
+        self.mode = self.previous_mode;
+        self.status_message = Some(if self.command_tx.send(command).is_ok() {
+            format!("Updating labels of {}...", name)
+        } else {
+            "Error: Worker thread unavailable".to_string()
+        });
+    }
+
+    fn labels_edited(&mut self, runner: String, result: Result<Vec<String>, String>) {
+        match result {
+            Ok(labels) => {
+                let message = format!("{}: labels now {}", runner, labels.join(", "));
+                self.log_action(message.clone());
+                self.status_message = Some(message);
+            }
+            Err(error) => {
+                let message = format!("Error: could not update labels of {}", runner);
+                self.log_action(format!("{}: {}", message, error));
+                self.status_message = Some(message.clone());
+                if matches!(
+                    self.mode,
+                    AppMode::Normal | AppMode::Detail | AppMode::Output
+                ) {
+                    self.show_output(OutputView {
+                        title: " Labels not updated ".to_string(),
+                        summary: message,
+                        ok: false,
+                        lines: vec![error],
+                        scroll: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Open the output of a failed command, unless a dialog or help is in the way.
+    fn show_failure(&mut self, failure: &CommandFailure) {
+        if !matches!(
+            self.mode,
+            AppMode::Normal | AppMode::Detail | AppMode::Output
+        ) {
+            return;
+        }
+        self.show_output(OutputView {
+            title: " Command failed ".to_string(),
+            summary: failure.context.clone(),
+            ok: false,
+            lines: failure.transcript(),
+            scroll: 0,
+        });
+    }
+
+    fn show_output(&mut self, view: OutputView) {
+        if self.mode != AppMode::Output {
+            self.previous_mode = self.mode;
+        }
+        self.output = Some(view);
+        self.mode = AppMode::Output;
+    }
+
+    pub fn close_output(&mut self) {
+        self.output = None;
+        self.mode = self.previous_mode;
+    }
+
+    /// Open the selected runner's directory with the desktop's file manager.
+    pub fn open_selected_dir(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(windows) {
+            "explorer"
+        } else {
+            "xdg-open"
+        };
+
+        // Detached and silenced so the opener can't draw over the TUI
+        let spawned = std::process::Command::new(opener)
+            .arg(&runner.path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        self.status_message = Some(match spawned {
+            Ok(child) => {
+                wait_in_background(child);
+                format!("Opened {}", runner.path.display())
+            }
+            Err(e) => format!("Error: could not run {}: {}", opener, e),
+        });
+    }
+
+    /// Ask the event loop to suspend the TUI and start a shell in the selected runner's directory.
+    pub fn shell_in_selected_dir(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let mut command = Command::new(user_shell());
+        command.current_dir(&runner.path);
+        self.pending_suspend = Some(SuspendRequest {
+            label: format!("shell in {}", runner.path.display()),
+            command,
+        });
+    }
+
+    /// Ask the event loop to suspend the TUI and run `suspend_command`, or a shell.
+    ///
+    /// The selected runner is passed along as `RUNNER_NAME`, `RUNNER_DIR` and
+    /// `RUNNER_SERVICE`, so a command like `journalctl -u "$RUNNER_SERVICE"` works.
+    pub fn suspend(&mut self) {
+        let (label, mut command) = match &self.suspend_command {
+            Some(line) => {
+                let mut command = Command::new("sh");
+                command.arg("-c").arg(line);
+                (line.clone(), command)
+            }
+            None => ("shell".to_string(), Command::new(user_shell())),
+        };
+        if let Some(runner) = self.selected_runner() {
+            command
+                .env("RUNNER_NAME", runner.display_name())
+                .env("RUNNER_DIR", &runner.path)
+                .env("RUNNER_SERVICE", &runner.service_name);
+        }
+        self.pending_suspend = Some(SuspendRequest { label, command });
+    }
+
+    /// Columns of the current list layout, which are also what it can be sorted by
+    pub fn shown_columns(&self) -> Vec<ListColumn> {
+        match self.list_density {
+            ListDensity::Verbose => self.list_columns.clone(),
+            ListDensity::Status => vec![ListColumn::Name, ListColumn::Status],
+            ListDensity::Normal | ListDensity::Compact => vec![ListColumn::Name],
+        }
+    }
+
+    /// Sort by the next column of the layout, ascending; after the last, back to list order.
+    pub fn cycle_sort(&mut self) {
+        let columns = self.shown_columns();
+        let next = match self
+            .sort_by
+            .and_then(|c| columns.iter().position(|&s| s == c))
+        {
+            Some(i) => columns.get(i + 1).copied(),
+            None => columns.first().copied(),
+        };
+        self.sort_by = next;
+        self.sort_descending = false;
+        self.describe_sort();
+    }
+
+    /// Flip between ascending and descending.
+    pub fn invert_sort(&mut self) {
+        if self.sort_by.is_some() {
+            self.sort_descending = !self.sort_descending;
+        }
+        self.describe_sort();
+    }
+
+    /// Sort by `column`, or flip the order if the list is already sorted by it,
+    /// as when its header is clicked.
+    pub fn sort_by_column(&mut self, column: ListColumn) {
+        if self.sort_by == Some(column) {
+            self.sort_descending = !self.sort_descending;
+        } else {
+            self.sort_by = Some(column);
+            self.sort_descending = false;
+        }
+        self.describe_sort();
+    }
+
+    fn describe_sort(&mut self) {
+        self.status_message = Some(match self.sort_by {
+            Some(column) => format!(
+                "Sorted by {} ({})",
+                column.heading().to_lowercase(),
+                if self.sort_descending {
+                    "descending"
+                } else {
+                    "ascending"
+                }
+            ),
+            None => "Unsorted".to_string(),
+        });
+    }
+
+    /// Ascending order of two runners by `column`. Runners without a value (no
+    /// version, no processes) come last.
+    fn compare_by(&self, column: ListColumn, a: &Runner, b: &Runner) -> Ordering {
+        let usage = |r: &Runner| self.runner_usage.get(&r.path).copied();
+        match column {
+            ListColumn::Status => status_rank(a).cmp(&status_rank(b)),
+            ListColumn::Repo => natural_cmp(&a.repo, &b.repo),
+            // Shown without the repo when that has a column of its own
+            ListColumn::Name
+                if self.list_density == ListDensity::Verbose
+                    && self.list_columns.contains(&ListColumn::Repo) =>
+            {
+                natural_cmp(&a.name, &b.name)
+            }
+            ListColumn::Name => {
+                natural_cmp(&a.repo, &b.repo).then_with(|| natural_cmp(&a.name, &b.name))
+            }
+            ListColumn::Uptime => last_if_missing(usage(a), usage(b), |a, b| {
+                // Longest up first, i.e. earliest started
+                a.started.cmp(&b.started).reverse()
+            }),
+            ListColumn::Version => {
+                last_if_missing(a.version.as_deref(), b.version.as_deref(), |a, b| {
+                    compare_versions(a, b)
+                })
+            }
+            ListColumn::Cpu => last_if_missing(usage(a), usage(b), |a, b| a.cpu.total_cmp(&b.cpu)),
+            ListColumn::Mem => last_if_missing(usage(a), usage(b), |a, b| a.memory.cmp(&b.memory)),
+            ListColumn::Busy => b.busy.cmp(&a.busy),
+        }
+    }
+
+    pub fn cycle_list_density(&mut self) {
+        self.list_density = self.list_density.next();
+        self.status_message = Some(format!("List layout: {}", self.list_density.as_str()));
+    }
+
+    const LIST_RATIO_STEP: u16 = 5;
+
+    pub fn shrink_list(&mut self) {
+        self.set_list_ratio(self.list_ratio.saturating_sub(Self::LIST_RATIO_STEP));
+    }
+
+    pub fn grow_list(&mut self) {
+        self.set_list_ratio(self.list_ratio + Self::LIST_RATIO_STEP);
+    }
+
+    /// Resize the list pane and remember the new split in the config file.
+    fn set_list_ratio(&mut self, ratio: u16) {
+        let ratio = ratio.clamp(LIST_RATIO_MIN, LIST_RATIO_MAX);
+        if ratio == self.list_ratio {
+            return;
+        }
+        self.list_ratio = ratio;
+
+        self.status_message = Some(match Config::update(|c| c.list_ratio = ratio) {
+            Ok(()) => format!("List width: {}%", ratio),
+            Err(e) => format!("Error: could not save layout: {}", e),
+        });
+    }
+
+    /// Hide or bring back the details pane and remember it in the config file.
+    pub fn toggle_details_pane(&mut self) {
+        self.show_details = !self.show_details;
+        let shown = self.show_details;
+        self.status_message = Some(match Config::update(|c| c.show_details = shown) {
+            Ok(()) if shown => "Details pane shown".to_string(),
+            Ok(()) => "Details pane hidden (| shows it, Enter opens details)".to_string(),
+            Err(e) => format!("Error: could not save layout: {}", e),
+        });
+    }
+
+    pub fn toggle_detail(&mut self) {
+        self.mode = if self.mode == AppMode::Detail {
+            AppMode::Normal
+        } else {
+            AppMode::Detail
+        };
+        self.scan_work_selected();
+    }
+
+    /// In the details view, have the worker rescan the selected runner's work
+    /// folder unless a recent scan is still fresh.
+    pub fn scan_work_selected(&mut self) {
+        if self.mode != AppMode::Detail {
+            return;
+        }
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let fresh = runner
+            .work
+            .as_ref()
+            .is_some_and(|work| work.scanned_at.elapsed() < WORK_SCAN_MAX_AGE);
+        if !fresh {
+            let command = WorkerCommand::ScanWork {
+                runner_index: self.selected,
+            };
+            let _ = self.command_tx.send(command);
+        }
+    }
+
+    /// Close the first-run introduction and write the config file so it stays closed.
+    pub fn dismiss_welcome(&mut self) {
+        self.mode = AppMode::Normal;
+        if let Err(e) = Config::update(|_| {}) {
+            self.status_message = Some(format!("Error: could not save config: {}", e));
+        }
+    }
+
+    pub fn toggle_help(&mut self) {
+        if self.mode == AppMode::Help {
+            self.mode = self.previous_mode;
+        } else {
+            self.previous_mode = self.mode;
+            self.help_scroll = 0;
+            self.mode = AppMode::Help;
+        }
+    }
+
+    pub fn scroll_help_up(&mut self, lines: usize) {
+        self.help_scroll = self.help_scroll.saturating_sub(lines);
+    }
+
+    /// Scroll the help screen down, stopping at its last line.
+    pub fn scroll_help_down(&mut self, lines: usize, line_count: usize) {
+        let last_line = line_count.saturating_sub(1);
+        self.help_scroll = (self.help_scroll + lines).min(last_line);
+    }
+
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let active = self
+            .runners
+            .iter()
+            .filter(|r| r.status == RunnerStatus::Active)
+            .count();
+        let failed = self
+            .runners
+            .iter()
+            .filter(|r| r.status == RunnerStatus::Failed)
+            .count();
+        let total = self.runners.len();
+        (active, failed, total)
+    }
+}
+
+/// Used memory as a percentage of the total
+pub fn memory_percent(stats: &SystemStats) -> f64 {
+    if stats.memory_total > 0 {
+        (stats.memory_used as f64 / stats.memory_total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Sort rank by status: problems first, then stopped, idle and busy runners
+fn status_rank(runner: &Runner) -> u8 {
+    match (&runner.status, runner.busy) {
+        (RunnerStatus::Failed, _) => 0,
+        (RunnerStatus::NotFound, _) => 1,
+        (RunnerStatus::Inactive, _) => 2,
+        (RunnerStatus::Active, false) => 3,
+        (RunnerStatus::Active, true) => 4,
+    }
+}
+
+/// Compare with `cmp` when both values are there; a missing one sorts after the rest.
+fn last_if_missing<T>(a: Option<T>, b: Option<T>, cmp: impl Fn(T, T) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => cmp(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Compare strings with runs of digits taken as numbers, so `runner-2` comes
+/// before `runner-10`.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let a_end = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+            let b_end = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+            let (a_digits, b_digits) = (
+                a[..a_end].trim_start_matches('0'),
+                b[..b_end].trim_start_matches('0'),
+            );
+            let order = a_digits
+                .len()
+                .cmp(&b_digits.len())
+                .then_with(|| a_digits.cmp(b_digits));
+            if order != Ordering::Equal {
+                return order;
+            }
+            a = &a[a_end..];
+            b = &b[b_end..];
+        } else {
+            if x != y {
+                return x.cmp(&y);
+            }
+            a = &a[x.len_utf8()..];
+            b = &b[y.len_utf8()..];
+        }
+    }
+}
+
+/// One entry of the process table, as far as per-runner usage is concerned
+struct ProcessSample<'a> {
+    exe: Option<&'a Path>,
+    cwd: Option<&'a Path>,
+    cpu: f32,
+    memory: u64,
+    start_time: u64,
+}
+
+/// Add up the processes that belong to each runner: its own binaries, and
+/// anything (e.g. a job step) running inside its directory.
+fn usage_by_runner<'a>(
+    runners: &[Runner],
+    processes: impl IntoIterator<Item = ProcessSample<'a>>,
+) -> HashMap<PathBuf, ProcessUsage> {
+    let mut usage: HashMap<PathBuf, ProcessUsage> = HashMap::new();
+    for process in processes {
+        let Some(runner) = runners.iter().find(|r| {
+            [process.exe, process.cwd]
+                .into_iter()
+                .flatten()
+                .any(|path| path.starts_with(&r.path))
+        }) else {
+            continue;
+        };
+        let entry = usage.entry(runner.path.clone()).or_insert(ProcessUsage {
+            started: process.start_time,
+            ..ProcessUsage::default()
+        });
+        entry.cpu += process.cpu;
+        entry.memory += process.memory;
+        entry.started = entry.started.min(process.start_time);
+    }
+    usage
+}
+
+/// Average usage of the busy runners in `usage`, or None when none are busy.
+fn average_job_usage(
+    runners: &[Runner],
+    usage: &HashMap<PathBuf, ProcessUsage>,
+) -> Option<ProcessUsage> {
+    let busy: Vec<&ProcessUsage> = runners
+        .iter()
+        .filter(|r| r.busy)
+        .filter_map(|r| usage.get(&r.path))
+        .collect();
+    if busy.is_empty() {
+        return None;
+    }
+    let count = busy.len();
+    Some(ProcessUsage {
+        cpu: busy.iter().map(|u| u.cpu).sum::<f32>() / count as f32,
+        memory: busy.iter().map(|u| u.memory).sum::<u64>() / count as u64,
+        started: 0,
+    })
+}
+
+/// How many `per_job`s fit in the CPU and memory `stats` has free. A zero
+/// figure doesn't limit it; None when both are zero.
+pub fn headroom(stats: &SystemStats, per_job: ProcessUsage) -> Option<usize> {
+    // Per-process CPU is in percent of one core, the global figure of all of them
+    let cores = stats.cpu_cores.len().max(1) as f64;
+    let free_cpu = (100.0 - stats.cpu_usage as f64).max(0.0) * cores;
+    let by_cpu = (per_job.cpu > 0.0).then(|| (free_cpu / per_job.cpu as f64) as usize);
+    let by_memory =
+        (per_job.memory > 0).then(|| (stats.memory_available / per_job.memory) as usize);
+    match (by_cpu, by_memory) {
+        (Some(cpu), Some(memory)) => Some(cpu.min(memory)),
+        (cpu, memory) => cpu.or(memory),
+    }
+}
+
+/// Fill in a hook command's `{repo}`, `{name}`, `{status}` and `{service}`
+/// placeholders, shell-quoted since the result runs through `sh -c`.
+fn expand_hook(template: &str, runner: &Runner) -> String {
+    template
+        .replace("{repo}", &shell_quote(&runner.repo))
+        .replace("{name}", &shell_quote(&runner.name))
+        .replace("{status}", &shell_quote(runner.status.as_str()))
+        .replace("{service}", &shell_quote(&runner.service_name))
+}
+
+/// Quote `value` as a single shell word.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Wait for `child` on a thread of its own, so it doesn't linger as a zombie.
+fn wait_in_background(mut child: Child) {
+    let _ = std::thread::Builder::new()
+        .name("reap-child".to_string())
+        .spawn(move || {
+            let _ = child.wait();
+        });
+}
+
+/// Show a desktop notification, ignoring failures (e.g. on a headless host).
+fn notify_desktop(message: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        // Passed as an argument so the message never needs AppleScript quoting
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 1 of argv) with title \"Runner Dashboard\"",
+            "-e",
+            "end run",
+            message,
+        ]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["Runner Dashboard", message]);
+        command
+    };
+    let spawned = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(child) = spawned {
+        wait_in_background(child);
+    }
+}
+
+/// GitHub repository for each repo directory, from the first runner registered with one.
+fn github_repos(runners: &[Runner]) -> Vec<(String, String)> {
+    let mut repos: Vec<(String, String)> = Vec::new();
+    for runner in runners {
+        if repos.iter().any(|(repo, _)| *repo == runner.repo) {
+            continue;
+        }
+        if let Some(github) = github_repo(&runner.path) {
+            repos.push((runner.repo.clone(), github));
+        }
+    }
+    repos
+}
+
+/// Report queued runs for each repo every `interval` until the app goes away.
+///
+/// Failed lookups are skipped, so a flaky network only delays the backlog badge.
+fn poll_queue_depth(
+    token: &str,
+    repos: &[(String, String)],
+    interval: Duration,
+    response_tx: &Sender<WorkerResponse>,
+) {
+    loop {
+        for (repo, github) in repos {
+            if let Ok(queued) = github::queued_runs(token, github) {
+                let response = WorkerResponse::QueueDepth {
+                    repo: repo.clone(),
+                    queued,
+                };
+                if response_tx.send(response).is_err() {
+                    return;
+                }
+            }
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// The user's login shell, falling back to `sh`
+fn user_shell() -> String {
+    std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string())
+}
+
+/// Append a sample, dropping the oldest once the buffer is full.
+fn push_history(history: &mut VecDeque<u64>, value: u64) {
+    if history.len() >= App::HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+fn disk_usage(disks: &Disks) -> Vec<DiskUsage> {
+    disks
+        .iter()
+        .map(|disk| DiskUsage {
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            used: disk.total_space().saturating_sub(disk.available_space()),
+            total: disk.total_space(),
+        })
+        .collect()
+}
+
+fn network_rates(networks: &Networks, elapsed_secs: f64) -> Vec<NetworkRate> {
+    let mut rates: Vec<NetworkRate> = networks
+        .iter()
+        .filter(|(name, _)| name.as_str() != "lo")
+        .map(|(name, data)| NetworkRate {
+            interface: name.clone(),
+            rx_bytes_per_sec: data.received() as f64 / elapsed_secs,
+            tx_bytes_per_sec: data.transmitted() as f64 / elapsed_secs,
+        })
+        .collect();
+    rates.sort_by(|a, b| a.interface.cmp(&b.interface));
+    rates
+}
+
+/// How many lines scrolled off the top between two tails of the same log.
+///
+/// Finds the smallest shift at which the remainder of `old` is a prefix of `new`.
+/// Returns `None` if the logs don't overlap (e.g. the file was rotated).
+fn log_lines_dropped(old: &[String], new: &[String]) -> Option<usize> {
+    if old.is_empty() {
+        return Some(0);
+    }
+    (0..old.len()).find(|&shift| {
+        let remaining = &old[shift..];
+        new.len() >= remaining.len() && new[..remaining.len()] == *remaining
+    })
+}
+
+impl Drop for App {
+    fn drop(&mut self) {
+        // Signal worker thread to shutdown
+        let _ = self.command_tx.send(WorkerCommand::Shutdown);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+
+    /// An app wired to channels the test controls, with three runners.
+    fn test_app() -> (App, Receiver<WorkerCommand>, Sender<WorkerResponse>) {
+        let (command_tx, command_rx) = mpsc::channel();
+        let (response_tx, response_rx) = mpsc::channel();
+        let runners = vec![
+            test_runner("acme", 1),
+            Runner {
+                status: RunnerStatus::Failed,
+                ..test_runner("acme", 2)
+            },
+            test_runner("acme", 3),
+        ];
+        let app = App::new_with(runners, command_tx, response_rx, SystemStats::default());
+        (app, command_rx, response_tx)
+    }
+
+    #[test]
+    fn selection_wraps_in_both_directions() {
+        let (mut app, _, _) = test_app();
+
+        app.select_previous();
+        assert_eq!(app.selected, 2);
+        app.select_next();
+        assert_eq!(app.selected, 0);
+        app.select_next();
+        assert_eq!(app.selected, 1);
+    }
+
+    #[test]
+    fn problem_navigation_cycles_through_failed_and_missing_runners() {
+        let (mut app, _, _) = test_app();
+        app.runners.push(Runner {
+            status: RunnerStatus::NotFound,
+            ..test_runner("acme", 4)
+        });
+
+        app.select_next_problem();
+        assert_eq!(app.selected, 1);
+        app.select_next_problem();
+        assert_eq!(app.selected, 3);
+        app.select_next_problem();
+        assert_eq!(app.selected, 1);
+        app.select_previous_problem();
+        assert_eq!(app.selected, 3);
+
+        // Runners hidden by the filter are skipped
+        for c in "runner-4".chars() {
+            app.push_filter_char(c);
+        }
+        app.select_next_problem();
+        assert_eq!(app.selected, 3);
+        app.runners[3].status = RunnerStatus::Active;
+        app.select_next_problem();
+        assert_eq!(app.selected, 3);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("No failed or missing runners in the list")
+        );
+    }
+
+    #[test]
+    fn status_changes_flash_the_row_briefly() {
+        let (mut app, _, response_tx) = test_app();
+        let mut updated = app.runners.clone();
+        updated[0].status = RunnerStatus::Failed;
+        updated[1].busy = true;
+
+        response_tx
+            .send(WorkerResponse::RunnersUpdated(updated))
+            .unwrap();
+        app.poll_worker_updates();
+
+        assert!(app.is_flashing(&app.runners[0]));
+        assert!(!app.is_flashing(&app.runners[1]));
+        assert!(!app.is_flashing(&app.runners[2]));
+
+        let later = Instant::now() + STATUS_FLASH;
+        let unchanged = app.runners.clone();
+        app.note_status_changes(&unchanged, later);
+        assert!(app.flash_until.is_empty());
+    }
+
+    #[test]
+    fn bell_rings_once_for_a_flapping_runner() {
+        let (mut app, _, _) = test_app();
+        app.bell = true;
+        let start = Instant::now();
+        let mut failed = app.runners.clone();
+        failed[0].status = RunnerStatus::Failed;
+        let recovered = app.runners.clone();
+
+        app.note_status_changes(&failed, start);
+        app.runners = failed.clone();
+        assert!(std::mem::take(&mut app.ring_bell));
+
+        // Recovering and failing again soon after stays quiet
+        app.note_status_changes(&recovered, start + Duration::from_secs(5));
+        app.runners = recovered.clone();
+        app.note_status_changes(&failed, start + Duration::from_secs(10));
+        app.runners = recovered.clone();
+        assert!(!app.ring_bell);
+
+        app.note_status_changes(&failed, start + BELL_DEBOUNCE);
+        assert!(app.ring_bell);
+    }
+
+    #[test]
+    fn sorting_orders_by_column_and_keeps_pinned_first() {
+        let (mut app, _, _) = test_app();
+        let order = |app: &App| -> Vec<u32> {
+            app.visible_runners()
+                .into_iter()
+                .map(|i| app.runners[i].number)
+                .collect()
+        };
+
+        app.sort_by_column(ListColumn::Status);
+        assert_eq!(order(&app), [2, 1, 3]);
+        // Clicking the same column again flips it; ties keep their list order
+        app.sort_by_column(ListColumn::Status);
+        assert_eq!(order(&app), [1, 3, 2]);
+        app.pinned.insert(app.runners[2].service_name.clone());
+        assert_eq!(order(&app), [3, 1, 2]);
+
+        // The normal layout only has the name to sort by, then back to list order
+        app.pinned.clear();
+        app.runners[0].name = "runner-10".to_string();
+        app.cycle_sort();
+        assert_eq!(app.sort_by, Some(ListColumn::Name));
+        assert_eq!(order(&app), [2, 3, 1]);
+        app.invert_sort();
+        assert_eq!(order(&app), [1, 3, 2]);
+        app.cycle_sort();
+        assert_eq!(app.sort_by, None);
+        assert_eq!(order(&app), [1, 2, 3]);
+    }
+
+    #[test]
+    fn natural_order_compares_numbers_by_value() {
+        assert_eq!(natural_cmp("runner-2", "runner-10"), Ordering::Less);
+        assert_eq!(natural_cmp("runner-02", "runner-2"), Ordering::Equal);
+        assert_eq!(natural_cmp("acme", "acme-web"), Ordering::Less);
+        assert_eq!(natural_cmp("widgets", "acme"), Ordering::Greater);
+    }
+
+    #[test]
+    fn process_usage_is_added_up_per_runner() {
+        let (app, _, _) = test_app();
+        let bin = app.runners[0].path.join("bin/Runner.Listener");
+        let job = app.runners[0].path.join("_work/acme/acme");
+        let elsewhere = PathBuf::from("/usr/bin/sshd");
+        // runner-1's directory is a prefix of runner-10's as a string, but not as a path
+        let other = PathBuf::from("/nonexistent/action-runners/acme/10/bin/Runner.Listener");
+        fn sample<'a>(exe: &'a Path, cwd: Option<&'a Path>, start_time: u64) -> ProcessSample<'a> {
+            ProcessSample {
+                exe: Some(exe),
+                cwd,
+                cpu: 10.0,
+                memory: 100,
+                start_time,
+            }
+        }
+
+        let usage = usage_by_runner(
+            &app.runners,
+            [
+                sample(&bin, None, 500),
+                sample(&elsewhere, Some(&job), 900),
+                sample(&elsewhere, None, 100),
+                sample(&other, None, 100),
+            ],
+        );
+        assert_eq!(usage.len(), 1);
+        assert_eq!(
+            usage[&app.runners[0].path],
+            ProcessUsage {
+                cpu: 20.0,
+                memory: 200,
+                started: 500,
+            }
+        );
+    }
+
+    #[test]
+    fn headroom_comes_from_configured_or_observed_job_usage() {
+        let (mut app, _, _) = test_app();
+        app.system_stats = SystemStats {
+            cpu_usage: 50.0,
+            cpu_cores: vec![50.0; 4],
+            memory_available: 8 << 30,
+            ..SystemStats::default()
+        };
+        assert_eq!(app.headroom(), None);
+
+        // Two busy runners averaging 50% of a core and 2 GB: CPU fits 4, memory 4
+        app.runners[0].busy = true;
+        app.runners[2].busy = true;
+        let usage = HashMap::from([
+            (
+                app.runners[0].path.clone(),
+                ProcessUsage {
+                    cpu: 20.0,
+                    memory: 1 << 30,
+                    started: 0,
+                },
+            ),
+            (
+                app.runners[1].path.clone(),
+                ProcessUsage {
+                    cpu: 90.0,
+                    memory: 8 << 30,
+                    started: 0,
+                },
+            ),
+            (
+                app.runners[2].path.clone(),
+                ProcessUsage {
+                    cpu: 80.0,
+                    memory: 3 << 30,
+                    started: 0,
+                },
+            ),
+        ]);
+        app.observed_job_usage = average_job_usage(&app.runners, &usage);
+        assert_eq!(app.headroom(), Some(4));
+
+        // A configured figure wins over the observed one
+        app.job_usage.memory = 4 << 30;
+        assert_eq!(app.headroom(), Some(2));
+        app.job_usage.cpu = 300.0;
+        assert_eq!(app.headroom(), Some(0));
+    }
+
+    #[test]
+    fn job_usage_is_learned_at_most_once_a_minute() {
+        let (mut app, _, _) = test_app();
+        assert!(!app.learns_job_usage());
+
+        app.runners[0].busy = true;
+        assert!(app.learns_job_usage());
+
+        app.job_usage_sampled_at = Some(Instant::now());
+        assert!(!app.learns_job_usage());
+        app.job_usage_sampled_at = Instant::now().checked_sub(JOB_USAGE_SAMPLE_INTERVAL);
+        assert!(app.learns_job_usage());
+
+        // Nothing left to learn once both figures are configured
+        app.job_usage = ProcessUsage {
+            cpu: 100.0,
+            memory: 1 << 30,
+            started: 0,
+        };
+        assert!(!app.learns_job_usage());
+    }
+
+    #[test]
+    fn hooks_run_when_a_runner_fails_and_recovers() {
+        let (mut app, _, _) = test_app();
+        let dir = std::env::temp_dir().join(format!("runner-hooks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("hooks.log");
+        app.on_failure = Some(format!(
+            "echo failed {{repo}} {{name}} >> {}",
+            out.display()
+        ));
+        app.on_recover = Some(format!(
+            "echo recovered {{name}} >> {}; exit 3",
+            out.display()
+        ));
+
+        // runner-2 starts out failed
+        let mut updated = app.runners.clone();
+        updated[0].status = RunnerStatus::Failed;
+        updated[1].status = RunnerStatus::Active;
+        updated[2].status = RunnerStatus::Inactive;
+        app.note_status_changes(&updated, Instant::now());
+
+        let mut logged: Vec<String> = (0..2)
+            .map(|_| app.hook_rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        logged.sort();
+        assert_eq!(
+            logged,
+            [
+                "Error: on_recover hook for acme/runner-2 exit status: 3",
+                "on_failure hook for acme/runner-1 exited with 0",
+            ]
+        );
+        let mut lines: Vec<String> = std::fs::read_to_string(&out)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        lines.sort();
+        assert_eq!(lines, ["failed acme runner-1", "recovered runner-2"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn hook_placeholders_are_shell_quoted() {
+        let mut runner = Runner {
+            status: RunnerStatus::Failed,
+            ..test_runner("acme", 1)
+        };
+        runner.repo = "it's; rm -rf ~".to_string();
+        assert_eq!(
+            expand_hook("notify {repo} {name} {status}", &runner),
+            r#"notify 'it'\''s; rm -rf ~' 'runner-1' 'failed'"#
+        );
+    }
+
+    #[test]
+    fn runners_show_as_pending_until_their_action_finishes() {
+        let (mut app, _command_rx, response_tx) = test_app();
+        app.select_next();
+        app.restart_selected();
+        assert_eq!(app.pending_action(&app.runners[1]), Some("restart"));
+        assert_eq!(app.pending_action(&app.runners[0]), None);
+
+        // Other refreshes leave it pending; the worker's report clears it
+        let runners = app.runners.clone();
+        response_tx
+            .send(WorkerResponse::RunnersUpdated(runners.clone()))
+            .unwrap();
+        app.poll_worker_updates();
+        assert!(app.pending_action(&app.runners[1]).is_some());
+
+        response_tx
+            .send(WorkerResponse::ActionFinished(runners[1].path.clone()))
+            .unwrap();
+        app.poll_worker_updates();
+        assert_eq!(app.pending_action(&app.runners[1]), None);
+    }
+
+    #[test]
+    fn pinned_runners_lead_the_list_and_navigation() {
+        let (mut app, _, _) = test_app();
+        app.pinned.insert(app.runners[2].service_name.clone());
+
+        assert_eq!(app.visible_runners(), vec![2, 0, 1]);
+        assert_eq!(app.selected, 0);
+        app.select_previous();
+        assert_eq!(app.selected, 2);
+        app.select_previous();
+        assert_eq!(app.selected, 1);
+        app.select_next();
+        assert_eq!(app.selected, 2);
+
+        // Problem navigation follows the displayed order too
+        app.runners[2].status = RunnerStatus::Failed;
+        app.selected = 1;
+        app.select_next_problem();
+        assert_eq!(app.selected, 2);
+        app.select_next_problem();
+        assert_eq!(app.selected, 1);
+    }
+
+    #[test]
+    fn marked_runners_are_restarted_together() {
+        let (mut app, command_rx, _) = test_app();
+        app.toggle_mark_selected();
+        app.selected = 2;
+        app.toggle_mark_selected();
+        app.selected = 1;
+
+        app.restart_selected();
+        assert_eq!(
+            app.pending_confirm.as_ref().map(|p| p.prompt.as_str()),
+            Some("Restart 2 marked runner(s), 4 at a time?")
+        );
+        app.confirm();
+
+        match command_rx.try_recv() {
+            Ok(WorkerCommand::ControlMany {
+                runner_indices,
+                action,
+                ..
+            }) => {
+                assert_eq!(runner_indices, vec![0, 2]);
+                assert_eq!(action, "restart");
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        app.clear_marks();
+        app.restart_selected();
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(WorkerCommand::ControlRunner {
+                runner_index: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn counts_active_failed_and_total() {
+        let (app, _, _) = test_app();
+
+        assert_eq!(app.counts(), (2, 1, 3));
+    }
+
+    #[test]
+    fn control_action_sends_selected_index_to_worker() {
+        let (mut app, command_rx, _) = test_app();
+        app.select_next();
+
+        app.restart_selected();
+
+        match command_rx.try_recv() {
+            Ok(WorkerCommand::ControlRunner {
+                runner_index,
+                action,
+            }) => {
+                assert_eq!(runner_index, 1);
+                assert_eq!(action, "restart");
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert_eq!(app.status_message.as_deref(), Some("Restarting runner..."));
+    }
+
+    #[test]
+    fn control_action_reports_missing_worker() {
+        let (mut app, command_rx, _) = test_app();
+        drop(command_rx);
+
+        app.start_selected();
+
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Error: Worker thread unavailable")
+        );
+    }
+
+    #[test]
+    fn log_scroll_is_clamped_to_last_line() {
+        let (mut app, _, _) = test_app();
+        app.logs = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        for _ in 0..10 {
+            app.scroll_logs_down();
+        }
+        assert_eq!(app.log_scroll, 2);
+
+        for _ in 0..10 {
+            app.scroll_logs_up();
+        }
+        assert_eq!(app.log_scroll, 0);
+    }
+
+    #[test]
+    fn shell_request_targets_the_selected_runner() {
+        let (mut app, _, _) = test_app();
+        app.select_next();
+        app.shell_in_selected_dir();
+
+        let request = app.pending_suspend.take().unwrap();
+        assert_eq!(
+            request.command.get_current_dir(),
+            Some(app.runners[1].path.as_path())
+        );
+    }
+
+    #[test]
+    fn suspend_runs_the_configured_command_with_the_selected_runner() {
+        let (mut app, _, _) = test_app();
+        app.suspend_command = Some("less +G \"$RUNNER_DIR/_diag/Runner.log\"".to_string());
+        app.suspend();
+
+        let request = app.pending_suspend.take().unwrap();
+        assert_eq!(request.command.get_program(), "sh");
+        let service = request
+            .command
+            .get_envs()
+            .find(|(key, _)| *key == "RUNNER_SERVICE")
+            .and_then(|(_, value)| value);
+        assert_eq!(service, Some(app.runners[0].service_name.as_ref()));
+    }
+
+    #[test]
+    fn alarm_raises_once_after_the_level_is_sustained() {
+        let start = Instant::now();
+        let sustain = Duration::from_secs(30);
+        let mut alarm = Alarm::default();
+
+        assert!(!alarm.update(true, sustain, start));
+        assert!(!alarm.update(true, sustain, start + Duration::from_secs(29)));
+        assert!(alarm.update(true, sustain, start + Duration::from_secs(30)));
+        assert!(!alarm.update(true, sustain, start + Duration::from_secs(31)));
+
+        // Dropping below the level starts over
+        assert!(!alarm.update(false, sustain, start + Duration::from_secs(32)));
+        assert!(!alarm.raised);
+        assert!(!alarm.update(true, sustain, start + Duration::from_secs(33)));
+    }
+
+    #[test]
+    fn sustained_high_memory_is_reported() {
+        let (mut app, _, _) = test_app();
+        app.alert_after = Duration::ZERO;
+        app.system_stats.memory_total = 100;
+        app.system_stats.memory_used = 95;
+        app.check_alarms(Instant::now());
+
+        assert!(app.mem_alarm.raised);
+        assert!(!app.cpu_alarm.raised);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Host memory 95% for 0s")
+        );
+    }
+
+    #[test]
+    fn log_window_cycles_back_to_the_tail() {
+        let mut window = LogWindow::Tail;
+        let mut since = Vec::new();
+        for _ in 0..4 {
+            window = window.next();
+            since.push(window.since());
+        }
+
+        assert_eq!(
+            since,
+            vec![Some("5 min ago"), Some("1 hour ago"), Some("today"), None]
+        );
+        assert_eq!(window, LogWindow::Tail);
+    }
+
+    #[test]
+    fn help_scroll_is_clamped_and_reset_on_open() {
+        let (mut app, _, _) = test_app();
+        app.toggle_help();
+
+        app.scroll_help_down(10, 12);
+        app.scroll_help_down(10, 12);
+        assert_eq!(app.help_scroll, 11);
+        app.scroll_help_up(4);
+        assert_eq!(app.help_scroll, 7);
+
+        app.toggle_help();
+        app.toggle_help();
+        assert_eq!(app.help_scroll, 0);
+    }
+
+    #[test]
+    fn closing_help_returns_to_the_previous_mode() {
+        let (mut app, _, _) = test_app();
+        app.toggle_detail();
+        app.toggle_help();
+        assert_eq!(app.previous_mode, AppMode::Detail);
+
+        app.toggle_help();
+        assert_eq!(app.mode, AppMode::Detail);
+    }
+
+    fn lines(range: std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| format!("line {}", i)).collect()
+    }
+
+    #[test]
+    fn log_scroll_stays_anchored_as_log_grows() {
+        let (mut app, _, _) = test_app();
+        app.apply_logs(lines(0..5));
+        app.scroll_logs_up();
+        app.scroll_logs_up();
+        assert_eq!(app.logs[app.log_scroll], "line 2");
+
+        // Still growing below the tail limit: nothing drops off the top
+        app.apply_logs(lines(0..8));
+        assert_eq!(app.logs[app.log_scroll], "line 2");
+
+        // Tail window slides: earlier lines drop off
+        app.apply_logs(lines(1..9));
+        assert_eq!(app.logs[app.log_scroll], "line 2");
+        app.apply_logs(lines(2..10));
+        assert_eq!(app.logs[app.log_scroll], "line 2");
+    }
+
+    #[test]
+    fn log_follow_pins_to_bottom() {
+        let (mut app, _, _) = test_app();
+        app.apply_logs(lines(0..5));
+        assert_eq!(app.log_scroll, 4);
+
+        app.apply_logs(lines(1..9));
+        assert_eq!(app.logs[app.log_scroll], "line 8");
+
+        app.scroll_logs_up();
+        assert!(!app.log_follow);
+        app.scroll_logs_down();
+        assert!(app.log_follow);
+    }
+
+    #[test]
+    fn log_scroll_is_clamped_when_logs_are_replaced() {
+        let (mut app, _, _) = test_app();
+        app.apply_logs(lines(0..10));
+        app.scroll_logs_up();
+
+        app.apply_logs(vec!["rotated".to_string()]);
+
+        assert_eq!(app.log_scroll, 0);
+    }
+
+    #[test]
+    fn tabs_cycle_in_both_directions() {
+        let (mut app, _, _) = test_app();
+
+        app.previous_tab();
+        assert_eq!(app.tab, Tab::Fleet);
+        app.next_tab();
+        app.next_tab();
+        assert_eq!(app.tab, Tab::Logs);
+        assert!(app.log_follow);
+
+        app.set_tab(Tab::Stats);
+        assert!(app.logs.is_empty());
+    }
+
+    #[test]
+    fn runner_update_clamps_selection() {
+        let (mut app, _, response_tx) = test_app();
+        app.selected = 2;
+
+        response_tx
+            .send(WorkerResponse::RunnersUpdated(vec![test_runner("acme", 1)]))
+            .unwrap();
+        app.poll_worker_updates();
+
+        assert_eq!(app.runners.len(), 1);
+        assert_eq!(app.selected, 0);
+        assert!(!app.refreshing);
+    }
+
+    #[test]
+    fn keypress_after_list_empties_sends_no_command() {
+        let (mut app, command_rx, response_tx) = test_app();
+        app.selected = 2;
+
+        response_tx
+            .send(WorkerResponse::RunnersUpdated(Vec::new()))
+            .unwrap();
+        app.poll_worker_updates();
+        assert_eq!(app.selected, 0);
+
+        app.select_next();
+        app.stop_selected();
+
+        assert!(command_rx.try_recv().is_err());
+        assert_eq!(app.status_message.as_deref(), Some("No runner selected"));
+    }
+
+    #[test]
+    fn filter_limits_selection_to_matching_runners() {
+        let (mut app, _, _) = test_app();
+        app.selected = 1;
+
+        for c in "R-3".chars() {
+            app.push_filter_char(c);
+        }
+        assert_eq!(app.visible_runners(), vec![2]);
+        assert_eq!(app.selected, 2);
+
+        app.select_next();
+        assert_eq!(app.selected, 2);
+
+        app.clear_filter();
+        app.select_next();
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn filter_with_no_matches_selects_nothing() {
+        let (mut app, command_rx, _) = test_app();
+
+        app.push_filter_char('z');
+        app.restart_selected();
+
+        assert!(app.visible_runners().is_empty());
+        assert!(command_rx.try_recv().is_err());
+        assert_eq!(app.status_message.as_deref(), Some("No runner selected"));
+    }
+
+    #[test]
+    fn csv_export_follows_the_filter_and_sort_order() {
+        let (mut app, _, _) = test_app();
+        app.regex_filter = true;
+        for c in "[12]$".chars() {
+            app.push_filter_char(c);
+        }
+        app.sort_by_column(ListColumn::Name);
+        app.sort_by_column(ListColumn::Name);
+
+        let names: Vec<String> = app.shown_runners().into_iter().map(|r| r.name).collect();
+        assert_eq!(names, ["runner-2", "runner-1"]);
+    }
+
+    #[test]
+    fn invalid_regex_filter_hides_everything_and_explains_why() {
+        let (mut app, _, _) = test_app();
+        app.regex_filter = true;
+
+        app.push_filter_char('(');
+        assert!(app.visible_runners().is_empty());
+        assert!(app.filter_error().unwrap().contains("Invalid regex"));
+
+        for c in "1|3)$".chars() {
+            app.push_filter_char(c);
+        }
+        assert_eq!(app.visible_runners(), vec![0, 2]);
+        assert_eq!(app.filter_error(), None);
+    }
+
+    #[test]
+    fn stopping_a_busy_runner_needs_yes_typed() {
+        let (mut app, command_rx, _) = test_app();
+        app.runners[0].busy = true;
+
+        app.stop_selected();
+        assert_eq!(app.mode, AppMode::Confirm);
+        assert!(app.confirm_needs_typing());
+        assert!(app
+            .pending_confirm
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("RUNNING A JOB"));
+
+        app.push_confirm_char('y');
+        app.submit_typed_confirm();
+        assert!(command_rx.try_recv().is_err());
+        assert_eq!(app.status_message.as_deref(), Some("Cancelled"));
+
+        app.stop_selected();
+        for c in "YES".chars() {
+            app.push_confirm_char(c);
+        }
+        app.submit_typed_confirm();
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(WorkerCommand::ControlRunner { action, .. }) if action == "stop"
+        ));
+    }
+
+    #[test]
+    fn idle_runners_stop_without_confirmation() {
+        let (mut app, command_rx, _) = test_app();
+
+        app.stop_selected();
+
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(command_rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn explicit_refresh_skips_cached_service_states() {
+        let (mut app, command_rx, _) = test_app();
+
+        app.refresh(false);
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(WorkerCommand::Refresh { force: false })
+        ));
+
+        app.request_refresh();
+        let force = std::mem::take(&mut app.refresh_requested);
+        assert!(force);
+        app.refresh(force);
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(WorkerCommand::Refresh { force: true })
+        ));
+    }
+
+    #[test]
+    fn idle_slows_refresh_until_input() {
+        let (mut app, _command_rx, _) = test_app();
+        app.idle_after = Some(Duration::from_secs(60));
+
+        assert!(!app.update_idle(Duration::from_secs(61)));
+        assert!(app.idle);
+        assert_eq!(
+            app.refresh_interval(),
+            Duration::from_millis(IDLE_REFRESH_INTERVAL_MS)
+        );
+
+        // Old data is expected while idle, not a stall
+        app.last_update = Instant::now() - Duration::from_secs(5);
+        app.refresh(false);
+        assert!(app.refreshing);
+        assert!(!app.is_refresh_stalled());
+
+        assert!(app.update_idle(Duration::ZERO));
+        assert_eq!(
+            app.refresh_interval(),
+            Duration::from_millis(REFRESH_INTERVAL_MS)
+        );
+    }
+
+    #[test]
+    fn worker_updates_mark_the_screen_dirty() {
+        let (mut app, _, response_tx) = test_app();
+        app.dirty = false;
+
+        app.poll_worker_updates();
+        assert!(!app.dirty);
+
+        response_tx
+            .send(WorkerResponse::ActionComplete {
+                message: "done".to_string(),
+                failure: None,
+            })
+            .unwrap();
+        app.poll_worker_updates();
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn queued_runs_beyond_idle_runners_become_a_backlog() {
+        let (mut app, _, _) = test_app();
+        app.backlog_after = Duration::from_secs(60);
+        let start = Instant::now();
+
+        // Two idle runners keep up with two queued runs
+        app.record_queue_depth("acme".to_string(), 2, start);
+        assert!(app.backlogs["acme"].over_since.is_none());
+
+        app.record_queue_depth("acme".to_string(), 3, start);
+        assert!(!app.is_backlogged("acme"));
+        app.record_queue_depth("acme".to_string(), 3, start + Duration::from_secs(60));
+        assert!(app.is_backlogged("acme"));
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("acme: 3 queued runs for 2 idle runners for 60s")
+        );
+
+        // Busy runners can't take the queue, but the margin allows for it
+        app.runners[0].busy = true;
+        app.runners[2].busy = true;
+        app.backlog_margin = 2;
+        app.record_queue_depth("acme".to_string(), 2, start + Duration::from_secs(90));
+        assert!(!app.is_backlogged("acme"));
+    }
+
+    #[test]
+    fn output_overlay_scrolls_and_returns_to_the_previous_view() {
+        let (mut app, _, _) = test_app();
+        app.mode = AppMode::Detail;
+        app.show_output(OutputView {
+            title: " Connection test ".to_string(),
+            summary: "[systemd] unit is active".to_string(),
+            ok: true,
+            lines: vec!["one".to_string(), "two".to_string(), "three".to_string()],
+            scroll: 0,
+        });
+        assert_eq!(app.mode, AppMode::Output);
+
+        let output = app.output.as_mut().unwrap();
+        output.scroll_down(10);
+        assert_eq!(output.scroll, 2);
+        output.scroll_up(1);
+        assert_eq!(output.scroll, 1);
+
+        app.close_output();
+        assert_eq!(app.mode, AppMode::Detail);
+        assert!(app.output.is_none());
+    }
+
+    #[test]
+    fn failed_actions_open_their_output() {
+        let (mut app, _, response_tx) = test_app();
+        let failure = CommandFailure {
+            context: "Failed to start acme-runner-1".to_string(),
+            command: "sudo systemctl start actions.runner.ci.acme-runner-1".to_string(),
+            status: Some(1),
+            stdout: String::new(),
+            stderr: "Access denied\n".to_string(),
+        };
+        response_tx
+            .send(WorkerResponse::ActionComplete {
+                message: format!("Error: {}", failure),
+                failure: Some(failure),
+            })
+            .unwrap();
+        app.poll_worker_updates();
+
+        assert_eq!(app.mode, AppMode::Output);
+        let output = app.output.as_ref().unwrap();
+        assert!(!output.ok);
+        assert_eq!(output.summary, "Failed to start acme-runner-1");
+        assert!(output.lines.contains(&"Access denied".to_string()));
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Error: Failed to start acme-runner-1: Access denied")
+        );
+    }
+
+    #[test]
+    fn rising_restart_counts_make_a_runner_flapping() {
+        let (mut app, _, _) = test_app();
+        let start = Instant::now();
+        let with_restarts = |count: u32| {
+            let mut runner = app.runners[0].clone();
+            runner.service_state = Some(crate::runner::ServiceState {
+                active: "active".to_string(),
+                sub: "running".to_string(),
+                restarts: Some(count),
+            });
+            runner
+        };
+        let (steady, one_more, two_more, reset) = (
+            with_restarts(14),
+            with_restarts(15),
+            with_restarts(16),
+            with_restarts(0),
+        );
+
+        app.record_restarts(std::slice::from_ref(&steady), start);
+        app.record_restarts(
+            std::slice::from_ref(&steady),
+            start + Duration::from_secs(60),
+        );
+        assert_eq!(app.recent_restarts(&steady), 0);
+        assert!(!app.is_flapping(&steady));
+
+        app.record_restarts(
+            std::slice::from_ref(&one_more),
+            start + Duration::from_secs(120),
+        );
+        assert!(!app.is_flapping(&one_more));
+        app.record_restarts(
+            std::slice::from_ref(&two_more),
+            start + Duration::from_secs(180),
+        );
+        assert!(app.is_flapping(&two_more));
+        assert_eq!(app.restart_rate(&two_more), Some(40.0));
+        assert!(app
+            .action_log
+            .back()
+            .unwrap()
+            .message
+            .contains("acme-runner-1 is flapping"));
+
+        // Long after the last restart, it has settled
+        let later = start + FLAPPING_WINDOW + Duration::from_secs(300);
+        app.record_restarts(std::slice::from_ref(&two_more), later);
+        assert!(!app.is_flapping(&two_more));
+
+        // Started by hand: the count resets, and so does the history
+        app.record_restarts(std::slice::from_ref(&reset), later + Duration::from_secs(5));
+        assert_eq!(app.recent_restarts(&reset), 0);
+    }
+
+    #[test]
+    fn connection_tests_run_on_the_worker_and_open_their_report() {
+        let (mut app, command_rx, response_tx) = test_app();
+        app.probe_selected();
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(WorkerCommand::Probe { runner_index: 0 })
+        ));
+        assert_eq!(app.mode, AppMode::Normal);
+
+        response_tx
+            .send(WorkerResponse::Probed {
+                runner: "acme-runner-1".to_string(),
+                report: ProbeReport {
+                    backend: "systemd",
+                    ok: true,
+                    summary: "unit is active".to_string(),
+                    transcript: vec!["$ systemctl is-active x".to_string()],
+                },
+            })
+            .unwrap();
+        app.poll_worker_updates();
+        assert_eq!(app.mode, AppMode::Output);
+        let output = app.output.as_ref().unwrap();
+        assert_eq!(output.summary, "[systemd] unit is active");
+        assert_eq!(output.title, " Connection test: acme-runner-1 ");
+    }
+
+    #[test]
+    fn label_edits_report_new_labels_or_open_the_error() {
+        let (mut app, _, response_tx) = test_app();
+        response_tx
+            .send(WorkerResponse::LabelsEdited {
+                runner: "acme-runner-1".to_string(),
+                result: Ok(vec!["self-hosted".to_string(), "gpu".to_string()]),
+            })
+            .unwrap();
+        app.poll_worker_updates();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("acme-runner-1: labels now self-hosted, gpu")
+        );
+        assert_eq!(app.mode, AppMode::Normal);
+
+        response_tx
+            .send(WorkerResponse::LabelsEdited {
+                runner: "acme-runner-1".to_string(),
+                result: Err("GitHub refused to add gpu: 422 Validation Failed".to_string()),
+            })
+            .unwrap();
+        app.poll_worker_updates();
+        assert_eq!(app.mode, AppMode::Output);
+        let output = app.output.as_ref().unwrap();
+        assert!(!output.ok);
+        assert_eq!(
+            output.lines,
+            ["GitHub refused to add gpu: 422 Validation Failed"]
+        );
+    }
+
+    #[test]
+    fn deregistering_asks_twice_and_needs_the_runner_name() {
+        let (mut app, command_rx, _) = test_app();
+        app.deregister_selected();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Cannot deregister acme-runner-1: it is running; stop it first (x)")
+        );
+
+        let dir =
+            std::env::temp_dir().join(format!("runner-dashboard-dereg-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("config.sh"), "").unwrap();
+        app.runners[0].status = RunnerStatus::Inactive;
+        app.runners[0].service_installed = false;
+        app.runners[0].path = dir.clone();
+
+        app.deregister_selected();
+        assert!(!app.confirm_needs_typing());
+        app.confirm();
+        assert_eq!(app.mode, AppMode::Confirm);
+        assert!(app.confirm_needs_typing());
+        for c in "yes".chars() {
+            app.push_confirm_char(c);
+        }
+        app.submit_typed_confirm();
+        assert!(command_rx.try_recv().is_err());
+
+        app.deregister_selected();
+        app.confirm();
+        for c in "acme-runner-1".chars() {
+            app.push_confirm_char(c);
+        }
+        app.submit_typed_confirm();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(WorkerCommand::ControlRunner { action, .. }) if action == "deregister"
+        ));
+    }
+
+    #[test]
+    fn restarting_failed_runners_sends_one_bulk_command() {
+        let (mut app, command_rx, _) = test_app();
+        app.runners[0].status = RunnerStatus::Failed;
+
+        app.restart_failed();
+        assert_eq!(app.mode, AppMode::Confirm);
+        assert!(command_rx.try_recv().is_err());
+        app.confirm();
+
+        match command_rx.try_recv() {
+            Ok(WorkerCommand::ControlMany {
+                runner_indices,
+                action,
+                limits,
+            }) => {
+                assert_eq!(runner_indices, vec![0, 1]);
+                assert_eq!(action, "restart");
+                assert_eq!(limits, Config::default().bulk_limits());
+            }
+            _ => panic!("expected a bulk restart"),
+        }
+
+        for runner in &mut app.runners {
+            runner.status = RunnerStatus::Active;
+        }
+        app.restart_failed();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("No failed runners to restart")
+        );
+    }
+
+    #[test]
+    fn log_search_steps_through_matches_and_holds_context() {
+        let (mut app, _, _) = test_app();
+        app.tab = Tab::Logs;
+        app.logs = [
+            "starting",
+            "Job build failed",
+            "idle",
+            "job deploy FAILED",
+            "idle",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        app.start_log_search();
+        for c in "fail".chars() {
+            app.push_log_search_char(c);
+        }
+        app.accept_log_search();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.log_match, Some(1));
+        app.next_log_match();
+        assert_eq!(app.log_match, Some(3));
+        app.next_log_match();
+        assert_eq!(app.log_match, Some(1));
+        app.previous_log_match();
+        assert_eq!(app.log_match, Some(3));
+
+        let context: Vec<String> = (0..20)
+            .map(|i| format!("line {}", i))
+            .chain(["job deploy FAILED".to_string()])
+            .collect();
+        app.show_log_context("job deploy FAILED".to_string(), context);
+        assert_eq!(app.log_match, Some(20));
+        assert_eq!(app.log_scroll, 20 - LOG_CONTEXT_LEAD_IN);
+
+        // Live refreshes don't replace the context until it is closed
+        app.refresh_logs();
+        assert_eq!(app.logs.len(), 21);
+        app.expand_log_context();
+        assert!(app.log_context.is_none());
+        assert!(app.log_follow);
+    }
+
+    #[test]
+    fn idle_runners_are_reaped_once_after_the_configured_time() {
+        let (mut app, command_rx, _) = test_app();
+        app.reap_after = Some(Duration::from_secs(600));
+        app.runners[2].busy = true;
+        let start = Instant::now();
+
+        app.reap_idle_runners(start);
+        app.reap_idle_runners(start + Duration::from_secs(599));
+        assert!(app.action_log.is_empty());
+
+        // Dry run is the default: log, don't stop
+        app.reap_idle_runners(start + Duration::from_secs(600));
+        assert!(command_rx.try_recv().is_err());
+        assert_eq!(
+            app.action_log.back().unwrap().message,
+            "Idle for 600s: would stop acme-runner-1 (reap_dry_run is on)"
+        );
+        assert_eq!(app.action_log.len(), 1);
+
+        app.reap_dry_run = false;
+        app.reap_idle_runners(start + Duration::from_secs(1200));
+        assert!(command_rx.try_recv().is_err());
+
+        // Picking up a job restarts the clock
+        app.runners[0].busy = true;
+        app.reap_idle_runners(start + Duration::from_secs(1300));
+        app.runners[0].busy = false;
+        app.reap_idle_runners(start + Duration::from_secs(1400));
+        app.reap_idle_runners(start + Duration::from_secs(2000));
+        assert!(matches!(
+            command_rx.try_recv(),
+            Ok(WorkerCommand::ControlRunner { runner_index: 0, action }) if action == "stop"
+        ));
+    }
+
+    #[test]
+    fn reaping_is_turned_off_without_busy_detection() {
+        let (mut app, command_rx, _) = test_app();
+        app.reap_after = Some(Duration::from_secs(600));
+        app.reap_dry_run = false;
+        app.busy_detection = false;
+        let start = Instant::now();
+
+        app.reap_idle_runners(start);
+        app.reap_idle_runners(start + Duration::from_secs(1200));
+
+        assert!(command_rx.try_recv().is_err());
+        assert_eq!(app.reap_after, None);
+        assert_eq!(app.action_log.len(), 1);
+        assert!(app.action_log[0]
+            .message
+            .starts_with("Idle runner reaping is off: pgrep"));
     }
 }
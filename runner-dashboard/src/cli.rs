@@ -0,0 +1,169 @@
+use crate::matcher::Matcher;
+use anyhow::Result;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// htop-like dashboard for GitHub Actions self-hosted runners
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about,
+    after_help = "Exit codes (headless modes):\n  \
+                  0  all runners active\n  \
+                  1  at least one runner failed\n  \
+                  2  no failures, but some runners inactive or not found\n\n\
+                  start/stop/restart exit with 0 if every action succeeded, 1 otherwise."
+)]
+// Each of these replaces the TUI, so at most one can be given
+#[command(group(
+    ArgGroup::new("mode")
+        .multiple(false)
+        .args(["status", "csv", "once", "doctor", "serve", "daemon"])
+))]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Print a one-shot status table to stdout instead of starting the TUI
+    #[arg(long)]
+    pub status: bool,
+
+    /// Write the runner list as CSV to FILE and exit
+    #[arg(long, value_name = "FILE")]
+    pub csv: Option<PathBuf>,
+
+    /// Render one frame of the dashboard as text to stdout (or FILE) and exit
+    #[arg(long, value_name = "FILE", num_args = 0..=1)]
+    pub once: Option<Option<PathBuf>>,
+
+    /// Report which service backends and tools are available, whether sudo is
+    /// passwordless, where runners are looked for and what was found, and exit
+    #[arg(long)]
+    pub doctor: bool,
+
+    /// Run control actions on the main thread and wait for each to finish
+    #[arg(long)]
+    pub sync: bool,
+
+    /// Treat runner targets and the interactive filter as regular expressions instead of globs
+    #[arg(long, global = true)]
+    pub regex: bool,
+
+    /// Only discover runners for repository NAME (repeat to include several)
+    #[arg(long = "repo", value_name = "NAME")]
+    pub repos: Vec<String>,
+
+    /// Discover runners under DIR instead of ~/action-runners (repeat to include several)
+    #[arg(long = "dir", value_name = "DIR")]
+    pub dirs: Vec<PathBuf>,
+
+    /// Read RUNNER's logs from SOURCE: `unit:NAME`, `syslog:IDENTIFIER` or `file:PATH`.
+    /// RUNNER is a service name or `repo/name` (repeat for several runners)
+    #[arg(long = "log-source", value_name = "RUNNER=SOURCE")]
+    pub log_sources: Vec<String>,
+
+    /// Account that installed the runner services, if not the current user
+    #[arg(long, value_name = "NAME")]
+    pub runner_user: Option<String>,
+
+    /// Use ASCII instead of Unicode symbols (for terminals that show them as boxes)
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Time each refresh phase (process scan, systemctl/launchctl) and log it: to the
+    /// Events tab in the TUI, the log with --daemon, stderr otherwise
+    #[arg(long, global = true)]
+    pub profile: bool,
+
+    /// Ring the terminal bell when a runner fails (at most once a minute per runner)
+    #[arg(long)]
+    pub bell: bool,
+
+    /// Serve /status (JSON), /metrics (Prometheus) and /healthz over HTTP on ADDR
+    #[arg(long, value_name = "ADDR")]
+    pub serve: Option<String>,
+
+    /// Watch runners without a UI, logging status changes to FILE (`-` for stdout)
+    #[arg(long, value_name = "FILE")]
+    pub daemon: Option<PathBuf>,
+}
+
+/// Control runners without starting the TUI
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start the matching runners
+    Start(ControlArgs),
+    /// Stop the matching runners
+    Stop(ControlArgs),
+    /// Restart the matching runners
+    Restart(ControlArgs),
+}
+
+impl Command {
+    pub fn action(&self) -> &'static str {
+        match self {
+            Command::Start(_) => "start",
+            Command::Stop(_) => "stop",
+            Command::Restart(_) => "restart",
+        }
+    }
+
+    pub fn args(&self) -> &ControlArgs {
+        match self {
+            Command::Start(args) | Command::Stop(args) | Command::Restart(args) => args,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ControlArgs {
+    /// Runner as repo/name or repo/number (e.g. acme/runner-2, acme/2); globs like acme/* or 'acme-*' select several
+    #[arg(required_unless_present = "all")]
+    pub target: Option<String>,
+
+    /// Act on every discovered runner
+    #[arg(long, conflicts_with = "target")]
+    pub all: bool,
+}
+
+impl ControlArgs {
+    /// Matcher for the target; `--all` matches everything whatever the syntax.
+    pub fn matcher(&self, regex: bool) -> Result<Matcher> {
+        match &self.target {
+            Some(target) if !self.all => Matcher::new(target, regex),
+            _ => Matcher::new("*", false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_modes_are_mutually_exclusive() {
+        for args in [
+            ["--status", "--doctor"],
+            ["--csv=out.csv", "--once"],
+            ["--serve=:9100", "--daemon=-"],
+        ] {
+            let error =
+                Cli::try_parse_from(std::iter::once("runner-dashboard").chain(args)).unwrap_err();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+
+        let cli = Cli::try_parse_from(["runner-dashboard", "--status", "--ascii"]).unwrap();
+        assert!(cli.status && cli.ascii);
+    }
+
+    #[test]
+    fn all_matches_every_runner_with_regex_targets_too() {
+        let cli = Cli::try_parse_from(["runner-dashboard", "--regex", "start", "--all"]).unwrap();
+        let matcher = cli.command.unwrap().args().matcher(cli.regex).unwrap();
+        assert!(matches!(matcher, Matcher::Glob(ref pattern) if pattern == "*"));
+
+        let cli = Cli::try_parse_from(["runner-dashboard", "--regex", "stop", "^acme/"]).unwrap();
+        let matcher = cli.command.unwrap().args().matcher(cli.regex).unwrap();
+        assert!(matches!(matcher, Matcher::Regex(_)));
+    }
+}
@@ -0,0 +1,356 @@
+use crate::app::{ListColumn, Thresholds};
+use crate::runner::{JournalFormat, LogSource};
+use crate::service_template::ServiceTemplate;
+use crate::worker::BulkLimits;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Bounds for the list pane width, as a percentage of the main area
+pub const LIST_RATIO_MIN: u16 = 20;
+pub const LIST_RATIO_MAX: u16 = 80;
+
+/// User settings persisted in `<config dir>/runner-dashboard/config.toml`.
+///
+/// Missing keys fall back to their defaults, so older files keep working.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Width of the runner list pane as a percentage of the main area
+    pub list_ratio: u16,
+    /// Show the details pane beside the list; off gives the list the full width
+    pub show_details: bool,
+    /// Columns of the verbose runner list, in order
+    pub list_columns: Vec<ListColumn>,
+    /// Service names of runners kept at the top of the list (toggled with `*`)
+    pub pinned: Vec<String>,
+    /// Account that installed the runner services, used to build their names
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runner_user: Option<String>,
+    /// Directories to discover runners under, instead of ~/action-runners
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub runner_dirs: Vec<String>,
+    /// Template for runner service names; see `ServiceTemplate`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_name_template: Option<String>,
+    /// Slow down refreshes after this many seconds without input; 0 disables
+    pub idle_after_secs: u64,
+    /// Dim the screen while idle
+    pub dim_when_idle: bool,
+    /// Show paths in full instead of abbreviating the home directory to `~`
+    pub full_paths: bool,
+    /// CPU usage (%) above which it shows yellow, and red and alerting
+    pub cpu_warn_percent: f64,
+    pub cpu_alert_percent: f64,
+    /// Memory (and disk) usage (%) above which it shows yellow, and red and alerting
+    pub mem_warn_percent: f64,
+    pub mem_alert_percent: f64,
+    /// Alert once CPU or memory stays above its alert level this long
+    pub alert_after_secs: u64,
+    /// Also raise a desktop notification when an alert starts
+    pub alert_notify: bool,
+    /// CPU (% of one core) and memory a job needs, for the headroom estimate;
+    /// 0 uses the average of the busy runners seen so far
+    pub job_cpu_percent: f64,
+    pub job_memory_mb: u64,
+    /// Flag a repo as backlogged once its queued runs outnumber its idle runners
+    /// (plus `backlog_margin`) for this long
+    pub backlog_after_secs: u64,
+    pub backlog_margin: usize,
+    /// How often to ask GitHub for queued runs when GITHUB_TOKEN is set
+    pub queue_poll_secs: u64,
+    /// Reuse systemctl/launchctl results for this long between refreshes, unless a
+    /// runner's processes came or went; 0 queries on every refresh
+    pub service_cache_secs: u64,
+    /// Stop runners that have been up without a job this long; 0 disables
+    pub reap_idle_after_secs: u64,
+    /// Only log the idle runners that would be stopped
+    pub reap_dry_run: bool,
+    /// Bulk actions act on this many runners at a time, pausing between waves
+    pub bulk_concurrency: usize,
+    pub bulk_gap_secs: u64,
+    /// Draw with ASCII instead of Unicode symbols, for terminals that can't show them
+    pub ascii: bool,
+    /// Restart runners that fail while --daemon is watching them
+    pub daemon_restart_failed: bool,
+    /// Shade usage bars green to red along their length instead of one color
+    pub gradient_bars: bool,
+    /// Commands run (via `sh -c`) when a runner enters or leaves the failed state;
+    /// `{repo}`, `{name}`, `{status}` and `{service}` are filled in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_recover: Option<String>,
+    /// Unix socket that status changes and finished actions are streamed to as
+    /// JSON lines, for same-host integrations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_socket: Option<String>,
+    /// Shell command run by Ctrl-Z while the TUI is suspended; defaults to $SHELL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspend_command: Option<String>,
+    /// `journalctl -o` format for runner logs: short, short-iso, short-precise or cat
+    pub journal_format: JournalFormat,
+    /// Where to read logs for runners that don't log under their service unit:
+    /// service name or `repo/name` to `unit:NAME`, `syslog:IDENTIFIER` or `file:PATH`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub log_sources: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            list_ratio: 50,
+            show_details: true,
+            list_columns: vec![ListColumn::Name, ListColumn::Status, ListColumn::Version],
+            pinned: Vec::new(),
+            runner_user: None,
+            runner_dirs: Vec::new(),
+            service_name_template: None,
+            idle_after_secs: 120,
+            dim_when_idle: true,
+            full_paths: false,
+            cpu_warn_percent: 50.0,
+            cpu_alert_percent: 80.0,
+            mem_warn_percent: 70.0,
+            mem_alert_percent: 90.0,
+            alert_after_secs: 30,
+            alert_notify: false,
+            job_cpu_percent: 0.0,
+            job_memory_mb: 0,
+            backlog_after_secs: 60,
+            backlog_margin: 0,
+            queue_poll_secs: 30,
+            service_cache_secs: 0,
+            reap_idle_after_secs: 0,
+            reap_dry_run: true,
+            bulk_concurrency: 4,
+            bulk_gap_secs: 2,
+            ascii: false,
+            daemon_restart_failed: false,
+            gradient_bars: true,
+            on_failure: None,
+            on_recover: None,
+            event_socket: None,
+            suspend_command: None,
+            journal_format: JournalFormat::ShortIso,
+            log_sources: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file, if the platform has a config directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("runner-dashboard").join("config.toml"))
+    }
+
+    /// The configured service name template, or the standard naming when unset.
+    pub fn service_template(&self) -> Result<ServiceTemplate> {
+        match &self.service_name_template {
+            Some(template) => ServiceTemplate::parse(template)
+                .context("Invalid service_name_template in the config file"),
+            None => Ok(ServiceTemplate::default()),
+        }
+    }
+
+    /// The configured log sources, parsed, by the runner they're for.
+    pub fn log_sources(&self) -> Result<HashMap<String, LogSource>> {
+        self.log_sources
+            .iter()
+            .map(|(runner, spec)| {
+                let source = LogSource::parse(spec).with_context(|| {
+                    format!(
+                        "Invalid log_sources entry for {} in the config file",
+                        runner
+                    )
+                })?;
+                Ok((runner.clone(), source))
+            })
+            .collect()
+    }
+
+    /// The configured runner directories, with `~` expanded.
+    pub fn runner_dirs(&self) -> Vec<PathBuf> {
+        self.runner_dirs
+            .iter()
+            .map(|dir| PathBuf::from(shellexpand::tilde(dir).as_ref()))
+            .collect()
+    }
+
+    /// The configured event socket path, with `~` expanded.
+    pub fn event_socket(&self) -> Option<PathBuf> {
+        self.event_socket
+            .as_deref()
+            .map(|path| PathBuf::from(shellexpand::tilde(path).as_ref()))
+    }
+
+    pub fn bulk_limits(&self) -> BulkLimits {
+        BulkLimits {
+            concurrency: self.bulk_concurrency.max(1),
+            gap: std::time::Duration::from_secs(self.bulk_gap_secs),
+        }
+    }
+
+    pub fn cpu_thresholds(&self) -> Thresholds {
+        Thresholds {
+            warn: self.cpu_warn_percent,
+            alert: self.cpu_alert_percent,
+        }
+    }
+
+    pub fn mem_thresholds(&self) -> Thresholds {
+        Thresholds {
+            warn: self.mem_warn_percent,
+            alert: self.mem_alert_percent,
+        }
+    }
+
+    /// Load the config file, or the defaults if it doesn't exist.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let mut config: Self = toml::from_str(contents)?;
+        config.list_ratio = config.list_ratio.clamp(LIST_RATIO_MIN, LIST_RATIO_MAX);
+        Ok(config)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("Cannot find config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        let contents = toml::to_string_pretty(self)?;
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Load the current file, apply `change`, and write it back.
+    ///
+    /// Re-reading first keeps settings that were edited by hand since startup.
+    pub fn update(change: impl FnOnce(&mut Config)) -> Result<()> {
+        let mut config = Self::load()?;
+        change(&mut config);
+        config.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_use_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn unknown_list_columns_are_rejected() {
+        let config = Config::parse(r#"list_columns = ["repo", "name", "cpu"]"#).unwrap();
+        assert_eq!(
+            config.list_columns,
+            [ListColumn::Repo, ListColumn::Name, ListColumn::Cpu]
+        );
+        assert!(Config::parse(r#"list_columns = ["name", "colour"]"#).is_err());
+    }
+
+    #[test]
+    fn log_sources_are_parsed_by_kind() {
+        let config = Config::parse(
+            r#"
+            [log_sources]
+            "acme/runner-1" = "syslog:acme-ci"
+            "actions.runner.acme.runner-2" = "file:/var/log/runner-2.log"
+            "#,
+        )
+        .unwrap();
+        let sources = config.log_sources().unwrap();
+        assert_eq!(
+            sources["acme/runner-1"],
+            LogSource::Syslog("acme-ci".to_string())
+        );
+        assert_eq!(
+            sources["actions.runner.acme.runner-2"],
+            LogSource::File(PathBuf::from("/var/log/runner-2.log"))
+        );
+
+        let bad = Config::parse("[log_sources]\n\"acme/runner-1\" = \"journal:x\"").unwrap();
+        let error = format!("{:#}", bad.log_sources().unwrap_err());
+        assert!(error.contains("acme/runner-1"));
+        assert!(error.contains("expected unit, syslog or file"));
+    }
+
+    #[test]
+    fn journal_format_must_be_one_journalctl_supports_here() {
+        let config = Config::parse(r#"journal_format = "short-precise""#).unwrap();
+        assert_eq!(config.journal_format, JournalFormat::ShortPrecise);
+        assert!(Config::parse(r#"journal_format = "json""#).is_err());
+    }
+
+    #[test]
+    fn list_ratio_is_clamped() {
+        assert_eq!(Config::parse("list_ratio = 5").unwrap().list_ratio, 20);
+        assert_eq!(Config::parse("list_ratio = 95").unwrap().list_ratio, 80);
+        assert_eq!(Config::parse("list_ratio = 65").unwrap().list_ratio, 65);
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let config = Config {
+            list_ratio: 35,
+            show_details: false,
+            list_columns: vec![ListColumn::Repo, ListColumn::Name, ListColumn::Cpu],
+            pinned: vec!["actions.runner.acme.runner-1".to_string()],
+            runner_user: Some("builder".to_string()),
+            runner_dirs: vec!["~/action-runners".to_string(), "/srv/runners".to_string()],
+            service_name_template: Some("actions.runner.{user}.{name}".to_string()),
+            idle_after_secs: 0,
+            dim_when_idle: false,
+            full_paths: true,
+            cpu_warn_percent: 60.0,
+            cpu_alert_percent: 95.5,
+            mem_warn_percent: 75.0,
+            mem_alert_percent: 95.0,
+            alert_after_secs: 10,
+            alert_notify: true,
+            job_cpu_percent: 150.0,
+            job_memory_mb: 2048,
+            backlog_after_secs: 120,
+            backlog_margin: 2,
+            queue_poll_secs: 15,
+            service_cache_secs: 5,
+            reap_idle_after_secs: 1800,
+            reap_dry_run: false,
+            bulk_concurrency: 8,
+            bulk_gap_secs: 0,
+            ascii: true,
+            daemon_restart_failed: true,
+            gradient_bars: false,
+            on_failure: Some("/usr/local/bin/page-oncall {repo} {name}".to_string()),
+            on_recover: None,
+            event_socket: Some("~/.cache/runner-dashboard/events.sock".to_string()),
+            suspend_command: Some("htop".to_string()),
+            journal_format: JournalFormat::ShortPrecise,
+            log_sources: BTreeMap::from([(
+                "acme/runner-1".to_string(),
+                "syslog:acme-ci".to_string(),
+            )]),
+        };
+        let contents = toml::to_string_pretty(&config).unwrap();
+
+        assert_eq!(Config::parse(&contents).unwrap(), config);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::runner::{
+    civil_from_days, control_runner, discover_runners, missing_tools, refresh_runners,
+    DiscoveryOptions, Runner, RunnerStatus, SystemCommandRunner, UnitCache,
+};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often the daemon re-polls runner statuses
+const DAEMON_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long to sleep between checks for shutdown
+const SHUTDOWN_POLL: Duration = Duration::from_millis(200);
+
+/// What the daemon remembers about a runner between refreshes
+#[derive(Debug, Clone, PartialEq)]
+struct Seen {
+    status: RunnerStatus,
+    busy: bool,
+}
+
+/// Watch runners headlessly until SIGINT/SIGTERM, logging each change to `log_path`.
+///
+/// `-` logs to stdout, which is where a systemd unit running the daemon wants it.
+/// With `restart_failed`, a runner is restarted when it enters the failed state,
+/// but not again on every refresh while it stays failed. With `profile`, each
+/// refresh also logs how long its phases took.
+pub fn run(
+    log_path: &Path,
+    options: &DiscoveryOptions,
+    restart_failed: bool,
+    profile: bool,
+) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&shutdown))?;
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&shutdown))?;
+
+    let mut log = open_log(log_path)?;
+    let cmd = &SystemCommandRunner;
+    let units = UnitCache::load(cmd);
+    let mut runners = discover_runners(options)?;
+    refresh_runners(cmd, &mut runners, &units);
+
+    write_line(
+        &mut log,
+        &format!(
+            "Watching {} runners (restart failed: {})",
+            runners.len(),
+            if restart_failed { "on" } else { "off" }
+        ),
+    )?;
+    for tool in missing_tools(std::env::var_os("PATH").as_deref()) {
+        write_line(&mut log, &format!("Warning: {}", tool))?;
+    }
+    for runner in &runners {
+        write_line(
+            &mut log,
+            &format!("{}: {}", label(runner), runner.status.as_str()),
+        )?;
+    }
+    let mut seen = snapshot(&runners);
+    let mut last_refresh = Instant::now();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        if last_refresh.elapsed() < DAEMON_REFRESH_INTERVAL {
+            std::thread::sleep(SHUTDOWN_POLL);
+            continue;
+        }
+        let timings = refresh_runners(cmd, &mut runners, &units);
+        last_refresh = Instant::now();
+        if profile {
+            write_line(&mut log, &timings.describe())?;
+        }
+
+        for (runner, change) in changes(&seen, &runners) {
+            write_line(&mut log, &format!("{}: {}", label(runner), change))?;
+        }
+        if restart_failed {
+            for runner in newly_failed(&seen, &runners) {
+                let message = match control_runner(cmd, runner, "restart") {
+                    Ok(message) => message,
+                    Err(e) => format!("Error: {:#}", e),
+                };
+                write_line(&mut log, &format!("{}: {}", label(runner), message))?;
+            }
+        }
+        seen = snapshot(&runners);
+    }
+
+    write_line(&mut log, "Shutting down")
+}
+
+fn open_log(path: &Path) -> Result<Box<dyn Write>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(std::io::stdout()));
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open daemon log {}", path.display()))?;
+    Ok(Box::new(file))
+}
+
+/// Write one timestamped line and flush it, so `tail -f` sees it right away.
+fn write_line(log: &mut dyn Write, message: &str) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    writeln!(log, "{} {}", format_utc(now), message)?;
+    log.flush()?;
+    Ok(())
+}
+
+fn label(runner: &Runner) -> String {
+    format!("{}/{}", runner.repo, runner.name)
+}
+
+fn snapshot(runners: &[Runner]) -> HashMap<PathBuf, Seen> {
+    runners
+        .iter()
+        .map(|r| {
+            let seen = Seen {
+                status: r.status.clone(),
+                busy: r.busy,
+            };
+            (r.path.clone(), seen)
+        })
+        .collect()
+}
+
+/// What changed for each runner since `seen`, described for the log.
+fn changes<'a>(seen: &HashMap<PathBuf, Seen>, runners: &'a [Runner]) -> Vec<(&'a Runner, String)> {
+    let mut changes = Vec::new();
+    for runner in runners {
+        let Some(before) = seen.get(&runner.path) else {
+            continue;
+        };
+        if before.status != runner.status {
+            changes.push((
+                runner,
+                format!("{} -> {}", before.status.as_str(), runner.status.as_str()),
+            ));
+        }
+        if before.busy != runner.busy {
+            let job = if runner.busy {
+                "started a job"
+            } else {
+                "finished a job"
+            };
+            changes.push((runner, job.to_string()));
+        }
+    }
+    changes
+}
+
+/// Runners that were not failed at the last refresh and are now
+fn newly_failed<'a>(seen: &HashMap<PathBuf, Seen>, runners: &'a [Runner]) -> Vec<&'a Runner> {
+    runners
+        .iter()
+        .filter(|r| r.status == RunnerStatus::Failed)
+        .filter(|r| {
+            seen.get(&r.path)
+                .is_some_and(|before| before.status != RunnerStatus::Failed)
+        })
+        .collect()
+}
+
+/// `2024-05-01T12:34:56Z` for a Unix time
+fn format_utc(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let time = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+
+    #[test]
+    fn changes_report_status_and_job_transitions() {
+        let before = vec![
+            test_runner("acme", 1),
+            Runner {
+                busy: true,
+                ..test_runner("acme", 2)
+            },
+            Runner {
+                status: RunnerStatus::Inactive,
+                ..test_runner("acme", 3)
+            },
+        ];
+        let seen = snapshot(&before);
+        let after = vec![
+            Runner {
+                status: RunnerStatus::Failed,
+                ..test_runner("acme", 1)
+            },
+            test_runner("acme", 2),
+            Runner {
+                status: RunnerStatus::Inactive,
+                ..test_runner("acme", 3)
+            },
+            test_runner("acme", 4),
+        ];
+
+        let logged: Vec<String> = changes(&seen, &after)
+            .into_iter()
+            .map(|(r, change)| format!("{}: {}", label(r), change))
+            .collect();
+        assert_eq!(
+            logged,
+            [
+                "acme/runner-1: active -> failed",
+                "acme/runner-2: finished a job"
+            ]
+        );
+
+        // Only the transition is restarted; a runner still failed is left alone
+        let restarted: Vec<u32> = newly_failed(&seen, &after)
+            .iter()
+            .map(|r| r.number)
+            .collect();
+        assert_eq!(restarted, [1]);
+        assert!(newly_failed(&snapshot(&after), &after).is_empty());
+    }
+
+    #[test]
+    fn timestamps_format_as_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(1_714_566_896), "2024-05-01T12:34:56Z");
+        assert_eq!(format_utc(1_709_170_200), "2024-02-29T01:30:00Z");
+    }
+}
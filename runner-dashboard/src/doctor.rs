@@ -0,0 +1,291 @@
+use crate::runner::{
+    discover_runners, duplicate_service_names, find_executable, installed_service_names,
+    missing_tools, refresh_runners, resolve_runner_user, CommandRunner, DiscoveryOptions, Runner,
+    RunnerStatus, UnitCache, UserSource,
+};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+/// Service managers runners can be installed under, in report order
+const BACKENDS: [&str; 3] = ["systemctl", "launchctl", "sc"];
+/// Other tools status, logs and actions go through
+const TOOLS: [&str; 3] = ["pgrep", "journalctl", "sudo"];
+
+/// The service manager the dashboard drives on this platform
+fn platform_backend() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "launchctl"
+    } else {
+        "systemctl"
+    }
+}
+
+/// What the dashboard can see on this host, for `--doctor`
+#[derive(Debug)]
+pub struct Diagnosis {
+    /// Each backend and tool looked for, and where it was found on $PATH
+    pub tools: Vec<(&'static str, Option<PathBuf>)>,
+    /// Whether `sudo -n true` succeeded; None without sudo
+    pub passwordless_sudo: Option<bool>,
+    /// Directories runners are discovered under, and whether each exists
+    pub roots: Vec<(PathBuf, bool)>,
+    pub user: String,
+    pub user_source: UserSource,
+    pub runners: Vec<Runner>,
+    /// From `missing_tools`
+    pub missing_tools: Vec<String>,
+}
+
+/// Look for the service backends and tools on `path`, then discover and refresh
+/// runners the way the dashboard does.
+pub fn diagnose(
+    cmd: &dyn CommandRunner,
+    options: &DiscoveryOptions,
+    path: Option<&OsStr>,
+) -> Result<Diagnosis> {
+    let tools: Vec<(&'static str, Option<PathBuf>)> = BACKENDS
+        .into_iter()
+        .chain(TOOLS)
+        .map(|name| (name, find_executable(name, path)))
+        .collect();
+    let has_sudo = tools
+        .iter()
+        .any(|(name, at)| *name == "sudo" && at.is_some());
+    let passwordless_sudo = has_sudo
+        .then(|| cmd.run("sudo", &["-n", "true"]).ok())
+        .flatten()
+        .map(|output| output.status.success());
+
+    let roots = options
+        .roots()?
+        .into_iter()
+        .map(|root| {
+            let exists = root.is_dir();
+            (root, exists)
+        })
+        .collect();
+
+    let installed = match options.runner_user {
+        Some(_) => HashSet::new(),
+        None => installed_service_names(cmd, &options.service_template),
+    };
+    let (user, user_source) = resolve_runner_user(options, &installed);
+
+    let mut runners = discover_runners(options)?;
+    refresh_runners(cmd, &mut runners, &UnitCache::load(cmd));
+
+    Ok(Diagnosis {
+        tools,
+        passwordless_sudo,
+        roots,
+        user,
+        user_source,
+        runners,
+        missing_tools: missing_tools(path),
+    })
+}
+
+impl Diagnosis {
+    /// Problems that explain runners showing as not found, or actions failing.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = self.missing_tools.clone();
+        if self.passwordless_sudo == Some(false) && !cfg!(target_os = "macos") {
+            warnings.push(
+                "sudo asks for a password, so start/stop/restart (sudo systemctl) will fail \
+                 from the dashboard"
+                    .to_string(),
+            );
+        }
+        for (root, _) in self.roots.iter().filter(|(_, exists)| !exists) {
+            warnings.push(format!("{} does not exist", root.display()));
+        }
+        if self.runners.is_empty() {
+            warnings.push(
+                "No runners found; --dir or runner_dirs should name the directory holding \
+                 the repository folders"
+                    .to_string(),
+            );
+        }
+        let mut shared: Vec<String> = duplicate_service_names(&self.runners).into_iter().collect();
+        shared.sort();
+        for service in shared {
+            warnings.push(format!(
+                "Several runners share the service name {}; their status can't be told apart",
+                service
+            ));
+        }
+        for runner in &self.runners {
+            if runner.status == RunnerStatus::NotFound && !runner.service_installed {
+                let hint = match self.user_source {
+                    UserSource::Environment => " (installed by another account? try --runner-user)",
+                    _ => "",
+                };
+                warnings.push(format!(
+                    "{}/{}: no service named {} is installed{}",
+                    runner.repo, runner.name, runner.service_name, hint
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// The report `--doctor` prints.
+    pub fn render(&self) -> String {
+        let name_width = self
+            .tools
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        let tool_line = |name: &str, at: &Option<PathBuf>, note: String| match at {
+            Some(at) => format!("  {:<name_width$}  {}{}\n", name, at.display(), note),
+            None => format!("  {:<name_width$}  not found{}\n", name, note),
+        };
+
+        let mut report = String::from("Service backends\n");
+        for (name, at) in self
+            .tools
+            .iter()
+            .filter(|(name, _)| BACKENDS.contains(name))
+        {
+            let note = if *name == platform_backend() {
+                " (used on this platform)".to_string()
+            } else {
+                String::new()
+            };
+            report.push_str(&tool_line(name, at, note));
+        }
+
+        report.push_str("\nTools\n");
+        for (name, at) in self.tools.iter().filter(|(name, _)| TOOLS.contains(name)) {
+            let note = match (*name, self.passwordless_sudo) {
+                ("sudo", Some(true)) => " (passwordless)".to_string(),
+                ("sudo", Some(false)) => " (asks for a password)".to_string(),
+                _ => String::new(),
+            };
+            report.push_str(&tool_line(name, at, note));
+        }
+
+        report.push_str("\nRunner directories\n");
+        for (root, exists) in &self.roots {
+            let found = if *exists {
+                let count = self.runners.iter().filter(|r| &r.root == root).count();
+                format!("{} runner{}", count, if count == 1 { "" } else { "s" })
+            } else {
+                "missing".to_string()
+            };
+            report.push_str(&format!("  {}  {}\n", root.display(), found));
+        }
+
+        report.push_str(&format!(
+            "\nService user\n  {} ({})\n",
+            self.user,
+            self.user_source.describe()
+        ));
+
+        let counts: Vec<String> = [
+            RunnerStatus::Active,
+            RunnerStatus::Inactive,
+            RunnerStatus::Failed,
+            RunnerStatus::NotFound,
+        ]
+        .iter()
+        .filter_map(|status| {
+            let count = self.runners.iter().filter(|r| &r.status == status).count();
+            (count > 0).then(|| format!("{} {}", count, status.as_str()))
+        })
+        .collect();
+        report.push_str(&format!("\nRunners\n  {} found", self.runners.len()));
+        if !counts.is_empty() {
+            report.push_str(&format!(": {}", counts.join(", ")));
+        }
+        report.push('\n');
+
+        report.push_str("\nWarnings\n");
+        let warnings = self.warnings();
+        if warnings.is_empty() {
+            report.push_str("  none\n");
+        }
+        for warning in warnings {
+            report.push_str(&format!("  {}\n", warning));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+
+    fn diagnosis() -> Diagnosis {
+        Diagnosis {
+            tools: vec![
+                ("systemctl", Some(PathBuf::from("/usr/bin/systemctl"))),
+                ("launchctl", None),
+                ("sc", None),
+                ("pgrep", Some(PathBuf::from("/usr/bin/pgrep"))),
+                ("journalctl", None),
+                ("sudo", Some(PathBuf::from("/usr/bin/sudo"))),
+            ],
+            passwordless_sudo: Some(true),
+            roots: vec![
+                (PathBuf::from("/nonexistent/action-runners"), true),
+                (PathBuf::from("/srv/runners"), false),
+            ],
+            user: "ci".to_string(),
+            user_source: UserSource::Environment,
+            runners: vec![
+                test_runner("acme", 1),
+                Runner {
+                    status: RunnerStatus::NotFound,
+                    service_installed: false,
+                    ..test_runner("acme", 2)
+                },
+            ],
+            missing_tools: vec!["journalctl not found — logs limited to _diag files".to_string()],
+        }
+    }
+
+    #[test]
+    fn report_covers_backends_directories_user_and_runners() {
+        let report = diagnosis().render();
+
+        assert!(
+            report.contains("  systemctl   /usr/bin/systemctl"),
+            "{}",
+            report
+        );
+        assert!(report.contains("  launchctl   not found"));
+        assert!(report.contains("  sudo        /usr/bin/sudo (passwordless)"));
+        assert!(
+            report.contains("  /nonexistent/action-runners  2 runners\n  /srv/runners  missing")
+        );
+        assert!(report.contains("  ci (from $USER)"));
+        assert!(report.contains("  2 found: 1 active, 1 not-found"));
+    }
+
+    #[test]
+    fn warnings_explain_runners_that_are_not_found() {
+        let mut diagnosis = diagnosis();
+        let warnings = diagnosis.warnings();
+
+        assert_eq!(
+            warnings[0],
+            "journalctl not found — logs limited to _diag files"
+        );
+        assert!(warnings.contains(&"/srv/runners does not exist".to_string()));
+        assert!(warnings.iter().any(|w| w.starts_with(
+            "acme/runner-2: no service named actions.runner.ci.acme-runner-2 is installed \
+             (installed by another account?"
+        )));
+
+        diagnosis.missing_tools.clear();
+        diagnosis.roots.truncate(1);
+        diagnosis.runners.truncate(1);
+        assert!(diagnosis.warnings().is_empty());
+        assert!(diagnosis.render().ends_with("Warnings\n  none\n"));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::runner::{Runner, RunnerStatus};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+/// Something a same-host integration (tray icon, editor plugin) wants to hear about
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A runner's status changed between two refreshes
+    Status {
+        runner: String,
+        from: &'static str,
+        to: &'static str,
+    },
+    /// A control action on a runner finished
+    Action {
+        runner: String,
+        action: String,
+        ok: bool,
+        message: String,
+    },
+}
+
+/// A Unix domain socket that streams events, one JSON object per line, to every
+/// connected client.
+///
+/// Clients are never read from. One that disconnects, or falls so far behind that
+/// its buffer fills up, is dropped rather than allowed to stall the worker.
+pub struct EventSocket {
+    path: PathBuf,
+    listener: UnixListener,
+    clients: Vec<UnixStream>,
+    /// Status of each runner at the last refresh, to tell what changed
+    statuses: Option<HashMap<PathBuf, RunnerStatus>>,
+}
+
+impl EventSocket {
+    /// Listen on `path`, replacing a socket left behind by an earlier run.
+    ///
+    /// Missing parent directories are created private to the user. Anything at
+    /// `path` other than a socket is left alone.
+    pub fn bind(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) => {
+                if !metadata.file_type().is_socket() {
+                    bail!(
+                        "Event socket path {} exists and is not a socket",
+                        path.display()
+                    );
+                }
+                if UnixStream::connect(path).is_ok() {
+                    bail!("Event socket {} is already in use", path.display());
+                }
+                std::fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to check {}", path.display())),
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind event socket {}", path.display()))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            listener,
+            clients: Vec::new(),
+            statuses: None,
+        })
+    }
+
+    /// Publish a status event for each runner whose status changed since the last
+    /// call. The first call only records where things stand.
+    pub fn runners_updated(&mut self, runners: &[Runner]) {
+        let current: HashMap<PathBuf, RunnerStatus> = runners
+            .iter()
+            .map(|r| (r.path.clone(), r.status.clone()))
+            .collect();
+        if let Some(previous) = self.statuses.replace(current) {
+            for event in status_changes(&previous, runners) {
+                self.publish(&event);
+            }
+        }
+    }
+
+    /// Send `event` to every connected client, dropping the ones that are gone.
+    pub fn publish(&mut self, event: &Event) {
+        self.accept();
+        let Ok(mut line) = serde_json::to_string(event) else {
+            return;
+        };
+        line.push('\n');
+        self.clients
+            .retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+    }
+
+    /// Take on any clients that connected since the last event.
+    fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((client, _)) => {
+                    // A client that stops reading must not block the worker
+                    if client.set_nonblocking(true).is_ok() {
+                        self.clients.push(client);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl Drop for EventSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Status events for runners whose status differs from `previous`. Runners that
+/// were not there before are left out.
+fn status_changes(previous: &HashMap<PathBuf, RunnerStatus>, runners: &[Runner]) -> Vec<Event> {
+    runners
+        .iter()
+        .filter_map(|runner| {
+            let before = previous.get(&runner.path)?;
+            (*before != runner.status).then(|| Event::Status {
+                runner: format!("{}/{}", runner.repo, runner.name),
+                from: before.as_str(),
+                to: runner.status.as_str(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn clients_receive_status_changes_as_json_lines() {
+        let dir = std::env::temp_dir().join(format!("runner-events-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.sock");
+
+        let mut socket = EventSocket::bind(&path).unwrap();
+        socket.runners_updated(&[test_runner("acme", 1), test_runner("acme", 2)]);
+
+        let client = UnixStream::connect(&path).unwrap();
+        // One that hangs up straight away is dropped on the next event
+        drop(UnixStream::connect(&path).unwrap());
+
+        socket.runners_updated(&[
+            Runner {
+                status: RunnerStatus::Failed,
+                ..test_runner("acme", 1)
+            },
+            test_runner("acme", 2),
+            test_runner("acme", 3),
+        ]);
+        socket.publish(&Event::Action {
+            runner: "acme/runner-1".to_string(),
+            action: "restart".to_string(),
+            ok: true,
+            message: "Restarted acme-runner-1".to_string(),
+        });
+        assert_eq!(socket.clients.len(), 1);
+
+        let mut lines = BufReader::new(client).lines();
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"{"event":"status","runner":"acme/runner-1","from":"active","to":"failed"}"#
+        );
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"{"event":"action","runner":"acme/runner-1","action":"restart","ok":true,"message":"Restarted acme-runner-1"}"#
+        );
+
+        // Binding again replaces the socket only once nobody is listening on it
+        assert!(EventSocket::bind(&path).is_err());
+        drop(socket);
+        assert!(!path.exists());
+        drop(EventSocket::bind(&path).unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn binding_creates_the_directory_and_never_replaces_other_files() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("runner-events-dir-{}", std::process::id()));
+        let path = dir.join("nested/events.sock");
+
+        let socket = EventSocket::bind(&path).unwrap();
+        let mode = std::fs::metadata(path.parent().unwrap())
+            .unwrap()
+            .permissions()
+            .mode();
+        drop(socket);
+
+        let file = dir.join("notes.txt");
+        std::fs::write(&file, "keep me").unwrap();
+        let error = EventSocket::bind(&file).err().unwrap().to_string();
+        let kept = std::fs::read_to_string(&file).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(error.contains("is not a socket"), "{}", error);
+        assert_eq!(kept, "keep me");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::runner::{AgentSettings, Runner};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// One setting compared across the fleet
+#[derive(Debug, PartialEq)]
+pub struct FleetRow {
+    pub setting: &'static str,
+    /// The setting on each runner, in the order given; None where it isn't known
+    pub values: Vec<Option<String>>,
+    /// The value more runners share than any other, if there is one
+    pub majority: Option<String>,
+}
+
+impl FleetRow {
+    /// Whether the value on runner `index` is known and differs from the majority
+    pub fn differs(&self, index: usize) -> bool {
+        match (&self.majority, self.values.get(index)) {
+            (Some(majority), Some(Some(value))) => value != majority,
+            _ => false,
+        }
+    }
+
+    /// Number of runners whose value differs from the majority
+    pub fn drift_count(&self) -> usize {
+        (0..self.values.len()).filter(|&i| self.differs(i)).count()
+    }
+}
+
+/// Reads one compared setting off a runner and its agent settings
+type SettingValue = fn(&Runner, &AgentSettings) -> Option<String>;
+
+/// Tabulate version, labels and agent settings for `runners`, one row per setting.
+///
+/// `settings` holds what was read from each runner directory, by path; runners
+/// missing from it are treated as unknown rather than different.
+pub fn compare(runners: &[&Runner], settings: &HashMap<PathBuf, AgentSettings>) -> Vec<FleetRow> {
+    let agent = |runner: &Runner| settings.get(&runner.path).cloned().unwrap_or_default();
+    let rows: [(&'static str, SettingValue); 7] = [
+        ("Version", |runner, _| runner.version.clone()),
+        ("Labels", |runner, _| {
+            // Order doesn't matter to job matching, so don't let it look like drift
+            let mut labels = runner.labels.clone()?;
+            labels.sort_by_key(|l| l.to_lowercase());
+            Some(labels.join(", "))
+        }),
+        ("Platform", |_, agent| agent.platform.clone()),
+        ("Runner group", |_, agent| agent.runner_group.clone()),
+        ("Ephemeral", |_, agent| yes_no(agent.ephemeral)),
+        ("Auto-update", |_, agent| yes_no(agent.auto_update)),
+        ("Work folder", |_, agent| agent.work_folder.clone()),
+    ];
+    let agents: Vec<AgentSettings> = runners.iter().map(|r| agent(r)).collect();
+    rows.iter()
+        .map(|(setting, value)| {
+            let values: Vec<Option<String>> = runners
+                .iter()
+                .zip(&agents)
+                .map(|(runner, agent)| value(runner, agent))
+                .collect();
+            FleetRow {
+                setting,
+                majority: majority(&values),
+                values,
+            }
+        })
+        .collect()
+}
+
+fn yes_no(flag: Option<bool>) -> Option<String> {
+    flag.map(|on| if on { "yes" } else { "no" }.to_string())
+}
+
+/// The most common known value, unless another is just as common.
+fn majority(values: &[Option<String>]) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for value in values.iter().flatten() {
+        *counts.entry(value).or_default() += 1;
+    }
+    let top = *counts.values().max()?;
+    let mut leaders = counts.iter().filter(|(_, &count)| count == top);
+    let (value, _) = leaders.next()?;
+    leaders.next().is_none().then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+
+    #[test]
+    fn values_off_the_majority_are_flagged() {
+        let runner = |number, version: &str, labels: &[&str]| Runner {
+            version: Some(version.to_string()),
+            labels: Some(labels.iter().map(|l| l.to_string()).collect()),
+            ..test_runner("acme", number)
+        };
+        let runners = [
+            runner(1, "2.320.0", &["self-hosted", "gpu"]),
+            runner(2, "2.320.0", &["gpu", "self-hosted"]),
+            runner(3, "2.311.0", &["self-hosted"]),
+        ];
+        let unknown = Runner {
+            version: Some("2.320.0".to_string()),
+            ..test_runner("acme", 4)
+        };
+        let refs: Vec<&Runner> = runners.iter().chain([&unknown]).collect();
+        let settings = HashMap::from([(
+            runners[0].path.clone(),
+            AgentSettings {
+                ephemeral: Some(true),
+                ..AgentSettings::default()
+            },
+        )]);
+
+        let rows = compare(&refs, &settings);
+        let row = |setting: &str| rows.iter().find(|r| r.setting == setting).unwrap();
+
+        let version = row("Version");
+        assert_eq!(version.majority.as_deref(), Some("2.320.0"));
+        assert_eq!(
+            (0..4).map(|i| version.differs(i)).collect::<Vec<_>>(),
+            [false, false, true, false]
+        );
+
+        // Label order is ignored, and an unfetched list isn't drift
+        let labels = row("Labels");
+        assert_eq!(labels.values[1].as_deref(), Some("gpu, self-hosted"));
+        assert_eq!(labels.drift_count(), 1);
+        assert!(!labels.differs(3));
+
+        // One known value is the majority of itself
+        assert_eq!(row("Ephemeral").majority.as_deref(), Some("yes"));
+        assert_eq!(row("Ephemeral").drift_count(), 0);
+    }
+
+    #[test]
+    fn a_tie_has_no_majority() {
+        let values = [Some("a".to_string()), Some("b".to_string()), None];
+        assert_eq!(majority(&values), None);
+        assert_eq!(majority(&[None, None]), None);
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const API_BASE: &str = "https://api.github.com";
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// GitHub token from the environment, if one is configured.
+pub fn token() -> Option<String> {
+    std::env::var("GITHUB_TOKEN")
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS)))
+        .build()
+        .into()
+}
+
+/// Perform an authenticated GET against the GitHub REST API and decode the JSON body.
+fn get_json<T: for<'de> Deserialize<'de>>(token: &str, path: &str) -> Result<T> {
+    let url = format!("{}{}", API_BASE, path);
+    let mut response = agent()
+        .get(&url)
+        .header("Authorization", &format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "runner-dashboard")
+        .call()
+        .with_context(|| format!("GitHub request failed: GET {}", path))?;
+
+    response
+        .body_mut()
+        .read_json()
+        .with_context(|| format!("Invalid GitHub response for GET {}", path))
+}
+
+/// Perform an authenticated POST without a body and decode the JSON response.
+fn post_json<T: for<'de> Deserialize<'de>>(token: &str, path: &str) -> Result<T> {
+    let url = format!("{}{}", API_BASE, path);
+    let mut response = agent()
+        .post(&url)
+        .header("Authorization", &format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "runner-dashboard")
+        .send_empty()
+        .with_context(|| format!("GitHub request failed: POST {}", path))?;
+
+    response
+        .body_mut()
+        .read_json()
+        .with_context(|| format!("Invalid GitHub response for POST {}", path))
+}
+
+#[derive(Deserialize)]
+struct RemoveToken {
+    token: String,
+}
+
+/// Short-lived token for `config.sh remove` on a runner of `owner/repo`.
+pub fn runner_remove_token(token: &str, repo: &str) -> Result<String> {
+    let path = format!("/repos/{}/actions/runners/remove-token", repo);
+    let response: RemoveToken = post_json(token, &path)?;
+    Ok(response.token)
+}
+
+#[derive(Deserialize)]
+struct WorkflowRuns {
+    total_count: usize,
+}
+
+/// Number of workflow runs in `owner/repo` waiting for a runner to pick them up.
+pub fn queued_runs(token: &str, repo: &str) -> Result<usize> {
+    let path = format!("/repos/{}/actions/runs?status=queued&per_page=1", repo);
+    let runs: WorkflowRuns = get_json(token, &path)?;
+    Ok(runs.total_count)
+}
+
+/// A self-hosted runner as GitHub has it registered
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegisteredRunner {
+    pub name: String,
+    /// "online" or "offline"
+    pub status: String,
+    #[serde(default)]
+    pub labels: Vec<RunnerLabel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunnerLabel {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct RunnerPage {
+    total_count: usize,
+    runners: Vec<RegisteredRunner>,
+}
+
+/// Runners registered with `owner/repo`. Needs a token with admin access to it.
+pub fn repo_runners(token: &str, repo: &str) -> Result<Vec<RegisteredRunner>> {
+    let mut runners = Vec::new();
+    for page in 1.. {
+        let path = format!("/repos/{}/actions/runners?per_page=100&page={}", repo, page);
+        let response: RunnerPage = get_json(token, &path)?;
+        let last = response.runners.is_empty();
+        runners.extend(response.runners);
+        if last || runners.len() >= response.total_count {
+            break;
+        }
+    }
+    Ok(runners)
+}
+
+/// Longest label name GitHub accepts
+const MAX_LABEL_LEN: usize = 256;
+
+/// Labels to add to and remove from a runner
+#[derive(Debug, Default, PartialEq)]
+pub struct LabelEdits {
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+/// Parse label edits typed as words: `gpu` or `+gpu` adds a label, `-gpu` removes it.
+pub fn parse_label_edits(input: &str) -> Result<LabelEdits> {
+    let mut edits = LabelEdits::default();
+    for word in input.split_whitespace() {
+        let (list, label) = match word.strip_prefix('-') {
+            Some(label) => (&mut edits.remove, label),
+            None => (&mut edits.add, word.strip_prefix('+').unwrap_or(word)),
+        };
+        if label.is_empty() {
+            bail!("'{}' has no label name", word);
+        }
+        if label.chars().count() > MAX_LABEL_LEN {
+            bail!(
+                "Label '{}...' is longer than {} characters",
+                label.chars().take(16).collect::<String>(),
+                MAX_LABEL_LEN
+            );
+        }
+        if let Some(c) = label.chars().find(|c| *c == ',' || c.is_control()) {
+            bail!(
+                "Label '{}' contains {:?}, which GitHub doesn't allow",
+                label,
+                c
+            );
+        }
+        if !list.iter().any(|l| l.eq_ignore_ascii_case(label)) {
+            list.push(label.to_string());
+        }
+    }
+    if edits.add.is_empty() && edits.remove.is_empty() {
+        bail!("Type labels to add, or -label to remove one");
+    }
+    Ok(edits)
+}
+
+#[derive(Serialize)]
+struct AddLabels<'a> {
+    labels: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct LabelList {
+    labels: Vec<RunnerLabel>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+/// Decode a label list response, or turn GitHub's error message into the error.
+fn read_labels(mut response: ureq::http::Response<ureq::Body>, what: &str) -> Result<Vec<String>> {
+    let status = response.status();
+    if !status.is_success() {
+        let message = response
+            .body_mut()
+            .read_json::<ApiError>()
+            .map(|e| e.message)
+            .unwrap_or_default();
+        let hint = match status.as_u16() {
+            401 => " (is GITHUB_TOKEN valid?)",
+            403 | 404 => " (does the token have admin access to the repository?)",
+            _ => "",
+        };
+        bail!(
+            "GitHub refused to {}: {} {}{}",
+            what,
+            status.as_u16(),
+            message,
+            hint
+        );
+    }
+    let list: LabelList = response
+        .body_mut()
+        .read_json()
+        .with_context(|| format!("Invalid GitHub response when trying to {}", what))?;
+    Ok(list.labels.into_iter().map(|l| l.name).collect())
+}
+
+/// Agent whose error statuses come back as responses, so their message can be read
+fn label_agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS)))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+/// Add `labels` to runner `id` of `owner/repo`, returning all of its labels.
+pub fn add_runner_labels(
+    token: &str,
+    repo: &str,
+    id: u64,
+    labels: &[String],
+) -> Result<Vec<String>> {
+    let path = format!("/repos/{}/actions/runners/{}/labels", repo, id);
+    let response = label_agent()
+        .post(&format!("{}{}", API_BASE, path))
+        .header("Authorization", &format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "runner-dashboard")
+        .send_json(AddLabels { labels })
+        .with_context(|| format!("GitHub request failed: POST {}", path))?;
+    read_labels(response, &format!("add {}", labels.join(", ")))
+}
+
+/// Remove `label` from runner `id` of `owner/repo`, returning the labels left.
+pub fn remove_runner_label(token: &str, repo: &str, id: u64, label: &str) -> Result<Vec<String>> {
+    let path = format!(
+        "/repos/{}/actions/runners/{}/labels/{}",
+        repo,
+        id,
+        encode_path_segment(label)
+    );
+    let response = label_agent()
+        .delete(&format!("{}{}", API_BASE, path))
+        .header("Authorization", &format!("Bearer {}", token))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "runner-dashboard")
+        .call()
+        .with_context(|| format!("GitHub request failed: DELETE {}", path))?;
+    read_labels(response, &format!("remove {}", label))
+}
+
+/// Percent-encode everything but unreserved characters, for one URL path segment.
+fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Fetch the latest published actions/runner release version (without the `v` prefix).
+pub fn latest_runner_version(token: &str) -> Result<String> {
+    let release: Release = get_json(token, "/repos/actions/runner/releases/latest")?;
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_edits_are_parsed_and_validated() {
+        assert_eq!(
+            parse_label_edits("gpu +linux-arm64 -old gpu").unwrap(),
+            LabelEdits {
+                add: vec!["gpu".to_string(), "linux-arm64".to_string()],
+                remove: vec!["old".to_string()],
+            }
+        );
+        assert!(parse_label_edits("  ").is_err());
+        assert!(parse_label_edits("-").is_err());
+        assert!(parse_label_edits("a,b").is_err());
+        assert!(parse_label_edits(&"x".repeat(MAX_LABEL_LEN + 1)).is_err());
+
+        // Counted and cut by character, not byte
+        let euros = parse_label_edits(&"€".repeat(MAX_LABEL_LEN + 1)).unwrap_err();
+        assert!(euros
+            .to_string()
+            .starts_with(&format!("Label '{}...'", "€".repeat(16))));
+        assert!(parse_label_edits(&"€".repeat(MAX_LABEL_LEN)).is_ok());
+    }
+
+    #[test]
+    fn label_names_are_encoded_for_the_url() {
+        assert_eq!(encode_path_segment("gpu-2.x"), "gpu-2.x");
+        assert_eq!(encode_path_segment("team/a b"), "team%2Fa%20b");
+    }
+}
@@ -1,27 +1,240 @@
 mod app;
+mod cli;
+mod config;
+mod daemon;
+mod doctor;
+mod event_socket;
+mod fleet;
+mod github;
+mod matcher;
+mod report;
 mod runner;
+mod server;
+mod service_template;
+mod symbols;
 mod ui;
+mod worker;
 
-use anyhow::Result;
-use app::{App, AppMode};
+use anyhow::{Context, Result};
+use app::{App, AppMode, Tab};
+use clap::Parser;
+use cli::{Cli, Command};
+use config::Config;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
+        MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io;
+use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal};
+use runner::{DiscoveryOptions, LogSource};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::SigId;
+use std::ffi::c_int;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    let config = Config::load()?;
+    let mut log_sources = config.log_sources()?;
+    for arg in &cli.log_sources {
+        let (runner, spec) = arg
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("--log-source {} should be RUNNER=SOURCE", arg))?;
+        let source = LogSource::parse(spec).context("Invalid --log-source")?;
+        log_sources.insert(runner.to_string(), source);
+    }
+    let options = DiscoveryOptions {
+        repos: cli.repos.clone(),
+        runner_user: cli.runner_user.clone().or(config.runner_user.clone()),
+        service_template: config.service_template()?,
+        dirs: if cli.dirs.is_empty() {
+            config.runner_dirs()
+        } else {
+            cli.dirs.clone()
+        },
+        log_sources,
+    };
+    let ascii = cli.ascii || config.ascii;
+
+    if let Some(command) = &cli.command {
+        return control_from_cli(command, &options, cli.regex);
+    }
+
+    if cli.status {
+        return print_status(&options, ascii, cli.profile);
+    }
+
+    if cli.doctor {
+        let path = std::env::var_os("PATH");
+        let diagnosis = doctor::diagnose(&runner::SystemCommandRunner, &options, path.as_deref())?;
+        print!("{}", diagnosis.render());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(path) = &cli.csv {
+        return export_csv(path, &options, cli.profile);
+    }
+
+    if let Some(output) = &cli.once {
+        return render_once(output.as_deref(), &options, ascii, cli.profile);
+    }
+
+    if let Some(log) = &cli.daemon {
+        daemon::run(log, &options, config.daemon_restart_failed, cli.profile)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(addr) = &cli.serve {
+        server::serve(addr, &options, cli.profile)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    run_tui(cli.sync, cli.regex, ascii, cli.bell, cli.profile, &options)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Discover runners and refresh their status once, for the one-shot modes.
+fn refresh_once(options: &DiscoveryOptions, profile: bool) -> Result<Vec<runner::Runner>> {
+    let cmd = &runner::SystemCommandRunner;
+    let mut runners = runner::discover_runners(options)?;
+    let timings = runner::refresh_runners(cmd, &mut runners, &runner::UnitCache::load(cmd));
+    if profile {
+        eprintln!("{}", timings.describe());
+    }
+    Ok(runners)
+}
+
+/// Discover and refresh runners once, print a status table, and exit.
+///
+/// The exit code reflects fleet health so it can be used as a shell health check.
+fn print_status(options: &DiscoveryOptions, ascii: bool, profile: bool) -> Result<ExitCode> {
+    let runners = refresh_once(options, profile)?;
+
+    print!(
+        "{}",
+        report::status_table(&runners, symbols::Symbols::get(ascii))
+    );
+
+    Ok(ExitCode::from(report::fleet_exit_code(&runners)))
+}
+
+/// Discover and refresh runners once and write them to a CSV file.
+fn export_csv(path: &Path, options: &DiscoveryOptions, profile: bool) -> Result<ExitCode> {
+    let runners = refresh_once(options, profile)?;
+
+    report::write_csv(&runners, path)?;
+    eprintln!("Wrote {} runners to {}", runners.len(), path.display());
+
+    Ok(ExitCode::from(report::fleet_exit_code(&runners)))
+}
+
+/// Run a start/stop/restart subcommand against the matching runners.
+///
+/// Actions run synchronously, one runner at a time, and each result is printed.
+fn control_from_cli(
+    command: &Command,
+    options: &DiscoveryOptions,
+    regex: bool,
+) -> Result<ExitCode> {
+    let args = command.args();
+    let target = args.target.as_deref().unwrap_or("*");
+    let matcher = args.matcher(regex)?;
+
+    let runners = runner::discover_runners(options)?;
+    let matching: Vec<&runner::Runner> = runners.iter().filter(|r| matcher.matches(r)).collect();
+
+    if matching.is_empty() {
+        let available: Vec<String> = runners
+            .iter()
+            .map(|r| format!("  {}/{}", r.repo, r.name))
+            .collect();
+        return Err(anyhow::anyhow!(
+            "No runner matches {} '{}'. Available runners:\n{}",
+            if regex { "regex" } else { "pattern" },
+            target,
+            if available.is_empty() {
+                "  (none found)".to_string()
+            } else {
+                available.join("\n")
+            }
+        ));
+    }
+
+    let cmd = &runner::SystemCommandRunner;
+    let mut failed = false;
+    for runner in matching {
+        let label = format!("{}/{}", runner.repo, runner.name);
+        match runner::control_runner(cmd, runner, command.action()) {
+            Ok(message) => println!("{}: {}", label, message),
+            Err(e) => {
+                eprintln!("{}: Error: {}", label, e);
+                failed = true;
+            }
+        }
+    }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Terminal size used for `--once`, fixed so renders are reproducible
+const ONCE_WIDTH: u16 = 120;
+const ONCE_HEIGHT: u16 = 40;
+
+/// Refresh runners once, render a single dashboard frame as text, and exit.
+fn render_once(
+    output: Option<&Path>,
+    options: &DiscoveryOptions,
+    ascii: bool,
+    profile: bool,
+) -> Result<ExitCode> {
+    let runners = refresh_once(options, profile)?;
+
+    let mut app = App::for_render(runners)?;
+    app.ascii = ascii;
+    let screen = ui::render_to_string(&app, ONCE_WIDTH, ONCE_HEIGHT)?;
+
+    match output {
+        Some(path) => std::fs::write(path, &screen)?,
+        None => print!("{}", screen),
+    }
+
+    Ok(ExitCode::from(report::fleet_exit_code(&app.runners)))
+}
+
+fn run_tui(
+    sync_actions: bool,
+    regex_filter: bool,
+    ascii: bool,
+    bell: bool,
+    profile: bool,
+    options: &DiscoveryOptions,
+) -> Result<()> {
     // Setup panic hook to restore terminal on panic
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic| {
+        // Background threads catch and report their own panics; the UI keeps running
+        if worker::is_background_thread() {
+            return;
+        }
         let _ = disable_raw_mode();
         let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
         original_hook(panic);
     }));
 
+    let mut signals = QuitSignals::register(&[SIGINT, SIGTERM])?;
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -30,10 +243,22 @@ fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut app = App::new()?;
+    let mut app = App::new(options)?;
+    app.sync_actions = sync_actions;
+    app.regex_filter = regex_filter;
+    app.ascii = ascii;
+    app.bell = bell;
+    app.profile = profile;
+    let path = std::env::var_os("PATH");
+    app.busy_detection = runner::find_executable("pgrep", path.as_deref()).is_some();
+    let missing_tools = runner::missing_tools(path.as_deref());
+    for tool in &missing_tools {
+        app.log_action(tool.clone());
+    }
+    app.missing_tools = missing_tools;
 
     // Run the app
-    let result = run_app(&mut terminal, &mut app);
+    let result = run_app(&mut terminal, &mut app, &mut signals);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -44,58 +269,150 @@ fn main() -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
-    if let Err(err) = result {
-        eprintln!("Error: {}", err);
-    }
-
-    Ok(())
+    result
 }
 
-const REFRESH_INTERVAL_MS: u64 = 1000;
+/// Redraw interval while a refresh is in flight, so the spinner animates
+const SPINNER_TICK_MS: u64 = 100;
+
+/// Redraw at least this often so elapsed-time text (e.g. "updated 3s ago") keeps moving
+const CLOCK_TICK: Duration = Duration::from_secs(1);
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> Result<()> {
-    let refresh_rate = Duration::from_millis(REFRESH_INTERVAL_MS);
+/// Lines moved by PageUp/PageDown on the help screen and output overlay
+const PAGE_LINES: usize = 10;
+
+fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    signals: &mut QuitSignals,
+) -> Result<()> {
     let mut last_refresh = Instant::now();
+    let mut last_input = Instant::now();
+    let mut last_draw = Instant::now();
 
     loop {
+        if signals.terminate.load(Ordering::Relaxed) {
+            break;
+        }
+
+        // Refresh slowly while nobody is at the keyboard
+        app.update_idle(last_input.elapsed());
+        let refresh_rate = app.refresh_interval();
+        let mut refresh_now = false;
+
         // Poll for updates from background worker (non-blocking)
         app.poll_worker_updates();
 
-        // Draw UI
-        terminal.draw(|f| ui::draw(f, app))?;
+        // Draw UI, only when something on screen may have changed
+        if app.refreshing || last_draw.elapsed() >= CLOCK_TICK {
+            app.dirty = true;
+        }
+        if app.dirty {
+            terminal.draw(|f| ui::draw(f, app))?;
+            app.dirty = false;
+            last_draw = Instant::now();
+        }
+        if std::mem::take(&mut app.ring_bell) {
+            let backend = terminal.backend_mut();
+            backend.write_all(b"\x07")?;
+            backend.flush()?;
+        }
 
         // Handle events with timeout for periodic refresh
-        let time_until_refresh = refresh_rate
+        let mut timeout = refresh_rate
             .checked_sub(last_refresh.elapsed())
-            .unwrap_or_else(|| Duration::from_millis(0));
+            .unwrap_or_else(|| Duration::from_millis(0))
+            .min(CLOCK_TICK.saturating_sub(last_draw.elapsed()));
+        if app.refreshing {
+            timeout = timeout.min(Duration::from_millis(SPINNER_TICK_MS));
+        }
+
+        if event::poll(timeout)? {
+            let event = event::read()?;
+            last_input = Instant::now();
+            // Keys change state, and a resize needs a full redraw
+            app.dirty = true;
+            // Data may be several seconds old after idling; fetch it right away
+            refresh_now = app.update_idle(Duration::ZERO);
 
-        if event::poll(time_until_refresh)? {
-            if let Event::Key(key) = event::read()? {
+            if let Event::Key(key) = event {
                 // Clear status message on any key press
                 app.status_message = None;
 
+                let suspend =
+                    key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL);
+
                 match app.mode {
+                    AppMode::Normal | AppMode::Detail if suspend => app.suspend(),
                     AppMode::Help => {
-                        // Any key exits help
-                        app.mode = AppMode::Normal;
-                    }
-                    AppMode::Logs => {
-                        handle_logs_mode(app, key.code);
+                        handle_help_mode(app, key.code);
                     }
                     AppMode::Normal => {
-                        handle_normal_mode(app, key.code, key.modifiers);
+                        if !handle_tab_keys(app, key.code) {
+                            match app.tab {
+                                Tab::Runners => handle_normal_mode(app, key.code, key.modifiers),
+                                Tab::Logs => handle_logs_mode(app, key.code),
+                                Tab::Stats | Tab::Events => handle_panel_mode(app, key.code),
+                                Tab::Fleet => handle_fleet_mode(app, key.code),
+                            }
+                        }
+                    }
+                    AppMode::Confirm => {
+                        handle_confirm_mode(app, key.code);
+                    }
+                    AppMode::Detail => {
+                        handle_detail_mode(app, key.code);
+                    }
+                    AppMode::Filter => {
+                        handle_filter_mode(app, key.code);
+                    }
+                    AppMode::LogSearch => {
+                        handle_log_search_mode(app, key.code);
+                    }
+                    AppMode::Welcome => {
+                        // Any key starts the dashboard
+                        app.dismiss_welcome();
+                    }
+                    AppMode::Output => {
+                        handle_output_mode(app, key.code);
+                    }
+                    AppMode::Labels => {
+                        handle_label_mode(app, key.code);
                     }
                 }
 
                 if app.should_quit {
                     break;
                 }
+            } else if let Event::Mouse(mouse) = event {
+                if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                    let size = terminal.size()?;
+                    let area = Rect::new(0, 0, size.width, size.height);
+                    if let Some(column) = ui::sort_column_at(app, area, mouse.column, mouse.row) {
+                        app.sort_by_column(column);
+                    }
+                }
             }
         }
 
+        if let Some(mut request) = app.pending_suspend.take() {
+            let label = request.label;
+            app.status_message = Some(
+                match run_suspended(terminal, signals, &mut request.command) {
+                    Ok(status) if status.success() => format!("Back from {}", label),
+                    Ok(status) => format!("Back from {} ({})", label, status),
+                    Err(e) => format!("Error: could not run {}: {}", label, e),
+                },
+            );
+            app.dirty = true;
+            // Whatever ran may have changed runner state
+            app.refresh_requested = true;
+        }
+        let force = std::mem::take(&mut app.refresh_requested);
+
         // Request periodic refresh (non-blocking)
-        if last_refresh.elapsed() >= refresh_rate {
-            app.refresh();
+        if refresh_now || force || last_refresh.elapsed() >= refresh_rate {
+            app.refresh(force);
             last_refresh = Instant::now();
         }
     }
@@ -103,20 +420,142 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
     Ok(())
 }
 
+/// SIGINT/SIGTERM turned into a flag checked by the event loop, so signals take
+/// the normal exit path in `run_tui`. A second signal terminates immediately.
+struct QuitSignals {
+    signals: Vec<c_int>,
+    terminate: Arc<AtomicBool>,
+    ids: Vec<SigId>,
+}
+
+impl QuitSignals {
+    fn register(signals: &[c_int]) -> Result<Self> {
+        let mut quit = Self {
+            signals: signals.to_vec(),
+            terminate: Arc::new(AtomicBool::new(false)),
+            ids: Vec::new(),
+        };
+        quit.arm()?;
+        Ok(quit)
+    }
+
+    fn arm(&mut self) -> Result<()> {
+        for &signal in &self.signals {
+            self.ids
+                .push(signal_hook::flag::register_conditional_shutdown(
+                    signal,
+                    1,
+                    Arc::clone(&self.terminate),
+                )?);
+            self.ids.push(signal_hook::flag::register(
+                signal,
+                Arc::clone(&self.terminate),
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Run `f` with the signals ignored, e.g. while a pager or shell holds the
+    /// terminal and Ctrl-C is meant for it. Any earlier quit request is dropped.
+    fn ignored<T>(&mut self, f: impl FnOnce() -> T) -> Result<T> {
+        for id in self.ids.drain(..) {
+            signal_hook::low_level::unregister(id);
+        }
+        self.terminate.store(false, Ordering::Relaxed);
+        let result = f();
+        self.arm()?;
+        Ok(result)
+    }
+}
+
+/// Hand the terminal to `command` until it exits, then restore the TUI.
+///
+/// Mirrors the setup and teardown in `run_tui`. Ctrl-C inside the command also
+/// reaches this process, so quit signals are ignored until it exits.
+fn run_suspended(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    signals: &mut QuitSignals,
+    command: &mut std::process::Command,
+) -> Result<std::process::ExitStatus> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    let status = signals.ignored(|| command.status())?;
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+
+    Ok(status?)
+}
+
+/// Handle tab switching keys shared by every tab. Returns true if the key was used.
+fn handle_tab_keys(app: &mut App, key: KeyCode) -> bool {
+    match key {
+        KeyCode::Tab => app.next_tab(),
+        KeyCode::BackTab => app.previous_tab(),
+        KeyCode::Char(c @ '1'..='9') => match Tab::ALL.get(c as usize - '1' as usize) {
+            Some(&tab) => app.set_tab(tab),
+            None => return false,
+        },
+        _ => return false,
+    }
+    true
+}
+
 fn handle_normal_mode(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
     match key {
         KeyCode::Char('q') => app.should_quit = true,
         KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => app.should_quit = true,
+        // Ahead of `r`, which restarts
+        KeyCode::F(5) => app.request_refresh(),
+        KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => app.request_refresh(),
 
         // Navigation
         KeyCode::Up | KeyCode::Char('k') => app.select_previous(),
         KeyCode::Down | KeyCode::Char('j') => app.select_next(),
+        KeyCode::Char(']') => app.select_next_problem(),
+        KeyCode::Char('[') => app.select_previous_problem(),
+        KeyCode::Char('*') => app.toggle_pin_selected(),
+        KeyCode::Char(' ') => app.toggle_mark_selected(),
+        KeyCode::Esc => app.clear_marks(),
 
         // Actions
         KeyCode::Char('s') => app.start_selected(),
         KeyCode::Char('x') => app.stop_selected(),
         KeyCode::Char('r') => app.restart_selected(),
+        KeyCode::Char('i') => app.install_selected(),
+        KeyCode::Char('u') => app.uninstall_selected(),
+        KeyCode::Char('D') => app.deregister_selected(),
+        KeyCode::Char('P') => app.prune_work_selected(),
+        KeyCode::Char('R') => app.restart_failed(),
+        KeyCode::Char('T') => app.probe_selected(),
+        KeyCode::Char('C') => app.show_runner_config(),
+        KeyCode::Char('g') => app.check_registration_selected(),
+        KeyCode::Char('L') => app.start_label_edit(),
         KeyCode::Char('l') => app.toggle_logs(),
+        KeyCode::Enter | KeyCode::Char('d') => app.toggle_detail(),
+        KeyCode::Char('X') => app.export_csv(),
+        KeyCode::Char('m') => app.cycle_list_density(),
+        KeyCode::Char('S') => app.cycle_sort(),
+        KeyCode::Char('I') => app.invert_sort(),
+        KeyCode::Char('<') => app.shrink_list(),
+        KeyCode::Char('>') => app.grow_list(),
+        KeyCode::Char('|') => app.toggle_details_pane(),
+        KeyCode::Char('/') => app.start_filter(),
+        KeyCode::Char('o') if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.shell_in_selected_dir()
+        }
+        KeyCode::Char('O') => app.open_selected_dir(),
 
         // Help
         KeyCode::Char('?') | KeyCode::Char('h') => app.toggle_help(),
@@ -125,10 +564,47 @@ fn handle_normal_mode(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
     }
 }
 
+fn handle_filter_mode(app: &mut App, key: KeyCode) {
+    match key {
+        KeyCode::Enter => app.accept_filter(),
+        KeyCode::Esc => app.clear_filter(),
+        KeyCode::Backspace => app.pop_filter_char(),
+        KeyCode::Char(c) => app.push_filter_char(c),
+        _ => {}
+    }
+}
+
+fn handle_label_mode(app: &mut App, key: KeyCode) {
+    match key {
+        KeyCode::Enter => app.apply_label_edit(),
+        KeyCode::Esc => app.cancel_label_edit(),
+        KeyCode::Backspace => app.pop_label_char(),
+        KeyCode::Char(c) => app.push_label_char(c),
+        _ => {}
+    }
+}
+
+fn handle_log_search_mode(app: &mut App, key: KeyCode) {
+    match key {
+        KeyCode::Enter => app.accept_log_search(),
+        KeyCode::Esc => app.cancel_log_search(),
+        KeyCode::Backspace => app.pop_log_search_char(),
+        KeyCode::Char(c) => app.push_log_search_char(c),
+        _ => {}
+    }
+}
+
 fn handle_logs_mode(app: &mut App, key: KeyCode) {
     match key {
         KeyCode::Char('q') => app.should_quit = true,
         KeyCode::Char('l') | KeyCode::Esc => app.toggle_logs(),
+        KeyCode::Char('r') => app.reload_logs(),
+        KeyCode::Char('t') => app.cycle_log_window(),
+        KeyCode::Char('p') => app.cycle_log_priority(),
+        KeyCode::Char('/') => app.start_log_search(),
+        KeyCode::Char('n') => app.next_log_match(),
+        KeyCode::Char('N') => app.previous_log_match(),
+        KeyCode::Char('e') => app.expand_log_context(),
 
         // Scroll
         KeyCode::Up | KeyCode::Char('k') => app.scroll_logs_up(),
@@ -140,3 +616,142 @@ fn handle_logs_mode(app: &mut App, key: KeyCode) {
         _ => {}
     }
 }
+
+/// Keys for the read-only Stats and Events tabs.
+fn handle_panel_mode(app: &mut App, key: KeyCode) {
+    match key {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Esc => app.set_tab(Tab::Runners),
+        KeyCode::Char('?') | KeyCode::Char('h') => app.toggle_help(),
+        _ => {}
+    }
+}
+
+fn handle_fleet_mode(app: &mut App, key: KeyCode) {
+    match key {
+        KeyCode::Left => app.scroll_fleet_left(),
+        KeyCode::Right => app.scroll_fleet_right(),
+        KeyCode::Char('r') => app.load_agent_settings(),
+        _ => handle_panel_mode(app, key),
+    }
+}
+
+/// Keys on the help screen; navigation keys scroll, so only a few close it.
+fn handle_help_mode(app: &mut App, key: KeyCode) {
+    let line_count = ui::help_line_count();
+    match key {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') | KeyCode::Char('h') => {
+            app.toggle_help()
+        }
+        KeyCode::Up | KeyCode::Char('k') => app.scroll_help_up(1),
+        KeyCode::Down | KeyCode::Char('j') => app.scroll_help_down(1, line_count),
+        KeyCode::PageUp => app.scroll_help_up(PAGE_LINES),
+        KeyCode::PageDown => app.scroll_help_down(PAGE_LINES, line_count),
+        KeyCode::Home => app.help_scroll = 0,
+        _ => {}
+    }
+}
+
+fn handle_output_mode(app: &mut App, key: KeyCode) {
+    let Some(output) = app.output.as_mut() else {
+        app.close_output();
+        return;
+    };
+    match key {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => app.close_output(),
+        KeyCode::Up | KeyCode::Char('k') => output.scroll_up(1),
+        KeyCode::Down | KeyCode::Char('j') => output.scroll_down(1),
+        KeyCode::PageUp => output.scroll_up(PAGE_LINES),
+        KeyCode::PageDown => output.scroll_down(PAGE_LINES),
+        KeyCode::Home => output.scroll = 0,
+        _ => {}
+    }
+}
+
+fn handle_detail_mode(app: &mut App, key: KeyCode) {
+    match key {
+        KeyCode::Char('q') => app.should_quit = true,
+        KeyCode::Char('d') | KeyCode::Enter | KeyCode::Esc => app.toggle_detail(),
+
+        // Step through runners without leaving the details view
+        KeyCode::Up | KeyCode::Char('k') => app.select_previous(),
+        KeyCode::Down | KeyCode::Char('j') => app.select_next(),
+        KeyCode::Char(']') => app.select_next_problem(),
+        KeyCode::Char('[') => app.select_previous_problem(),
+
+        // Actions
+        KeyCode::Char('s') => app.start_selected(),
+        KeyCode::Char('x') => app.stop_selected(),
+        KeyCode::Char('r') => app.restart_selected(),
+        KeyCode::Char('i') => app.install_selected(),
+        KeyCode::Char('P') => app.prune_work_selected(),
+        KeyCode::Char('T') => app.probe_selected(),
+        KeyCode::Char('g') => app.check_registration_selected(),
+        KeyCode::Char('C') => app.show_runner_config(),
+        KeyCode::Char('L') => app.start_label_edit(),
+
+        // Help
+        KeyCode::Char('?') | KeyCode::Char('h') => app.toggle_help(),
+
+        _ => {}
+    }
+}
+
+fn handle_confirm_mode(app: &mut App, key: KeyCode) {
+    // Scrolling the list of affected runners neither confirms nor cancels
+    match key {
+        KeyCode::Down => return app.scroll_confirm_down(1),
+        KeyCode::Up => return app.scroll_confirm_up(1),
+        KeyCode::PageDown => return app.scroll_confirm_down(PAGE_LINES),
+        KeyCode::PageUp => return app.scroll_confirm_up(PAGE_LINES),
+        _ => {}
+    }
+
+    if app.confirm_needs_typing() {
+        match key {
+            KeyCode::Enter => app.submit_typed_confirm(),
+            KeyCode::Esc => app.cancel_confirm(),
+            KeyCode::Backspace => app.pop_confirm_char(),
+            KeyCode::Char(c) => app.push_confirm_char(c),
+            _ => {}
+        }
+        return;
+    }
+
+    match key {
+        KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm(),
+        KeyCode::Char('j') => app.scroll_confirm_down(1),
+        KeyCode::Char('k') => app.scroll_confirm_up(1),
+        _ => app.cancel_confirm(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use signal_hook::consts::SIGUSR2;
+    use signal_hook::low_level::raise;
+
+    #[test]
+    fn quit_signals_are_ignored_while_a_command_holds_the_terminal() {
+        // SIGUSR2 stands in for Ctrl-C so the test harness keeps its own SIGINT
+        let mut signals = QuitSignals::register(&[SIGUSR2]).unwrap();
+        signals.terminate.store(true, Ordering::Relaxed);
+
+        let terminate = Arc::clone(&signals.terminate);
+        let seen = signals
+            .ignored(|| {
+                let cleared = !terminate.load(Ordering::Relaxed);
+                // Twice would exit the process if the handlers were still armed
+                raise(SIGUSR2).unwrap();
+                raise(SIGUSR2).unwrap();
+                (cleared, terminate.load(Ordering::Relaxed))
+            })
+            .unwrap();
+        assert_eq!(seen, (true, false));
+        assert!(!signals.terminate.load(Ordering::Relaxed));
+
+        raise(SIGUSR2).unwrap();
+        assert!(signals.terminate.load(Ordering::Relaxed));
+    }
+}
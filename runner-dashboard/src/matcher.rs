@@ -0,0 +1,136 @@
+use crate::runner::Runner;
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Selects runners by name, shared by the control subcommands and the `/` filter.
+///
+/// Runners are matched as `repo/name` and `repo/number`, so `acme/2`,
+/// `acme/runner-2` and `acme-*/*` all work as targets.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// Case-insensitive glob that must match the whole string; `*` matches any
+    /// run of characters (including `/`) and `?` matches exactly one.
+    Glob(String),
+    /// Regular expression found anywhere in the string
+    Regex(Regex),
+}
+
+impl Matcher {
+    /// Parse `pattern` as a glob, or as a regular expression when `regex` is set.
+    pub fn new(pattern: &str, regex: bool) -> Result<Self> {
+        if regex {
+            let regex =
+                Regex::new(pattern).with_context(|| format!("Invalid regex '{}'", pattern))?;
+            Ok(Matcher::Regex(regex))
+        } else {
+            Ok(Matcher::Glob(pattern.to_lowercase()))
+        }
+    }
+
+    /// Like `new`, but plain text without wildcards matches anywhere, as a search box would.
+    pub fn search(text: &str, regex: bool) -> Result<Self> {
+        if regex || text.contains(['*', '?']) {
+            Self::new(text, regex)
+        } else {
+            Self::new(&format!("*{}*", text), false)
+        }
+    }
+
+    pub fn matches(&self, runner: &Runner) -> bool {
+        let by_name = format!("{}/{}", runner.repo, runner.name);
+        let by_number = format!("{}/{}", runner.repo, runner.number);
+        [by_name, by_number].iter().any(|text| self.is_match(text))
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        match self {
+            Matcher::Glob(pattern) => {
+                let pattern: Vec<char> = pattern.chars().collect();
+                let text: Vec<char> = text.to_lowercase().chars().collect();
+                glob_match(&pattern, &text)
+            }
+            Matcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// Match a whole string against a glob, backtracking to the most recent `*`.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and retry
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+
+    fn glob(pattern: &str) -> Matcher {
+        Matcher::new(pattern, false).unwrap()
+    }
+
+    #[test]
+    fn glob_matches_by_name_number_and_wildcard() {
+        let runner = test_runner("acme", 10);
+
+        assert!(glob("acme/runner-10").matches(&runner));
+        assert!(glob("acme/10").matches(&runner));
+        assert!(glob("acme/*").matches(&runner));
+        assert!(glob("*/10").matches(&runner));
+        assert!(glob("*").matches(&runner));
+        assert!(!glob("acme/1").matches(&runner));
+        assert!(!glob("widgets/*").matches(&runner));
+        assert!(!glob("acme").matches(&runner));
+    }
+
+    #[test]
+    fn glob_selects_repo_families() {
+        assert!(glob("acme-*").matches(&test_runner("acme-frontend", 1)));
+        assert!(glob("*-FRONTEND/runner-?").matches(&test_runner("acme-frontend", 3)));
+        assert!(!glob("*-frontend/runner-?").matches(&test_runner("acme-frontend", 12)));
+        assert!(!glob("acme-*").matches(&test_runner("acme", 1)));
+    }
+
+    #[test]
+    fn search_treats_plain_text_as_substring() {
+        let matcher = Matcher::search("FRONT", false).unwrap();
+
+        assert!(matcher.matches(&test_runner("acme-frontend", 1)));
+        assert!(!matcher.matches(&test_runner("acme", 1)));
+    }
+
+    #[test]
+    fn regex_is_found_anywhere_and_rejects_bad_patterns() {
+        let matcher = Matcher::new("^acme-(web|api)/", true).unwrap();
+
+        assert!(matcher.matches(&test_runner("acme-api", 2)));
+        assert!(!matcher.matches(&test_runner("acme-db", 2)));
+
+        let error = Matcher::new("acme/(", true).unwrap_err();
+        assert!(error.to_string().contains("Invalid regex 'acme/('"));
+    }
+}
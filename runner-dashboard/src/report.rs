@@ -0,0 +1,359 @@
+use crate::runner::{Runner, RunnerStatus};
+use crate::symbols::Symbols;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// Flat per-runner record shared by the machine-readable exports.
+#[derive(Debug, Serialize)]
+pub struct RunnerRecord {
+    pub repo: String,
+    pub name: String,
+    pub number: u32,
+    pub status: &'static str,
+    pub service_name: String,
+    pub path: String,
+    pub version: Option<String>,
+    pub busy: bool,
+}
+
+impl RunnerRecord {
+    /// Column names, in the same order as `fields`.
+    pub const COLUMNS: &'static [&'static str] = &[
+        "repo",
+        "name",
+        "number",
+        "status",
+        "service_name",
+        "path",
+        "version",
+        "busy",
+    ];
+
+    pub fn from_runner(runner: &Runner) -> Self {
+        Self {
+            repo: runner.repo.clone(),
+            name: runner.name.clone(),
+            number: runner.number,
+            status: runner.status.as_str(),
+            service_name: runner.service_name.clone(),
+            path: runner.path.to_string_lossy().to_string(),
+            version: runner.version.clone(),
+            busy: runner.busy,
+        }
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.repo.clone(),
+            self.name.clone(),
+            self.number.to_string(),
+            self.status.to_string(),
+            self.service_name.clone(),
+            self.path.clone(),
+            self.version.clone().unwrap_or_default(),
+            self.busy.to_string(),
+        ]
+    }
+}
+
+/// Render a compact, column-aligned status table for terminal output.
+///
+/// Widths are in characters, which is also what `format!` pads by.
+pub fn status_table(runners: &[Runner], symbols: &Symbols) -> String {
+    let repo_width = runners
+        .iter()
+        .map(|r| r.repo.chars().count())
+        .chain(std::iter::once("REPO".len()))
+        .max()
+        .unwrap_or(0);
+    let name_width = runners
+        .iter()
+        .map(|r| r.name.chars().count())
+        .chain(std::iter::once("NAME".len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut table = format!("{:<repo_width$}  {:<name_width$}  STATUS\n", "REPO", "NAME");
+
+    for runner in runners {
+        table.push_str(&format!(
+            "{:<repo_width$}  {:<name_width$}  {} {}\n",
+            runner.repo,
+            runner.name,
+            symbols.status(&runner.status),
+            runner.status.as_str()
+        ));
+    }
+
+    table
+}
+
+/// Exit code when every discovered runner is active.
+pub const EXIT_HEALTHY: u8 = 0;
+/// Exit code when at least one runner has failed.
+pub const EXIT_FAILED: u8 = 1;
+/// Exit code when no runner has failed but some are inactive or not found.
+pub const EXIT_DEGRADED: u8 = 2;
+
+/// Map fleet state to a process exit code for headless modes.
+pub fn fleet_exit_code(runners: &[Runner]) -> u8 {
+    if runners.iter().any(|r| r.status == RunnerStatus::Failed) {
+        EXIT_FAILED
+    } else if runners.iter().all(|r| r.status == RunnerStatus::Active) {
+        EXIT_HEALTHY
+    } else {
+        EXIT_DEGRADED
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render runners as CSV with a header row.
+pub fn to_csv(runners: &[Runner]) -> String {
+    let mut csv = RunnerRecord::COLUMNS.join(",");
+    csv.push('\n');
+
+    for runner in runners {
+        let fields: Vec<String> = RunnerRecord::from_runner(runner)
+            .fields()
+            .iter()
+            .map(|f| csv_field(f))
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Write runners as CSV to the given file.
+pub fn write_csv(runners: &[Runner], path: &Path) -> Result<()> {
+    std::fs::write(path, to_csv(runners))
+        .with_context(|| format!("Failed to write CSV to {}", path.display()))
+}
+
+/// Per-status runner counts for summaries.
+#[derive(Debug, Default, Serialize)]
+pub struct StatusCounts {
+    pub active: usize,
+    pub inactive: usize,
+    pub failed: usize,
+    pub not_found: usize,
+    pub total: usize,
+}
+
+impl StatusCounts {
+    pub fn from_runners(runners: &[Runner]) -> Self {
+        let mut counts = Self {
+            total: runners.len(),
+            ..Self::default()
+        };
+        for runner in runners {
+            match runner.status {
+                RunnerStatus::Active => counts.active += 1,
+                RunnerStatus::Inactive => counts.inactive += 1,
+                RunnerStatus::Failed => counts.failed += 1,
+                RunnerStatus::NotFound => counts.not_found += 1,
+            }
+        }
+        counts
+    }
+}
+
+#[derive(Serialize)]
+struct StatusDocument {
+    counts: StatusCounts,
+    runners: Vec<RunnerRecord>,
+}
+
+/// Render runners and status counts as a JSON document.
+pub fn to_json(runners: &[Runner]) -> Result<String> {
+    let document = StatusDocument {
+        counts: StatusCounts::from_runners(runners),
+        runners: runners.iter().map(RunnerRecord::from_runner).collect(),
+    };
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Escape a Prometheus label value.
+fn prometheus_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// All statuses, in the order they are exported as metric labels.
+const ALL_STATUSES: &[RunnerStatus] = &[
+    RunnerStatus::Active,
+    RunnerStatus::Inactive,
+    RunnerStatus::Failed,
+    RunnerStatus::NotFound,
+];
+
+/// Render runner status in the Prometheus text exposition format.
+pub fn to_prometheus(runners: &[Runner]) -> String {
+    let mut out = String::new();
+
+    out.push_str("# HELP github_runner_up Whether the runner is active (1) or not (0).\n");
+    out.push_str("# TYPE github_runner_up gauge\n");
+    for runner in runners {
+        out.push_str(&format!(
+            "github_runner_up{{repo=\"{}\",name=\"{}\"}} {}\n",
+            prometheus_label(&runner.repo),
+            prometheus_label(&runner.name),
+            u8::from(runner.status == RunnerStatus::Active)
+        ));
+    }
+
+    out.push_str(
+        "# HELP github_runner_status Runner status, 1 on the label matching the current status.\n",
+    );
+    out.push_str("# TYPE github_runner_status gauge\n");
+    for runner in runners {
+        for status in ALL_STATUSES {
+            out.push_str(&format!(
+                "github_runner_status{{repo=\"{}\",name=\"{}\",status=\"{}\"}} {}\n",
+                prometheus_label(&runner.repo),
+                prometheus_label(&runner.name),
+                status.as_str(),
+                u8::from(runner.status == *status)
+            ));
+        }
+    }
+
+    out.push_str("# HELP github_runners Number of runners by status.\n");
+    out.push_str("# TYPE github_runners gauge\n");
+    for status in ALL_STATUSES {
+        let count = runners.iter().filter(|r| r.status == *status).count();
+        out.push_str(&format!(
+            "github_runners{{status=\"{}\"}} {}\n",
+            status.as_str(),
+            count
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+
+    fn with_status(number: u32, status: RunnerStatus) -> Runner {
+        Runner {
+            status,
+            ..test_runner("acme", number)
+        }
+    }
+
+    #[test]
+    fn status_table_aligns_names_with_non_ascii_characters() {
+        let runners = [
+            test_runner("café", 1),
+            Runner {
+                status: RunnerStatus::Failed,
+                ..test_runner("acme", 2)
+            },
+        ];
+
+        let table = status_table(&runners, Symbols::get(true));
+
+        assert_eq!(
+            table,
+            "REPO  NAME      STATUS\n\
+             café  runner-1  * active\n\
+             acme  runner-2  X failed\n"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_delimiters_quotes_and_newlines() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+
+        let runners = [Runner {
+            repo: "acme,inc".to_string(),
+            version: Some("2.311.0".to_string()),
+            busy: true,
+            ..test_runner("acme", 1)
+        }];
+        assert_eq!(
+            to_csv(&runners),
+            "repo,name,number,status,service_name,path,version,busy\n\
+             \"acme,inc\",runner-1,1,active,actions.runner.ci.acme-runner-1,\
+             /nonexistent/action-runners/acme/1,2.311.0,true\n"
+        );
+    }
+
+    #[test]
+    fn prometheus_metrics_label_each_runner_and_status() {
+        let runners = [
+            test_runner("acme", 1),
+            Runner {
+                repo: "say \"hi\"\\now".to_string(),
+                status: RunnerStatus::Failed,
+                ..test_runner("acme", 2)
+            },
+        ];
+
+        let metrics = to_prometheus(&runners);
+        let lines: Vec<&str> = metrics.lines().collect();
+
+        assert!(lines.contains(&"# TYPE github_runner_up gauge"));
+        assert!(lines.contains(&"github_runner_up{repo=\"acme\",name=\"runner-1\"} 1"));
+        assert!(lines.contains(&r#"github_runner_up{repo="say \"hi\"\\now",name="runner-2"} 0"#));
+        assert!(lines.contains(
+            &r#"github_runner_status{repo="say \"hi\"\\now",name="runner-2",status="failed"} 1"#
+        ));
+        assert!(lines.contains(
+            &"github_runner_status{repo=\"acme\",name=\"runner-1\",status=\"not-found\"} 0"
+        ));
+        let counts: Vec<&str> = lines
+            .iter()
+            .copied()
+            .filter(|l| l.starts_with("github_runners{"))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                "github_runners{status=\"active\"} 1",
+                "github_runners{status=\"inactive\"} 0",
+                "github_runners{status=\"failed\"} 1",
+                "github_runners{status=\"not-found\"} 0",
+            ]
+        );
+        assert_eq!(prometheus_label("a\nb"), "a\\nb");
+    }
+
+    /// The codes are documented in `--help`, so they're checked as numbers
+    #[test]
+    fn exit_code_reflects_the_worst_runner() {
+        let healthy = [test_runner("acme", 1), test_runner("acme", 2)];
+        assert_eq!(fleet_exit_code(&healthy), 0);
+
+        let failed = [
+            test_runner("acme", 1),
+            with_status(2, RunnerStatus::Inactive),
+            with_status(3, RunnerStatus::Failed),
+        ];
+        assert_eq!(fleet_exit_code(&failed), 1);
+
+        for status in [RunnerStatus::Inactive, RunnerStatus::NotFound] {
+            let degraded = [test_runner("acme", 1), with_status(2, status)];
+            assert_eq!(fleet_exit_code(&degraded), 2);
+        }
+    }
+}
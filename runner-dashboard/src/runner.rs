@@ -1,7 +1,29 @@
+use crate::github::{self, LabelEdits, RegisteredRunner};
+use crate::service_template::ServiceTemplate;
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Runs external programs for status queries and control actions.
+///
+/// Abstracted so the parsing and decision logic can be tested with canned output.
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output>;
+}
+
+/// Runs commands on the host.
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+        Command::new(program).args(args).output()
+    }
+}
 
 /// Shell metacharacters that could enable command injection
 const DANGEROUS_CHARS: &[char] = &[
@@ -42,15 +64,6 @@ impl RunnerStatus {
             RunnerStatus::NotFound => "not-found",
         }
     }
-
-    pub fn symbol(&self) -> &'static str {
-        match self {
-            RunnerStatus::Active => "●",
-            RunnerStatus::Inactive => "○",
-            RunnerStatus::Failed => "✗",
-            RunnerStatus::NotFound => "?",
-        }
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -61,753 +74,3978 @@ pub struct Runner {
     pub status: RunnerStatus,
     pub service_name: String,
     pub path: PathBuf,
+    /// Root directory the runner was discovered under
+    pub root: PathBuf,
+    pub service_installed: bool,
+    pub version: Option<String>,
+    /// A job is executing right now (a `Runner.Worker` process exists)
+    pub busy: bool,
+    /// Result of the last check against GitHub's runner registrations
+    pub registration: Option<Registration>,
+    /// What the work folder held at the last scan
+    pub work: Option<WorkUsage>,
+    /// systemd's own view of the unit at the last refresh (Linux only)
+    pub service_state: Option<ServiceState>,
+    /// Labels GitHub has on the runner, as of the last registration check or edit
+    pub labels: Option<Vec<String>>,
+    /// Where to read its logs, when not from its service unit (or `_diag` on macOS)
+    pub log_source: Option<LogSource>,
+    /// Account its service runs as, resolved at discovery (see `resolve_runner_user`)
+    pub user: String,
 }
 
-impl Runner {
-    pub fn display_name(&self) -> String {
-        format!("{}-runner-{}", self.repo, self.number)
-    }
+/// Where a runner's logs are read from, for setups where its service unit isn't it,
+/// e.g. a wrapper script that logs under its own syslog identifier
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogSource {
+    /// `journalctl -u <unit>`
+    Unit(String),
+    /// `journalctl -t <identifier>`
+    Syslog(String),
+    /// A plain log file, read from the end
+    File(PathBuf),
 }
 
-/// Discover all runners from the action-runners directory
-pub fn discover_runners() -> Result<Vec<Runner>> {
-    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot find home directory"))?;
-    let runners_dir = home.join("action-runners");
-
-    if !runners_dir.exists() {
-        return Ok(Vec::new());
+impl LogSource {
+    /// Parse `unit:NAME`, `syslog:IDENTIFIER` or `file:PATH` (`~` expanded).
+    pub fn parse(spec: &str) -> Result<Self> {
+        let Some((kind, value)) = spec.split_once(':').filter(|(_, v)| !v.is_empty()) else {
+            anyhow::bail!(
+                "Log source '{}' should be unit:NAME, syslog:IDENTIFIER or file:PATH",
+                spec
+            );
+        };
+        match kind {
+            "unit" => Ok(LogSource::Unit(value.to_string())),
+            "syslog" => Ok(LogSource::Syslog(value.to_string())),
+            "file" => Ok(LogSource::File(PathBuf::from(
+                shellexpand::tilde(value).as_ref(),
+            ))),
+            _ => anyhow::bail!(
+                "Unknown log source kind '{}' in '{}' (expected unit, syslog or file)",
+                kind,
+                spec
+            ),
+        }
     }
 
-    let username = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
-    let mut runners = Vec::new();
-
-    for repo_entry in std::fs::read_dir(&runners_dir)? {
-        let repo_path = repo_entry?.path();
-        if !repo_path.is_dir() {
-            continue;
+    pub fn describe(&self) -> String {
+        match self {
+            LogSource::Unit(unit) => format!("unit {}", unit),
+            LogSource::Syslog(identifier) => format!("syslog identifier {}", identifier),
+            LogSource::File(path) => path.display().to_string(),
         }
+    }
+}
 
-        let Some(repo_name) = repo_path.file_name().and_then(|n| n.to_str()) else {
-            continue;
-        };
+/// A systemd unit's `ActiveState` and `SubState`, which tell apart states that
+/// `RunnerStatus` lumps together, e.g. `activating (auto-restart)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceState {
+    pub active: String,
+    pub sub: String,
+    /// `NRestarts`: automatic restarts since the unit was last started by hand.
+    /// None on systemd older than 235, which doesn't count them
+    pub restarts: Option<u32>,
+}
 
-        if repo_name.is_empty() {
-            continue;
-        }
+impl ServiceState {
+    /// `active (running)`
+    pub fn describe(&self) -> String {
+        format!("{} ({})", self.active, self.sub)
+    }
 
-        discover_repo_runners(&repo_path, repo_name, &username, &mut runners)?;
+    /// Starting, stopping, or waiting to be restarted after a crash
+    pub fn is_transitional(&self) -> bool {
+        matches!(
+            self.active.as_str(),
+            "activating" | "deactivating" | "reloading"
+        ) || self.sub == "auto-restart"
     }
+}
 
-    runners.sort_by(|a, b| a.repo.cmp(&b.repo).then_with(|| a.number.cmp(&b.number)));
-    Ok(runners)
+/// Job directories left in a runner's work folder, as of a scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkUsage {
+    pub folder: PathBuf,
+    /// Job workspaces, i.e. everything but the runner's own `_`-prefixed directories
+    pub job_dirs: usize,
+    /// Size of those job workspaces
+    pub bytes: u64,
+    pub scanned_at: std::time::Instant,
 }
 
-/// Discover runners within a single repository directory
-fn discover_repo_runners(
-    repo_path: &Path,
-    repo_name: &str,
-    username: &str,
-    runners: &mut Vec<Runner>,
-) -> Result<()> {
-    for runner_entry in std::fs::read_dir(repo_path)? {
-        let runner_path = runner_entry?.path();
+/// Whether GitHub still has a runner registered, as of the last check
+#[derive(Debug, Clone, PartialEq)]
+pub enum Registration {
+    /// Registered, and `online` as far as GitHub can tell
+    Ok { online: bool },
+    /// GitHub has no runner by its agent name; it was removed there
+    NotFound,
+    /// Registered, but offline to GitHub while it runs here: its credentials were
+    /// likely revoked, so it will never pick up a job
+    Deauthorized,
+    /// The check couldn't be made
+    Unknown(String),
+}
 
-        if !runner_path.is_dir() || !runner_path.join("run.sh").exists() {
-            continue;
+impl Registration {
+    pub fn describe(&self) -> String {
+        match self {
+            Registration::Ok { online: true } => "registration OK (online)".to_string(),
+            Registration::Ok { online: false } => "registration OK (offline)".to_string(),
+            Registration::NotFound => "registration not found on GitHub".to_string(),
+            Registration::Deauthorized => {
+                "deauthorized: running here but offline to GitHub".to_string()
+            }
+            Registration::Unknown(reason) => format!("registration unknown: {}", reason),
         }
+    }
+}
 
-        let runner_num: u32 = runner_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0);
-
-        let service_name = format!(
-            "actions.runner.{}.{}-runner-{}",
-            username, repo_name, runner_num
-        );
-
-        let status = get_service_status(&service_name, &runner_path);
+impl Runner {
+    pub fn display_name(&self) -> String {
+        format!("{}-runner-{}", self.repo, self.number)
+    }
 
-        runners.push(Runner {
-            name: format!("runner-{}", runner_num),
-            number: runner_num,
-            repo: repo_name.to_string(),
-            status,
-            service_name,
-            path: runner_path,
-        });
+    /// Whether start/stop/restart will go through `sudo` on this platform.
+    ///
+    /// Linux services are system-scope systemd units controlled with `sudo systemctl`,
+    /// or `sudo ./svc.sh` before they are installed. macOS LaunchAgents are per-user.
+    pub fn control_requires_sudo(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            return false;
+        }
+        self.service_installed || self.path.join("svc.sh").exists()
     }
 
-    Ok(())
+    /// Returns true if this runner's version is known and differs from `newest`.
+    pub fn differs_from_version(&self, newest: Option<&str>) -> bool {
+        match (self.version.as_deref(), newest) {
+            (Some(version), Some(newest)) => version != newest,
+            _ => false,
+        }
+    }
 }
 
-/// Get the status of a runner service (cross-platform)
-fn get_service_status(service_name: &str, runner_path: &std::path::Path) -> RunnerStatus {
-    if cfg!(target_os = "macos") {
-        get_macos_service_status(service_name, runner_path)
-    } else {
-        get_linux_service_status(service_name, runner_path)
-    }
+/// Numeric sort key for dotted version strings, so "2.311.0" > "2.99.1"
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
 }
 
-/// Get service status on Linux using cached systemctl data.
-fn get_linux_service_status_cached(
-    service_name: &str,
-    runner_path: &std::path::Path,
-    systemctl_cache: &HashMap<String, String>,
-    running_processes: &HashMap<PathBuf, bool>,
-) -> RunnerStatus {
-    // Try cached systemctl status
-    if let Some(status_str) = systemctl_cache.get(service_name) {
-        match status_str.as_str() {
-            "active" => return RunnerStatus::Active,
-            "inactive" => return RunnerStatus::Inactive,
-            "failed" => return RunnerStatus::Failed,
-            _ => {}
-        }
+/// Returns true if `version` is a major version, or more than one minor version, behind `reference`.
+pub fn is_version_outdated(version: &str, reference: &str) -> bool {
+    let current = version_key(version);
+    let reference = version_key(reference);
+    let part = |key: &[u64], i: usize| key.get(i).copied().unwrap_or(0);
+
+    let (major, ref_major) = (part(&current, 0), part(&reference, 0));
+    if major != ref_major {
+        return major < ref_major;
     }
 
-    // Fallback: check if runner process is running using cached data
-    check_runner_status_fallback_cached(runner_path, running_processes)
+    part(&current, 1) + 1 < part(&reference, 1)
 }
 
-/// Get service status on Linux using systemctl with process-based fallback
-fn get_linux_service_status(service_name: &str, runner_path: &std::path::Path) -> RunnerStatus {
-    // Try to get status from systemd service unit
-    if let Some(status) = check_systemd_service_status(service_name) {
-        return status;
+/// Returns the newer of two dotted version strings.
+pub fn max_version<'a>(a: &'a str, b: &'a str) -> &'a str {
+    if version_key(b) > version_key(a) {
+        b
+    } else {
+        a
     }
+}
 
-    // Fallback: check if runner process is running
-    check_runner_status_fallback(runner_path)
+/// Order two agent versions numerically, so `2.9.0` comes before `2.10.0`.
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    version_key(a).cmp(&version_key(b))
 }
 
-/// Check if a systemd service unit exists
-fn systemctl_unit_exists(service_name: &str) -> bool {
-    Command::new("systemctl")
-        .args(["cat", service_name])
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+/// Returns the newest agent version present across the given runners.
+pub fn newest_version(runners: &[Runner]) -> Option<&str> {
+    runners
+        .iter()
+        .filter_map(|r| r.version.as_deref())
+        .max_by_key(|v| version_key(v))
 }
 
-/// Get all systemd service statuses in a batch, returning service name to status mapping.
-fn get_all_systemctl_services(service_names: &[String]) -> HashMap<String, String> {
-    let mut result = HashMap::new();
+/// Service names claimed by more than one runner.
+///
+/// Two directories that parse to the same number (e.g. `1` and `01`) get the same
+/// unit name, which makes status lookups and control actions ambiguous.
+pub fn duplicate_service_names(runners: &[Runner]) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    runners
+        .iter()
+        .filter(|r| !seen.insert(r.service_name.as_str()))
+        .map(|r| r.service_name.clone())
+        .collect()
+}
 
-    for service_name in service_names {
-        if !systemctl_unit_exists(service_name) {
-            continue;
-        }
+/// Settings that narrow or adjust runner discovery
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryOptions {
+    /// Only include these repositories; empty means all
+    pub repos: Vec<String>,
+    /// Account name used in service names, instead of inferring it
+    pub runner_user: Option<String>,
+    /// How service names are built from the account, repo and runner
+    pub service_template: ServiceTemplate,
+    /// Directories holding repository directories; empty means `runners_dir()`
+    pub dirs: Vec<PathBuf>,
+    /// Log sources for runners that don't log under their service unit, keyed by
+    /// service name or `repo/name`
+    pub log_sources: HashMap<String, LogSource>,
+}
 
-        // Get status
-        if let Ok(output) = Command::new("systemctl")
-            .args(["is-active", service_name])
-            .output()
-        {
-            let status = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            result.insert(service_name.clone(), status);
+impl DiscoveryOptions {
+    /// Root directories to discover runners under, without repeats.
+    ///
+    /// Two spellings of the same directory (e.g. a symlink to it) count as one.
+    pub fn roots(&self) -> Result<Vec<PathBuf>> {
+        if self.dirs.is_empty() {
+            return Ok(vec![runners_dir()?]);
         }
+        let mut seen = HashSet::new();
+        Ok(self
+            .dirs
+            .iter()
+            .filter(|dir| seen.insert(real_path(dir)))
+            .cloned()
+            .collect())
     }
 
-    result
-}
+    fn includes_repo(&self, repo_name: &str) -> bool {
+        self.repos.is_empty() || self.repos.iter().any(|r| r == repo_name)
+    }
 
-/// Check systemd service status, returns None if service doesn't exist
-fn check_systemd_service_status(service_name: &str) -> Option<RunnerStatus> {
-    if !systemctl_unit_exists(service_name) {
-        return None;
+    /// The configured log source for `runner`, by service name or `repo/name`.
+    fn log_source(&self, runner: &Runner) -> Option<LogSource> {
+        self.log_sources
+            .get(&runner.service_name)
+            .or_else(|| {
+                self.log_sources
+                    .get(&format!("{}/{}", runner.repo, runner.name))
+            })
+            .cloned()
     }
+}
 
-    let output = Command::new("systemctl")
-        .args(["is-active", service_name])
-        .output()
-        .ok()?;
+/// Directory holding one subdirectory per repository, each with numbered runners
+pub fn runners_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot find home directory"))?;
+    Ok(home.join("action-runners"))
+}
 
-    let status = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    match status.as_str() {
-        "active" => Some(RunnerStatus::Active),
-        "inactive" => Some(RunnerStatus::Inactive),
-        "failed" => Some(RunnerStatus::Failed),
+/// GitHub `owner/repo` a runner is registered with, from the `gitHubUrl` in its
+/// `.runner` file. None for organization-level runners and unconfigured directories.
+pub fn github_repo(runner_path: &Path) -> Option<String> {
+    let registration = read_registration(runner_path)?;
+    let url = registration.get("gitHubUrl")?.as_str()?;
+    let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let mut parts = path.trim_end_matches('/').split('/').skip(1);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), Some(repo), None) if !owner.is_empty() && !repo.is_empty() => {
+            Some(format!("{}/{}", owner, repo))
+        }
         _ => None,
     }
 }
 
-/// Check runner status using cached process data and configuration file checks
-fn check_runner_status_fallback_cached(
-    runner_path: &std::path::Path,
-    running_processes: &HashMap<PathBuf, bool>,
-) -> RunnerStatus {
-    if *running_processes.get(runner_path).unwrap_or(&false) {
-        return RunnerStatus::Active;
+/// Ask GitHub whether `runner` is still registered, matching it by agent name.
+/// Also returns the labels GitHub has on it, when it was found.
+pub fn check_registration(runner: &Runner, token: &str) -> (Registration, Option<Vec<String>>) {
+    let Some(repo) = github_repo(&runner.path) else {
+        let reason = "no repository URL in .runner (organization runners can't be checked)";
+        return (Registration::Unknown(reason.to_string()), None);
+    };
+    let Some(name) = agent_name(&runner.path) else {
+        let reason = "no agent name in .runner";
+        return (Registration::Unknown(reason.to_string()), None);
+    };
+    match github::repo_runners(token, &repo) {
+        Ok(registered) => {
+            let labels = registered
+                .iter()
+                .find(|r| r.name == name)
+                .map(|r| r.labels.iter().map(|l| l.name.clone()).collect());
+            (registration_status(runner, &name, &registered), labels)
+        }
+        Err(e) => (Registration::Unknown(format!("{:#}", e)), None),
     }
+}
 
-    if runner_path.join(".runner").exists() {
-        return RunnerStatus::Inactive;
+/// Add and remove labels on `runner`'s GitHub registration, returning the labels
+/// it ends up with.
+pub fn edit_labels(runner: &Runner, token: &str, edits: &LabelEdits) -> Result<Vec<String>> {
+    let repo = github_repo(&runner.path).ok_or_else(|| {
+        anyhow::anyhow!("No repository URL in .runner (organization runners aren't supported)")
+    })?;
+    let id = agent_id(&runner.path).ok_or_else(|| anyhow::anyhow!("No agent ID in .runner"))?;
+    let mut labels = None;
+    if !edits.add.is_empty() {
+        labels = Some(github::add_runner_labels(token, &repo, id, &edits.add)?);
     }
-
-    RunnerStatus::NotFound
+    for label in &edits.remove {
+        labels = Some(github::remove_runner_label(token, &repo, id, label)?);
+    }
+    Ok(labels.unwrap_or_default())
 }
 
-/// Check runner status using process and configuration file checks
-fn check_runner_status_fallback(runner_path: &std::path::Path) -> RunnerStatus {
-    if is_runner_process_running(runner_path) {
-        return RunnerStatus::Active;
+/// Compare a local runner with what GitHub has registered.
+fn registration_status(
+    runner: &Runner,
+    agent_name: &str,
+    registered: &[RegisteredRunner],
+) -> Registration {
+    match registered.iter().find(|r| r.name == agent_name) {
+        None => Registration::NotFound,
+        Some(found) if found.status == "online" => Registration::Ok { online: true },
+        Some(_) if runner.status == RunnerStatus::Active => Registration::Deauthorized,
+        Some(_) => Registration::Ok { online: false },
     }
+}
 
-    if runner_path.join(".runner").exists() {
-        return RunnerStatus::Inactive;
-    }
+/// Name the runner registered with GitHub, from the `agentName` in its `.runner` file.
+fn agent_name(runner_path: &Path) -> Option<String> {
+    let registration = read_registration(runner_path)?;
+    let name = registration.get("agentName")?.as_str()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
 
-    RunnerStatus::NotFound
+/// GitHub's ID for the runner, from the `agentId` in its `.runner` file.
+fn agent_id(runner_path: &Path) -> Option<u64> {
+    let registration = read_registration(runner_path)?;
+    let id = registration.get("agentId")?;
+    // Older runners write the ID as a string
+    id.as_u64()
+        .or_else(|| id.as_str().and_then(|s| s.parse().ok()))
 }
 
-/// Get service status on macOS using cached launchctl data.
-fn get_macos_service_status_cached(
-    service_name: &str,
-    runner_path: &std::path::Path,
-    launchctl_output: Option<&str>,
-    running_processes: &HashMap<PathBuf, bool>,
-) -> RunnerStatus {
-    // Try exact service name match
-    if let Some(status) = check_launchctl_exact_service(service_name) {
-        return status;
-    }
+/// The runner's work folder: `workFolder` from `.runner`, relative to the runner
+/// directory unless absolute, or `_work` when it isn't configured.
+pub fn work_folder(runner_path: &Path) -> PathBuf {
+    let configured = read_registration(runner_path).and_then(|registration| {
+        let folder = registration.get("workFolder")?.as_str()?;
+        (!folder.is_empty()).then(|| folder.to_string())
+    });
+    runner_path.join(configured.as_deref().unwrap_or("_work"))
+}
 
-    // Try partial match using cached launchctl output
-    if let Some(output) = launchctl_output {
-        if let Some(status) = check_launchctl_partial_match_cached(runner_path, output) {
-            return status;
+/// Count and size the job workspaces left in the runner's work folder.
+///
+/// The runner's own `_actions`, `_temp`, `_tool` etc. are caches it manages, so
+/// only the other directories count. A missing folder is clean.
+pub fn scan_work(runner: &Runner) -> Result<WorkUsage> {
+    let folder = work_folder(&runner.path);
+    let mut usage = WorkUsage {
+        folder: folder.clone(),
+        job_dirs: 0,
+        bytes: 0,
+        scanned_at: std::time::Instant::now(),
+    };
+    if !folder.exists() {
+        return Ok(usage);
+    }
+    for entry in std::fs::read_dir(&folder)
+        .with_context(|| format!("Failed to read {}", folder.display()))?
+    {
+        let entry = entry?;
+        let is_dir = entry.file_type()?.is_dir();
+        if !is_dir || entry.file_name().to_string_lossy().starts_with('_') {
+            continue;
         }
+        usage.job_dirs += 1;
+        usage.bytes += disk_usage(&entry.path());
     }
-
-    // Fallback: check runner process and configuration using cached data
-    check_runner_status_fallback_cached(runner_path, running_processes)
+    Ok(usage)
 }
 
-/// Get service status on macOS using launchctl or process check
-fn get_macos_service_status(service_name: &str, runner_path: &std::path::Path) -> RunnerStatus {
-    // Try exact service name match
-    if let Some(status) = check_launchctl_exact_service(service_name) {
-        return status;
+/// Bytes used by the files under `path`.
+///
+/// Symlinks are counted as links rather than followed, so a link back up the tree
+/// (or out to a huge shared cache) can't loop or inflate the total. Unreadable
+/// entries are skipped.
+fn disk_usage(path: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if !metadata.is_dir() {
+            total += metadata.len();
+            continue;
+        }
+        if let Ok(entries) = std::fs::read_dir(&path) {
+            pending.extend(entries.filter_map(|e| e.ok()).map(|e| e.path()));
+        }
     }
+    total
+}
 
-    // Try partial match for service name variations
-    if let Some(status) = check_launchctl_partial_match(runner_path) {
-        return status;
+/// Why the runner's work folder can't be pruned right now, if it can't.
+///
+/// A stopped runner is required: an active one could pick up a job while its
+/// workspace is being deleted.
+pub fn prune_blocker(runner: &Runner) -> Option<&'static str> {
+    if runner.busy {
+        Some("it is running a job")
+    } else if runner.status == RunnerStatus::Active {
+        Some("it is running and could start a job; stop it first (x)")
+    } else {
+        None
     }
-
-    // Fallback: check runner process and configuration
-    check_runner_status_fallback(runner_path)
 }
 
-/// Check launchctl for exact service name match
-fn check_launchctl_exact_service(service_name: &str) -> Option<RunnerStatus> {
-    let output = Command::new("launchctl")
-        .args(["list", service_name])
-        .output()
-        .ok()?;
+/// Delete everything in the runner's work folder and report the space freed.
+fn prune_work(runner: &Runner) -> Result<String> {
+    let name = runner.display_name();
+    if let Some(reason) = prune_blocker(runner) {
+        anyhow::bail!("Cannot prune {}: {}", name, reason);
+    }
+    let folder = work_folder(&runner.path);
+    if !folder.exists() {
+        return Ok(format!("{} has no work folder to prune", name));
+    }
+    // A workFolder of ".." or "/" in .runner must not take the runner with it
+    let real_folder = std::fs::canonicalize(&folder)?;
+    if std::fs::canonicalize(&runner.path)?.starts_with(&real_folder) {
+        anyhow::bail!(
+            "Refusing to prune {}: its work folder {} contains the runner itself",
+            name,
+            folder.display()
+        );
+    }
 
-    if !output.status.success() {
-        return None;
+    let mut freed = 0;
+    let mut failed = Vec::new();
+    for entry in std::fs::read_dir(&folder)
+        .with_context(|| format!("Failed to read {}", folder.display()))?
+    {
+        let path = entry?.path();
+        let bytes = disk_usage(&path);
+        // remove_dir_all deletes symlinks rather than following them
+        let removed = match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => std::fs::remove_dir_all(&path),
+            Ok(_) => std::fs::remove_file(&path),
+            Err(e) => Err(e),
+        };
+        match removed {
+            Ok(()) => freed += bytes,
+            Err(e) => failed.push(format!("{}: {}", path.display(), e)),
+        }
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let first_line = stdout.lines().next()?;
-    let parts: Vec<&str> = first_line.split_whitespace().collect();
-    let pid = parts.first()?;
+    if failed.is_empty() {
+        Ok(format!(
+            "Pruned {}: freed {} from {}",
+            name,
+            format_size(freed),
+            folder.display()
+        ))
+    } else {
+        // Typically files a container job left owned by root
+        anyhow::bail!(
+            "Pruned {} partly, freeing {}; could not remove {}",
+            name,
+            format_size(freed),
+            failed.join(", ")
+        )
+    }
+}
 
-    if *pid != "-" && pid.parse::<u32>().is_ok() {
-        Some(RunnerStatus::Active)
+/// Human-readable size, e.g. `512 KB` or `4.2 GB`
+pub fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= KB * KB * KB {
+        format!("{:.1} GB", bytes / (KB * KB * KB))
+    } else if bytes >= KB * KB {
+        format!("{:.1} MB", bytes / (KB * KB))
     } else {
-        Some(RunnerStatus::Inactive)
+        format!("{:.0} KB", bytes / KB)
     }
 }
 
-/// Get all launchctl services in a single call for parsing by multiple callers.
-fn get_all_launchctl_services() -> Option<String> {
-    let output = Command::new("launchctl").arg("list").output().ok()?;
+/// Parsed `.runner` file written when the runner was configured.
+fn read_registration(runner_path: &Path) -> Option<serde_json::Value> {
+    let contents = std::fs::read_to_string(runner_path.join(".runner")).ok()?;
+    // The runner writes this file with a byte order mark
+    serde_json::from_str(contents.trim_start_matches('\u{feff}')).ok()
+}
 
-    if !output.status.success() {
-        return None;
+/// Agent settings from `.runner` and the installed runner package, for comparing
+/// runners across the fleet. None where the runner doesn't say.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentSettings {
+    /// Package platform, e.g. `linux-x64`, told by the listener binary's header
+    pub platform: Option<String>,
+    /// `poolName`: the runner group it was registered into
+    pub runner_group: Option<String>,
+    pub ephemeral: Option<bool>,
+    /// `disableUpdate` inverted: whether it updates itself to new releases
+    pub auto_update: Option<bool>,
+    /// `workFolder` as configured, before resolving it
+    pub work_folder: Option<String>,
+}
+
+/// Read the settings the fleet view compares from a runner directory.
+pub fn agent_settings(runner_path: &Path) -> AgentSettings {
+    let registration = read_registration(runner_path);
+    let field = |key: &str| registration.as_ref().and_then(|r| r.get(key));
+    let string = |key: &str| {
+        field(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    AgentSettings {
+        platform: package_platform(runner_path),
+        runner_group: string("poolName"),
+        // The runner leaves these out unless set at configure time
+        ephemeral: registration.as_ref().map(|_| {
+            field("ephemeral")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+        }),
+        auto_update: registration.as_ref().map(|_| {
+            !field("disableUpdate")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+        }),
+        work_folder: registration
+            .as_ref()
+            .map(|_| string("workFolder").unwrap_or_else(|| "_work".to_string())),
     }
+}
 
-    Some(String::from_utf8_lossy(&output.stdout).to_string())
+/// Platform of the runner package in `runner_path`, named like GitHub's release
+/// assets (`linux-x64`, `osx-arm64`, `win-x64`), from `bin/Runner.Listener`.
+fn package_platform(runner_path: &Path) -> Option<String> {
+    use std::io::Read;
+    let bin = runner_path.join("bin");
+    let mut file = std::fs::File::open(bin.join("Runner.Listener"))
+        .or_else(|_| std::fs::File::open(bin.join("Runner.Listener.exe")))
+        .ok()?;
+    let mut header = [0u8; 20];
+    file.read_exact(&mut header).ok()?;
+    platform_from_header(&header)
 }
 
-/// Check launchctl list for partial service name match using cached output
-fn check_launchctl_partial_match_cached(
-    runner_path: &std::path::Path,
-    launchctl_output: &str,
-) -> Option<RunnerStatus> {
-    let parent_dir = runner_path
-        .parent()
-        .and_then(|p| p.file_name())
-        .and_then(|n| n.to_str())?;
+/// Name the OS and architecture of an ELF, Mach-O or PE executable header.
+fn platform_from_header(header: &[u8; 20]) -> Option<String> {
+    let (os, arch) = if header.starts_with(b"\x7fELF") {
+        let machine = match u16::from_le_bytes([header[18], header[19]]) {
+            0x3e => "x64",
+            0xb7 => "arm64",
+            0x28 => "arm",
+            _ => "unknown",
+        };
+        ("linux", machine)
+    } else if header.starts_with(&[0xcf, 0xfa, 0xed, 0xfe]) {
+        let cpu = match u32::from_le_bytes([header[4], header[5], header[6], header[7]]) {
+            0x0100_0007 => "x64",
+            0x0100_000c => "arm64",
+            _ => "unknown",
+        };
+        ("osx", cpu)
+    } else if header.starts_with(b"MZ") {
+        // The machine type sits past the DOS stub, beyond this header
+        return Some("win".to_string());
+    } else {
+        return None;
+    };
+    Some(format!("{}-{}", os, arch))
+}
 
-    for line in launchctl_output.lines() {
-        if line.contains("actions.runner") && line.contains(parent_dir) {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if let Some(pid) = parts.first() {
-                if *pid != "-" && pid.parse::<u32>().is_ok() {
-                    return Some(RunnerStatus::Active);
+/// Key fragments that mark a `.runner` field as a secret to redact
+const SECRET_KEY_PARTS: &[&str] = &["token", "secret", "password", "credential", "key", "auth"];
+
+/// The runner's `.runner` file pretty-printed for display, one line per entry,
+/// with any secret-looking field's value replaced by `<redacted>`.
+///
+/// Only `.runner` is read; `.credentials` and `.credentials_rsaparams` hold the
+/// runner's keys and are never opened.
+pub fn read_runner_config(runner_path: &Path) -> Result<Vec<String>> {
+    let path = runner_path.join(".runner");
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            anyhow::bail!(
+                "No .runner file in {}; the runner has not been configured",
+                runner_path.display()
+            )
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    // Never echo a file that doesn't parse; it could be anything
+    let mut config: serde_json::Value =
+        serde_json::from_str(contents.trim_start_matches('\u{feff}'))
+            .with_context(|| format!("{} is not valid JSON", path.display()))?;
+    redact_secrets(&mut config);
+    let pretty = serde_json::to_string_pretty(&config)?;
+    Ok(pretty.lines().map(str::to_string).collect())
+}
+
+/// Replace the values of secret-looking keys, at any depth.
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_KEY_PARTS.iter().any(|part| key.contains(part)) {
+                    *field = serde_json::Value::String("<redacted>".to_string());
+                } else {
+                    redact_secrets(field);
                 }
             }
         }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
     }
-
-    None
 }
 
-/// Check launchctl list for partial service name match
-fn check_launchctl_partial_match(runner_path: &std::path::Path) -> Option<RunnerStatus> {
-    let launchctl_output = get_all_launchctl_services()?;
-    check_launchctl_partial_match_cached(runner_path, &launchctl_output)
-}
+/// Discover all runners from the action-runners directory
+pub fn discover_runners(options: &DiscoveryOptions) -> Result<Vec<Runner>> {
+    let roots: Vec<PathBuf> = options
+        .roots()?
+        .into_iter()
+        .filter(|root| root.exists())
+        .collect();
 
-/// Batch check all runner processes with a single pgrep call.
-///
-/// Returns a HashMap indicating which runner paths have running processes.
-fn batch_check_running_processes(runner_paths: &[PathBuf]) -> HashMap<PathBuf, bool> {
-    let mut result: HashMap<PathBuf, bool> =
-        runner_paths.iter().map(|p| (p.clone(), false)).collect();
+    if roots.is_empty() {
+        return Ok(Vec::new());
+    }
 
-    let output = match Command::new("pgrep").args(["-af", "Runner"]).output() {
-        Ok(output) if output.status.success() => output,
-        _ => return result,
+    // One listing of installed services tells each runner's name and whether it's
+    // installed; status is left to the batched refresh that follows discovery
+    let installed = installed_service_names(&SystemCommandRunner, &options.service_template);
+    let username = resolve_runner_user(options, &installed).0;
+    // An explicit user names the services outright
+    let candidates = match options.runner_user {
+        Some(_) => HashSet::new(),
+        None => installed.clone(),
     };
+    let mut runners = Vec::new();
+    // Real paths of the runner directories found so far, so one reachable through
+    // a symlink as well is only listed once
+    let mut seen = HashSet::new();
+
+    for root in &roots {
+        let entries = dir_entries(root)
+            .with_context(|| format!("Failed to read runner directory {}", root.display()))?;
+        for repo_path in entries {
+            if !repo_path.is_dir() {
+                continue;
+            }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+            let Some(repo_name) = repo_path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !options.includes_repo(repo_name) {
+                continue;
+            }
 
-    for path in runner_paths {
-        let path_str = path.to_string_lossy();
-        if stdout.lines().any(|line| line.contains(&*path_str)) {
-            result.insert(path.clone(), true);
+            if repo_name.is_empty() {
+                continue;
+            }
+
+            runners.extend(discover_repo_runners(
+                root,
+                repo_name,
+                &options.service_template,
+                &username,
+                &candidates,
+                &installed,
+                &mut seen,
+            )?);
         }
     }
 
-    result
+    qualify_colliding_repos(&mut runners);
+    runners.sort_by(|a, b| a.repo.cmp(&b.repo).then_with(|| a.number.cmp(&b.number)));
+    for runner in runners.iter_mut() {
+        runner.log_source = options.log_source(runner);
+    }
+
+    Ok(runners)
 }
 
-/// Check if a runner process is running by looking for Runner.Worker/Listener
-fn is_runner_process_running(runner_path: &std::path::Path) -> bool {
-    // Validate path to prevent command injection via pgrep pattern
-    if validate_path(runner_path).is_err() {
-        return false;
+/// Where the account in service names came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UserSource {
+    /// `--runner-user` or `runner_user` in the config
+    Configured,
+    /// The one account all installed runner services are named after
+    Installed,
+    /// `$USER`
+    Environment,
+}
+
+impl UserSource {
+    pub fn describe(self) -> &'static str {
+        match self {
+            UserSource::Configured => "from --runner-user / runner_user",
+            UserSource::Installed => "inferred from installed services",
+            UserSource::Environment => "from $USER",
+        }
     }
+}
 
-    let path_str = runner_path.to_string_lossy();
+/// Account used to build service names, and where it came from.
+///
+/// An explicit user wins; otherwise prefer the units that are actually installed,
+/// since $USER is wrong when the dashboard runs as another account (e.g. via sudo).
+pub fn resolve_runner_user(
+    options: &DiscoveryOptions,
+    installed: &HashSet<String>,
+) -> (String, UserSource) {
+    if let Some(user) = &options.runner_user {
+        return (user.clone(), UserSource::Configured);
+    }
+    match infer_runner_user(&options.service_template, installed) {
+        Some(user) => (user, UserSource::Installed),
+        None => (
+            std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
+            UserSource::Environment,
+        ),
+    }
+}
 
-    // Patterns to search for (Runner.Worker, Runner.Listener, or just the path in any dotnet process)
-    let patterns = [
-        format!("Runner.Worker.*{}", path_str),
-        format!("Runner.Listener.*{}", path_str),
-        format!("dotnet.*{}", path_str),
-        path_str.to_string(), // Just the path - catches any process with this dir
-    ];
+/// Service names of installed runners following `template`: systemd units, or
+/// LaunchAgents on macOS.
+pub fn installed_service_names(
+    cmd: &dyn CommandRunner,
+    template: &ServiceTemplate,
+) -> HashSet<String> {
+    let names: HashSet<String> = if cfg!(target_os = "macos") {
+        let agents_dir = PathBuf::from(shellexpand::tilde("~/Library/LaunchAgents").as_ref());
+        let Ok(entries) = std::fs::read_dir(agents_dir) else {
+            return HashSet::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter_map(|name| name.strip_suffix(".plist").map(str::to_string))
+            .collect()
+    } else {
+        UnitCache::load(cmd).units
+    };
+    names
+        .into_iter()
+        .filter(|name| template.fits(name))
+        .collect()
+}
 
-    for pattern in &patterns {
-        let output = Command::new("pgrep").args(["-f", pattern]).output();
+/// The `{user}` in installed service names, if all of them agree on one.
+fn infer_runner_user(template: &ServiceTemplate, installed: &HashSet<String>) -> Option<String> {
+    let users: HashSet<&str> = installed
+        .iter()
+        .filter_map(|name| template.user_in(name))
+        .collect();
+    match users.len() {
+        1 => users.into_iter().next().map(str::to_string),
+        _ => None,
+    }
+}
 
-        if let Ok(output) = output {
-            if output.status.success() && !output.stdout.is_empty() {
-                return true;
-            }
+/// Service name for a runner directory.
+///
+/// Uses the installed service matching `template` for this runner when there is one,
+/// so the name is right whichever account installed it. Otherwise falls back to the
+/// name the template gives it under `username`.
+fn resolve_service_name(
+    installed: &HashSet<String>,
+    template: &ServiceTemplate,
+    username: &str,
+    repo_name: &str,
+    runner_num: u32,
+    agent_name: &str,
+) -> String {
+    let expected = template.render(username, repo_name, runner_num, agent_name);
+    if installed.contains(&expected) {
+        return expected;
+    }
+
+    installed
+        .iter()
+        .filter(|name| template.matches_runner(name, repo_name, runner_num, agent_name))
+        .min()
+        .cloned()
+        .unwrap_or(expected)
+}
+
+/// Tell apart repositories of the same name found under different roots.
+///
+/// Their runners would otherwise share labels like `acme/1`, so each gets the
+/// name of its root appended: `acme@srv-runners`. Falls back to the full root
+/// path when the roots' own names are the same too.
+fn qualify_colliding_repos(runners: &mut [Runner]) {
+    let mut roots_by_repo: HashMap<String, HashSet<PathBuf>> = HashMap::new();
+    for runner in runners.iter() {
+        roots_by_repo
+            .entry(runner.repo.clone())
+            .or_default()
+            .insert(runner.root.clone());
+    }
+
+    for runner in runners.iter_mut() {
+        let roots = &roots_by_repo[&runner.repo];
+        if roots.len() < 2 {
+            continue;
         }
+        let root_name = runner.root.file_name().and_then(|n| n.to_str());
+        let unique = root_name.is_some_and(|name| {
+            roots
+                .iter()
+                .filter(|root| root.file_name().and_then(|n| n.to_str()) == Some(name))
+                .count()
+                == 1
+        });
+        let label = match root_name {
+            Some(name) if unique => name.to_string(),
+            _ => runner.root.display().to_string(),
+        };
+        runner.repo = format!("{}@{}", runner.repo, label);
     }
+}
 
-    false
+/// Entries of `dir`, real directories before symlinks and otherwise by name, so a
+/// runner reachable both ways is found at its real location first.
+fn dir_entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        entries.push((path.is_symlink(), path));
+    }
+    entries.sort();
+    Ok(entries.into_iter().map(|(_, path)| path).collect())
 }
 
-/// Refresh the status of all runners using batch operations.
+/// `path` with symlinks resolved, or as given if it can't be resolved.
+fn real_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Discover runners within the repository directory `repo_name` under `root`.
 ///
-/// Minimizes system calls by batching process checks and service queries.
-pub fn refresh_runners(runners: &mut [Runner]) {
-    if runners.is_empty() {
-        return;
+/// Service names are resolved against `candidates`, and a runner counts as
+/// installed if its name is in `installed`. Status is NotFound until refreshed.
+///
+/// Skips runner directories whose real path is already in `seen`, and adds the
+/// ones it finds.
+fn discover_repo_runners(
+    root: &Path,
+    repo_name: &str,
+    template: &ServiceTemplate,
+    username: &str,
+    candidates: &HashSet<String>,
+    installed: &HashSet<String>,
+    seen: &mut HashSet<PathBuf>,
+) -> Result<Vec<Runner>> {
+    let mut runners = Vec::new();
+    for runner_path in dir_entries(&root.join(repo_name))? {
+        if !runner_path.is_dir() || !runner_path.join("run.sh").exists() {
+            continue;
+        }
+        if !seen.insert(real_path(&runner_path)) {
+            continue;
+        }
+
+        let runner_num: u32 = runner_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+
+        let agent_name = agent_name(&runner_path)
+            .unwrap_or_else(|| format!("{}-runner-{}", repo_name, runner_num));
+        let service_name = resolve_service_name(
+            candidates,
+            template,
+            username,
+            repo_name,
+            runner_num,
+            &agent_name,
+        );
+
+        let service_installed = installed.contains(&service_name);
+        let version = detect_runner_version(&runner_path);
+
+        runners.push(Runner {
+            name: format!("runner-{}", runner_num),
+            number: runner_num,
+            repo: repo_name.to_string(),
+            status: RunnerStatus::NotFound,
+            service_name,
+            path: runner_path,
+            root: root.to_path_buf(),
+            service_installed,
+            version,
+            busy: false,
+            registration: None,
+            work: None,
+            service_state: None,
+            labels: None,
+            log_source: None,
+            user: username.to_string(),
+        });
     }
 
-    let runner_paths: Vec<PathBuf> = runners.iter().map(|r| r.path.clone()).collect();
-    let running_processes = batch_check_running_processes(&runner_paths);
+    Ok(runners)
+}
 
-    if cfg!(target_os = "macos") {
-        let launchctl_output = get_all_launchctl_services();
+/// Detect the runner agent version from files in its directory, without
+/// starting the listener
+fn detect_runner_version(runner_path: &Path) -> Option<String> {
+    // Self-updated runners point `bin` at a versioned `bin.<version>` directory
+    if let Ok(target) = std::fs::read_link(runner_path.join("bin")) {
+        if let Some(version) = target
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix("bin."))
+        {
+            return Some(version.to_string());
+        }
+    }
 
-        for runner in runners.iter_mut() {
-            runner.status = get_macos_service_status_cached(
-                &runner.service_name,
-                &runner.path,
-                launchctl_output.as_deref(),
-                &running_processes,
-            );
+    // Some installs record the version in a plain file
+    if let Ok(content) = std::fs::read_to_string(runner_path.join(".version")) {
+        let version = content.trim();
+        if !version.is_empty() {
+            return Some(version.to_string());
+        }
+    }
+
+    // Otherwise the listener's .NET manifest names it as `Runner.Listener/<version>`
+    let deps =
+        std::fs::read_to_string(runner_path.join("bin").join("Runner.Listener.deps.json")).ok()?;
+    let deps: serde_json::Value = serde_json::from_str(&deps).ok()?;
+    deps.get("targets")?
+        .as_object()?
+        .values()
+        .filter_map(|target| target.as_object())
+        .flat_map(|target| target.keys())
+        .find_map(|key| key.strip_prefix("Runner.Listener/"))
+        .filter(|version| !version.is_empty())
+        .map(str::to_string)
+}
+
+/// Get service status on Linux using cached systemctl data.
+fn get_linux_service_status_cached(
+    service_name: &str,
+    runner_path: &std::path::Path,
+    systemctl_cache: &HashMap<String, ServiceState>,
+    running_processes: &HashMap<PathBuf, RunnerProcesses>,
+) -> RunnerStatus {
+    // Try cached systemctl status
+    if let Some(state) = systemctl_cache.get(service_name) {
+        match state.active.as_str() {
+            "active" => return RunnerStatus::Active,
+            "inactive" => return RunnerStatus::Inactive,
+            "failed" => return RunnerStatus::Failed,
+            _ => {}
         }
+    }
+
+    // Fallback: check if runner process is running using cached data
+    check_runner_status_fallback_cached(runner_path, running_processes)
+}
+
+/// Check whether a service unit (systemd) or plist (launchd) is installed for a runner
+fn is_service_installed(cmd: &dyn CommandRunner, service_name: &str) -> bool {
+    if cfg!(target_os = "macos") {
+        launchd_plist_path(service_name).exists()
     } else {
-        let service_names: Vec<String> = runners.iter().map(|r| r.service_name.clone()).collect();
-        let systemctl_statuses = get_all_systemctl_services(&service_names);
+        systemctl_unit_exists(cmd, service_name)
+    }
+}
 
-        for runner in runners.iter_mut() {
-            runner.status = get_linux_service_status_cached(
-                &runner.service_name,
-                &runner.path,
-                &systemctl_statuses,
-                &running_processes,
-            );
+/// Installed systemd runner units, loaded with one call and reused across refreshes.
+///
+/// Unit existence rarely changes, so the worker reloads this only after control
+/// actions (which may install or remove units) rather than on every status poll.
+#[derive(Debug, Clone, Default)]
+pub struct UnitCache {
+    units: HashSet<String>,
+}
+
+impl UnitCache {
+    /// Load installed `actions.runner.*` service units (always empty on macOS).
+    pub fn load(cmd: &dyn CommandRunner) -> Self {
+        if cfg!(target_os = "macos") {
+            return Self::default();
         }
+
+        let units = cmd
+            .run(
+                "systemctl",
+                &[
+                    "list-unit-files",
+                    "actions.runner.*",
+                    "--type=service",
+                    "--no-legend",
+                ],
+            )
+            .map(|o| parse_unit_files_output(&String::from_utf8_lossy(&o.stdout)))
+            .unwrap_or_default();
+
+        Self { units }
+    }
+
+    pub fn contains(&self, service_name: &str) -> bool {
+        self.units.contains(service_name)
     }
 }
 
-/// Allowed actions for runner control
-const ALLOWED_ACTIONS: &[&str] = &["start", "stop", "restart"];
+/// Extract service names (without the `.service` suffix) from `systemctl list-unit-files` output.
+fn parse_unit_files_output(stdout: &str) -> HashSet<String> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter_map(|unit| unit.strip_suffix(".service"))
+        .map(|name| name.to_string())
+        .collect()
+}
 
-/// Control a runner service with input validation (cross-platform)
-pub fn control_runner(runner: &Runner, action: &str) -> Result<String> {
-    // Validate action is allowed
-    if !ALLOWED_ACTIONS.contains(&action) {
-        return Err(anyhow::anyhow!("Invalid action: {}", action));
+/// Check if a systemd service unit exists
+fn systemctl_unit_exists(cmd: &dyn CommandRunner, service_name: &str) -> bool {
+    cmd.run("systemctl", &["cat", service_name])
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Get all systemd service statuses in a batch, returning service name to status mapping.
+///
+/// Units that don't exist are omitted so callers fall back to process detection.
+fn get_all_systemctl_services(
+    cmd: &dyn CommandRunner,
+    service_names: &[String],
+    units: &UnitCache,
+) -> HashMap<String, ServiceState> {
+    // Query each unit once, even if several runners claim it
+    let mut seen = HashSet::new();
+    let existing: Vec<String> = service_names
+        .iter()
+        .filter(|name| units.contains(name) && seen.insert(name.as_str()))
+        .cloned()
+        .collect();
+
+    if existing.is_empty() {
+        return HashMap::new();
     }
 
-    // Validate service name matches expected pattern (alphanumeric, dots, hyphens only)
-    if !runner
-        .service_name
-        .chars()
-        .all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_')
+    // systemctl prints one block of properties per unit, in argument order
+    let mut args = vec!["show", "--property=ActiveState,SubState,NRestarts"];
+    args.extend(existing.iter().map(String::as_str));
+
+    match cmd.run("systemctl", &args) {
+        Ok(output) if output.status.success() => {
+            parse_show_output(&existing, &String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
+    }
+}
+
+/// Map multi-unit `systemctl show` output back to service names by position.
+///
+/// Blocks are separated by blank lines and hold `Key=value` lines.
+fn parse_show_output(service_names: &[String], stdout: &str) -> HashMap<String, ServiceState> {
+    let blocks = stdout
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty());
+    service_names
+        .iter()
+        .zip(blocks)
+        .filter_map(|(name, block)| {
+            let property = |key: &str| {
+                block.lines().find_map(|line| {
+                    line.trim()
+                        .strip_prefix(key)
+                        .and_then(|rest| rest.strip_prefix('='))
+                        .map(str::to_string)
+                })
+            };
+            let state = ServiceState {
+                active: property("ActiveState")?,
+                sub: property("SubState")?,
+                restarts: property("NRestarts").and_then(|n| n.parse().ok()),
+            };
+            Some((name.clone(), state))
+        })
+        .collect()
+}
+
+/// Check runner status using cached process data and configuration file checks
+fn check_runner_status_fallback_cached(
+    runner_path: &std::path::Path,
+    running_processes: &HashMap<PathBuf, RunnerProcesses>,
+) -> RunnerStatus {
+    if running_processes
+        .get(runner_path)
+        .is_some_and(|processes| processes.running)
     {
-        return Err(anyhow::anyhow!("Invalid service name format"));
+        return RunnerStatus::Active;
     }
 
-    // Validate service name starts with expected prefix
-    if !runner.service_name.starts_with("actions.runner.") {
-        return Err(anyhow::anyhow!(
-            "Service name must start with 'actions.runner.'"
-        ));
+    if runner_path.join(".runner").exists() {
+        return RunnerStatus::Inactive;
     }
 
-    if cfg!(target_os = "macos") {
-        control_runner_macos(runner, action)
+    RunnerStatus::NotFound
+}
+
+/// Get service status on macOS using cached launchctl data.
+fn get_macos_service_status_cached(
+    cmd: &dyn CommandRunner,
+    service_name: &str,
+    runner_path: &std::path::Path,
+    launchctl_output: Option<&str>,
+    running_processes: &HashMap<PathBuf, RunnerProcesses>,
+) -> RunnerStatus {
+    // Try exact service name match
+    if let Some(status) = check_launchctl_exact_service(cmd, service_name) {
+        return status;
+    }
+
+    // Try partial match using cached launchctl output
+    if let Some(output) = launchctl_output {
+        if let Some(status) = check_launchctl_partial_match_cached(runner_path, output) {
+            return status;
+        }
+    }
+
+    // Fallback: check runner process and configuration using cached data
+    check_runner_status_fallback_cached(runner_path, running_processes)
+}
+
+/// Check launchctl for exact service name match
+fn check_launchctl_exact_service(
+    cmd: &dyn CommandRunner,
+    service_name: &str,
+) -> Option<RunnerStatus> {
+    let output = cmd.run("launchctl", &["list", service_name]).ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().next()?;
+    let parts: Vec<&str> = first_line.split_whitespace().collect();
+    let pid = parts.first()?;
+
+    if *pid != "-" && pid.parse::<u32>().is_ok() {
+        Some(RunnerStatus::Active)
     } else {
-        control_runner_linux(runner, action)
+        Some(RunnerStatus::Inactive)
+    }
+}
+
+/// Get all launchctl services in a single call for parsing by multiple callers.
+fn get_all_launchctl_services(cmd: &dyn CommandRunner) -> Option<String> {
+    let output = cmd.run("launchctl", &["list"]).ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Check launchctl list for partial service name match using cached output
+fn check_launchctl_partial_match_cached(
+    runner_path: &std::path::Path,
+    launchctl_output: &str,
+) -> Option<RunnerStatus> {
+    let parent_dir = runner_path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())?;
+
+    for line in launchctl_output.lines() {
+        if line.contains("actions.runner") && line.contains(parent_dir) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if let Some(pid) = parts.first() {
+                if *pid != "-" && pid.parse::<u32>().is_ok() {
+                    return Some(RunnerStatus::Active);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// What the process scan found for one runner directory
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct RunnerProcesses {
+    /// Any runner process (listener, worker, run.sh) is alive
+    running: bool,
+    /// A `Runner.Worker` is alive, i.e. a job is executing
+    worker: bool,
+}
+
+/// Batch check all runner processes with a single pgrep call.
+///
+/// Returns which runner paths have running processes, and which are running a job.
+fn batch_check_running_processes(
+    cmd: &dyn CommandRunner,
+    runner_paths: &[PathBuf],
+) -> HashMap<PathBuf, RunnerProcesses> {
+    let mut result: HashMap<PathBuf, RunnerProcesses> = runner_paths
+        .iter()
+        .map(|p| (p.clone(), RunnerProcesses::default()))
+        .collect();
+
+    let output = match cmd.run("pgrep", &["-af", "Runner"]) {
+        Ok(output) if output.status.success() => output,
+        _ => return result,
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    for path in runner_paths {
+        let path_str = path.to_string_lossy();
+        let mut lines = stdout
+            .lines()
+            .filter(|line| line_references_path(line, &path_str))
+            .peekable();
+        if lines.peek().is_none() {
+            continue;
+        }
+        let worker = lines.any(|line| line.contains("Runner.Worker"));
+        result.insert(
+            path.clone(),
+            RunnerProcesses {
+                running: true,
+                worker,
+            },
+        );
+    }
+
+    result
+}
+
+/// Returns true if `line` mentions `path` as a whole path component.
+///
+/// A plain substring match would let `.../acme/1` match a process under `.../acme/10`.
+fn line_references_path(line: &str, path: &str) -> bool {
+    line.match_indices(path).any(|(start, _)| {
+        line[start + path.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| c == '/' || c.is_whitespace())
+    })
+}
+
+/// Anchor a pgrep/pkill pattern so the path only matches as a whole component.
+fn path_boundary_pattern(path_str: &str) -> String {
+    format!("{}(/|[[:space:]]|$)", path_str)
+}
+
+/// Check if a runner process is running by looking for Runner.Worker/Listener
+fn is_runner_process_running(cmd: &dyn CommandRunner, runner_path: &std::path::Path) -> bool {
+    // Validate path to prevent command injection via pgrep pattern
+    if validate_path(runner_path).is_err() {
+        return false;
+    }
+
+    let path_pattern = path_boundary_pattern(&runner_path.to_string_lossy());
+
+    // Patterns to search for (Runner.Worker, Runner.Listener, or just the path in any dotnet process)
+    let patterns = [
+        format!("Runner.Worker.*{}", path_pattern),
+        format!("Runner.Listener.*{}", path_pattern),
+        format!("dotnet.*{}", path_pattern),
+        path_pattern.clone(), // Just the path - catches any process with this dir
+    ];
+
+    for pattern in &patterns {
+        let output = cmd.run("pgrep", &["-f", pattern]);
+
+        if let Ok(output) = output {
+            if output.status.success() && !output.stdout.is_empty() {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Host tools status and logs come from, with what the dashboard can't tell
+/// without each.
+fn host_tools() -> &'static [(&'static str, &'static str)] {
+    if cfg!(target_os = "macos") {
+        &[
+            ("launchctl", "status limited to process detection"),
+            ("pgrep", "running and busy runners can't be detected"),
+        ]
+    } else {
+        &[
+            ("systemctl", "status limited to process detection"),
+            ("pgrep", "running and busy runners can't be detected"),
+            ("journalctl", "logs limited to _diag files"),
+        ]
+    }
+}
+
+/// A line per host tool that isn't on `path` (as in `$PATH`), e.g. "systemctl
+/// not found — status limited to process detection". Without them queries fail
+/// the same way as for a runner that isn't there, so this says why.
+pub fn missing_tools(path: Option<&OsStr>) -> Vec<String> {
+    host_tools()
+        .iter()
+        .filter(|(name, _)| find_executable(name, path).is_none())
+        .map(|(name, without)| format!("{} not found — {}", name, without))
+        .collect()
+}
+
+/// Where `name` is on `path` (as in `$PATH`), if it's there and executable.
+pub fn find_executable(name: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    std::env::split_paths(path?)
+        .map(|dir| dir.join(name))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+/// How long each phase of one `refresh_runners` call took, for `--profile`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefreshTimings {
+    pub runners: usize,
+    /// Finding runner processes (`pgrep`/`ps`)
+    pub processes: Duration,
+    /// The batched `systemctl` or `launchctl` query
+    pub services: Duration,
+    /// The service query was answered from `ServiceCache`
+    pub cached: bool,
+    pub total: Duration,
+}
+
+impl RefreshTimings {
+    pub fn describe(&self) -> String {
+        let backend = if cfg!(target_os = "macos") {
+            "launchctl"
+        } else {
+            "systemctl"
+        };
+        let services = if self.cached {
+            format!("{} cached", backend)
+        } else {
+            format!("{} {}ms", backend, self.services.as_millis())
+        };
+        format!(
+            "Refresh of {} runners took {}ms: processes {}ms, {}",
+            self.runners,
+            self.total.as_millis(),
+            self.processes.as_millis(),
+            services
+        )
+    }
+}
+
+/// Service query results kept between refreshes, so refreshing every second
+/// doesn't mean running `systemctl`/`launchctl` every second.
+///
+/// The process scan still runs each time. Results are reused only while they are
+/// younger than the TTL and no runner has gained or lost its processes since, so
+/// a runner that starts or dies is picked up on the next refresh regardless.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceCache {
+    ttl: Duration,
+    fetched_at: Option<Instant>,
+    systemctl: HashMap<String, ServiceState>,
+    launchctl: Option<String>,
+    /// Which runners had processes when the results were fetched
+    running: HashMap<PathBuf, bool>,
+}
+
+impl ServiceCache {
+    /// A zero TTL queries the backend on every refresh.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            ..Self::default()
+        }
+    }
+
+    /// Query the backend on the next refresh, e.g. after a control action.
+    pub fn invalidate(&mut self) {
+        self.fetched_at = None;
+    }
+
+    fn is_fresh(&self, processes: &HashMap<PathBuf, RunnerProcesses>, now: Instant) -> bool {
+        self.fetched_at
+            .is_some_and(|at| now.duration_since(at) < self.ttl)
+            && self.running.len() == processes.len()
+            && processes
+                .iter()
+                .all(|(path, p)| self.running.get(path) == Some(&p.running))
+    }
+}
+
+/// Refresh the status of all runners using batch operations.
+///
+/// Minimizes system calls by batching process checks and service queries.
+pub fn refresh_runners(
+    cmd: &dyn CommandRunner,
+    runners: &mut [Runner],
+    units: &UnitCache,
+) -> RefreshTimings {
+    refresh_runners_cached(cmd, runners, units, &mut ServiceCache::default())
+}
+
+/// Like `refresh_runners`, reusing the service query in `cache` while it is fresh.
+pub fn refresh_runners_cached(
+    cmd: &dyn CommandRunner,
+    runners: &mut [Runner],
+    units: &UnitCache,
+    cache: &mut ServiceCache,
+) -> RefreshTimings {
+    let started = Instant::now();
+    let mut timings = RefreshTimings {
+        runners: runners.len(),
+        ..RefreshTimings::default()
+    };
+    if runners.is_empty() {
+        return timings;
+    }
+
+    let runner_paths: Vec<PathBuf> = runners.iter().map(|r| r.path.clone()).collect();
+    let running_processes = batch_check_running_processes(cmd, &runner_paths);
+    timings.processes = started.elapsed();
+
+    let services_started = Instant::now();
+    if cache.is_fresh(&running_processes, services_started) {
+        timings.cached = true;
+    } else {
+        if cfg!(target_os = "macos") {
+            cache.launchctl = get_all_launchctl_services(cmd);
+        } else {
+            let service_names: Vec<String> =
+                runners.iter().map(|r| r.service_name.clone()).collect();
+            cache.systemctl = get_all_systemctl_services(cmd, &service_names, units);
+        }
+        cache.fetched_at = Some(services_started);
+        cache.running = running_processes
+            .iter()
+            .map(|(path, p)| (path.clone(), p.running))
+            .collect();
+        timings.services = services_started.elapsed();
+    }
+
+    // A service shared by several directories belongs to at most one of them, so its
+    // state says nothing reliable about the others. Judge each by its own processes.
+    let shared = duplicate_service_names(runners);
+
+    if cfg!(target_os = "macos") {
+        for runner in runners.iter_mut() {
+            runner.busy = is_busy(&runner.path, &running_processes);
+            if shared.contains(&runner.service_name) {
+                runner.status =
+                    check_runner_status_fallback_cached(&runner.path, &running_processes);
+                runner.service_installed = launchd_plist_path(&runner.service_name).exists();
+                continue;
+            }
+            runner.status = get_macos_service_status_cached(
+                cmd,
+                &runner.service_name,
+                &runner.path,
+                cache.launchctl.as_deref(),
+                &running_processes,
+            );
+            runner.service_installed = launchd_plist_path(&runner.service_name).exists();
+        }
+    } else {
+        let systemctl_statuses = &cache.systemctl;
+        for runner in runners.iter_mut() {
+            runner.busy = is_busy(&runner.path, &running_processes);
+            runner.service_state = None;
+            runner.status = if shared.contains(&runner.service_name) {
+                check_runner_status_fallback_cached(&runner.path, &running_processes)
+            } else {
+                runner.service_state = systemctl_statuses.get(&runner.service_name).cloned();
+                get_linux_service_status_cached(
+                    &runner.service_name,
+                    &runner.path,
+                    systemctl_statuses,
+                    &running_processes,
+                )
+            };
+            // Only existing units are present in the batch result
+            runner.service_installed = systemctl_statuses.contains_key(&runner.service_name);
+        }
+    }
+
+    timings.total = started.elapsed();
+    timings
+}
+
+fn is_busy(runner_path: &Path, running_processes: &HashMap<PathBuf, RunnerProcesses>) -> bool {
+    running_processes
+        .get(runner_path)
+        .is_some_and(|processes| processes.worker)
+}
+
+/// Allowed actions for runner control
+const ALLOWED_ACTIONS: &[&str] = &[
+    "start",
+    "stop",
+    "restart",
+    "install",
+    "uninstall",
+    "deregister",
+    PRUNE_WORK_ACTION,
+];
+
+/// Action that empties a runner's work folder
+pub const PRUNE_WORK_ACTION: &str = "prune-work";
+
+/// Control a runner service with input validation (cross-platform)
+pub fn control_runner(cmd: &dyn CommandRunner, runner: &Runner, action: &str) -> Result<String> {
+    // Validate action is allowed
+    if !ALLOWED_ACTIONS.contains(&action) {
+        return Err(anyhow::anyhow!("Invalid action: {}", action));
+    }
+
+    // Only touches files, so the service name doesn't matter
+    if action == PRUNE_WORK_ACTION {
+        return prune_work(runner);
+    }
+
+    // Validate service name matches expected pattern (alphanumeric, dots, hyphens only)
+    if !runner
+        .service_name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_')
+    {
+        return Err(anyhow::anyhow!("Invalid service name format"));
+    }
+
+    // Validate service name starts with expected prefix
+    if !runner.service_name.starts_with("actions.runner.") {
+        return Err(anyhow::anyhow!(
+            "Service name must start with 'actions.runner.'"
+        ));
+    }
+
+    match action {
+        "install" => return install_runner_service(cmd, runner),
+        "uninstall" => return uninstall_runner_service(cmd, runner),
+        "deregister" => return deregister_runner(cmd, runner),
+        _ => {}
+    }
+
+    if cfg!(target_os = "macos") {
+        control_runner_macos(cmd, runner, action)
+    } else {
+        control_runner_linux(cmd, runner, action)
+    }
+}
+
+/// Install the runner's service via svc.sh without starting it
+fn install_runner_service(cmd: &dyn CommandRunner, runner: &Runner) -> Result<String> {
+    if is_service_installed(cmd, &runner.service_name) {
+        return Err(anyhow::anyhow!(
+            "Service for {} is already installed",
+            runner.display_name()
+        ));
+    }
+
+    let svc_script = runner.path.join("svc.sh");
+    if !svc_script.exists() {
+        // Adopt script-only runners by writing the service definition ourselves
+        if runner.path.join("run.sh").exists() {
+            return if cfg!(target_os = "macos") {
+                install_launchd_agent(cmd, runner)
+            } else {
+                install_systemd_unit(cmd, runner)
+            };
+        }
+        return Err(anyhow::anyhow!(
+            "Cannot install service for {}: no svc.sh found",
+            runner.display_name()
+        ));
+    }
+
+    // svc.sh needs root for systemd units, but installs LaunchAgents as the user
+    let use_sudo = !cfg!(target_os = "macos");
+    install_service(&svc_script, &runner.path, runner, use_sudo)?;
+
+    Ok(format!(
+        "Successfully installed service for {}",
+        runner.display_name()
+    ))
+}
+
+/// Control runner on Linux using systemctl with svc.sh/run.sh fallback
+fn control_runner_linux(cmd: &dyn CommandRunner, runner: &Runner, action: &str) -> Result<String> {
+    // Try systemctl first
+    if let Some(result) = try_systemctl_control(cmd, runner, action)? {
+        return Ok(result);
+    }
+
+    // Fallback to svc.sh script
+    if let Some(result) = try_svc_script_control(runner, action, true)? {
+        return Ok(result);
+    }
+
+    // Final fallback: direct run.sh control
+    control_runner_direct(cmd, runner, action)
+}
+
+/// Attempt to control runner using systemctl, returns None if service doesn't exist
+fn try_systemctl_control(
+    cmd: &dyn CommandRunner,
+    runner: &Runner,
+    action: &str,
+) -> Result<Option<String>> {
+    // Checked live rather than via UnitCache: a unit may have just been installed
+    if !systemctl_unit_exists(cmd, &runner.service_name) {
+        return Ok(None);
+    }
+
+    let output = cmd.run("sudo", &["systemctl", action, &runner.service_name])?;
+    let command = format!("sudo systemctl {} {}", action, runner.service_name);
+
+    handle_control_output(output, command, action, runner)
+}
+
+/// A service command that exited unsuccessfully, with everything it printed.
+///
+/// Returned inside the `anyhow::Error` from `control_runner`, so callers that want
+/// the full output can `downcast_ref` it; its `Display` is the one-line summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandFailure {
+    /// What was being attempted, e.g. "Failed to start acme-runner-1"
+    pub context: String,
+    pub command: String,
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandFailure {
+    fn new(context: String, command: String, output: &Output) -> Self {
+        Self {
+            context,
+            command,
+            status: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+
+    /// The command followed by its output, as shown in the output overlay.
+    pub fn transcript(&self) -> Vec<String> {
+        command_transcript(&self.command, &self.stdout, &self.stderr, self.status)
+    }
+}
+
+impl std::fmt::Display for CommandFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.context, self.stderr.trim())
+    }
+}
+
+impl std::error::Error for CommandFailure {}
+
+/// `$ command`, then stdout and stderr line by line, then how it exited.
+///
+/// stderr gets its own heading when both streams have something in them.
+fn command_transcript(
+    command: &str,
+    stdout: &str,
+    stderr: &str,
+    status: Option<i32>,
+) -> Vec<String> {
+    let mut lines = vec![format!("$ {}", command)];
+    lines.extend(stdout.lines().map(str::to_string));
+    if !stdout.trim().is_empty() && !stderr.trim().is_empty() {
+        lines.push(STDERR_HEADING.to_string());
+    }
+    lines.extend(stderr.lines().map(str::to_string));
+    lines.push(match status {
+        Some(code) => format!("(exit status {})", code),
+        None => "(killed by a signal)".to_string(),
+    });
+    lines
+}
+
+/// Separates stdout from stderr in a command transcript
+pub const STDERR_HEADING: &str = "── stderr ──";
+
+/// Handle output from a control command and format result
+fn handle_control_output(
+    output: std::process::Output,
+    command: String,
+    action: &str,
+    runner: &Runner,
+) -> Result<Option<String>> {
+    if output.status.success() {
+        Ok(Some(format!(
+            "Successfully {}ed {}",
+            action,
+            runner.display_name()
+        )))
+    } else {
+        let context = format!("Failed to {} {}", action, runner.display_name());
+        Err(CommandFailure::new(context, command, &output).into())
+    }
+}
+
+/// Command line `run_script` runs, for reporting
+fn script_command(script_path: &Path, arg: &str, use_sudo: bool) -> String {
+    let sudo = if use_sudo { "sudo " } else { "" };
+    format!("{}{} {}", sudo, script_path.display(), arg)
+}
+
+/// Execute a script command, optionally with sudo
+fn run_script(
+    script_path: &Path,
+    arg: &str,
+    working_dir: &Path,
+    use_sudo: bool,
+) -> Result<std::process::Output> {
+    if use_sudo {
+        Command::new("sudo")
+            .arg(script_path)
+            .arg(arg)
+            .current_dir(working_dir)
+            .output()
+            .map_err(Into::into)
+    } else {
+        Command::new(script_path)
+            .arg(arg)
+            .current_dir(working_dir)
+            .output()
+            .map_err(Into::into)
+    }
+}
+
+/// Attempt to control runner using svc.sh script, returns None if script doesn't exist
+fn try_svc_script_control(runner: &Runner, action: &str, use_sudo: bool) -> Result<Option<String>> {
+    let svc_script = runner.path.join("svc.sh");
+    if !svc_script.exists() {
+        return Ok(None);
+    }
+
+    // For start action, ensure service is installed first
+    if action == "start" && needs_service_installation(&svc_script, &runner.path, use_sudo)? {
+        install_service(&svc_script, &runner.path, runner, use_sudo)?;
+    }
+
+    let output = run_script(&svc_script, action, &runner.path, use_sudo)?;
+    let command = script_command(&svc_script, action, use_sudo);
+    handle_control_output(output, command, action, runner)
+}
+
+/// Check if service needs installation by running status command
+fn needs_service_installation(
+    svc_script: &Path,
+    runner_path: &Path,
+    use_sudo: bool,
+) -> Result<bool> {
+    match run_script(svc_script, "status", runner_path, use_sudo) {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Ok(stdout.contains("not installed") || stderr.contains("not installed"))
+        }
+        Err(_) => Ok(true),
+    }
+}
+
+/// Install service using svc.sh install command
+fn install_service(
+    svc_script: &Path,
+    runner_path: &Path,
+    runner: &Runner,
+    use_sudo: bool,
+) -> Result<()> {
+    let output = run_script(svc_script, "install", runner_path, use_sudo)?;
+
+    if !output.status.success() {
+        let context = format!("Failed to install service for {}", runner.display_name());
+        let command = script_command(svc_script, "install", use_sudo);
+        return Err(CommandFailure::new(context, command, &output).into());
+    }
+
+    Ok(())
+}
+
+/// Environment variable holding a removal token, for when GITHUB_TOKEN can't mint one
+pub const REMOVE_TOKEN_VAR: &str = "RUNNER_REMOVE_TOKEN";
+
+/// Why a runner can't be deregistered right now, if it can't.
+///
+/// `config.sh remove` refuses while the service is installed, and removing a
+/// running runner would strand whatever it is doing.
+pub fn deregister_blocker(runner: &Runner) -> Option<&'static str> {
+    if runner.busy {
+        Some("it is running a job")
+    } else if runner.status == RunnerStatus::Active {
+        Some("it is running; stop it first (x)")
+    } else if runner.service_installed {
+        Some("its service is installed; uninstall it first (u)")
+    } else if !runner.path.join("config.sh").exists() {
+        Some("it has no config.sh")
+    } else {
+        None
+    }
+}
+
+/// Remove the runner's registration from GitHub with `config.sh remove`.
+fn deregister_runner(cmd: &dyn CommandRunner, runner: &Runner) -> Result<String> {
+    if let Some(reason) = deregister_blocker(runner) {
+        return Err(anyhow::anyhow!(
+            "Cannot deregister {}: {}",
+            runner.display_name(),
+            reason
+        ));
+    }
+    let token = removal_token(runner)?;
+
+    let config_script = runner.path.join("config.sh");
+    // config.sh changes to its own directory, so no working directory is needed
+    let output = cmd.run(
+        &config_script.to_string_lossy(),
+        &["remove", "--token", &token],
+    )?;
+    if !output.status.success() {
+        let context = format!("Failed to deregister {}", runner.display_name());
+        // Never echo the token back into the UI or the event log
+        let command = format!("{} remove --token ***", config_script.display());
+        return Err(CommandFailure::new(context, command, &output).into());
+    }
+    Ok(format!(
+        "Deregistered {} from GitHub",
+        runner.display_name()
+    ))
+}
+
+/// A removal token from the environment, or minted with GITHUB_TOKEN.
+fn removal_token(runner: &Runner) -> Result<String> {
+    if let Some(token) = std::env::var(REMOVE_TOKEN_VAR)
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+    {
+        return Ok(token.trim().to_string());
+    }
+    let Some(api_token) = github::token() else {
+        return Err(anyhow::anyhow!(
+            "Set GITHUB_TOKEN or {} to deregister runners",
+            REMOVE_TOKEN_VAR
+        ));
+    };
+    let repo = github_repo(&runner.path).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No repository URL in .runner for {}; set {}",
+            runner.display_name(),
+            REMOVE_TOKEN_VAR
+        )
+    })?;
+    github::runner_remove_token(&api_token, &repo)
+}
+
+/// Remove the runner's service, preferring svc.sh and falling back to the service manager
+fn uninstall_runner_service(cmd: &dyn CommandRunner, runner: &Runner) -> Result<String> {
+    if !is_service_installed(cmd, &runner.service_name) {
+        return Err(anyhow::anyhow!(
+            "Service for {} is not installed",
+            runner.display_name()
+        ));
+    }
+
+    let svc_script = runner.path.join("svc.sh");
+    let use_sudo = !cfg!(target_os = "macos");
+
+    if svc_script.exists() {
+        // svc.sh uninstall stops the service before removing it
+        let output = run_script(&svc_script, "uninstall", &runner.path, use_sudo)?;
+        if !output.status.success() {
+            let context = format!("Failed to uninstall service for {}", runner.display_name());
+            let command = script_command(&svc_script, "uninstall", use_sudo);
+            return Err(CommandFailure::new(context, command, &output).into());
+        }
+    } else if cfg!(target_os = "macos") {
+        remove_launchd_service(cmd, runner)?;
+    } else {
+        remove_systemd_unit(cmd, runner)?;
+    }
+
+    Ok(format!(
+        "Successfully uninstalled service for {}",
+        runner.display_name()
+    ))
+}
+
+/// Where generated systemd units are installed
+const SYSTEMD_UNIT_DIR: &str = "/etc/systemd/system";
+
+/// Write a system unit that keeps the runner's run.sh alive, then enable and start it
+fn install_systemd_unit(cmd: &dyn CommandRunner, runner: &Runner) -> Result<String> {
+    validate_path(&runner.path)?;
+    // ExecStart= and WorkingDirectory= split on whitespace and expand % specifiers
+    if runner
+        .path
+        .to_string_lossy()
+        .chars()
+        .any(|c| c.is_whitespace() || c == '%' || c == '\\')
+    {
+        return Err(anyhow::anyhow!(
+            "Cannot install a systemd unit for {}: its path contains whitespace, % or \\",
+            runner.display_name()
+        ));
+    }
+
+    // The unit runs as the account the runner was resolved to belong to
+    let user = Some(runner.user.as_str())
+        .filter(|user| {
+            !user.is_empty()
+                && user
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot install a systemd unit for {}: '{}' is not a valid user name",
+                runner.display_name(),
+                runner.user
+            )
+        })?;
+
+    // Stage the unit in a directory only we can write to, then copy it into place
+    // as root. A shared path in /tmp could be swapped for another user's file.
+    let staging = private_temp_dir()?;
+    let staged = staging.join(format!("{}.service", runner.service_name));
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&staged)
+        .and_then(|mut file| {
+            io::Write::write_all(&mut file, systemd_unit_contents(runner, user).as_bytes())
+        });
+    if let Err(e) = written {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(anyhow::Error::new(e).context(format!("Failed to write {}", staged.display())));
+    }
+
+    let unit_file = format!("{}/{}.service", SYSTEMD_UNIT_DIR, runner.service_name);
+    let staged_str = staged.to_string_lossy();
+    let steps = [
+        vec!["install", "-m", "644", &staged_str, &unit_file],
+        vec!["systemctl", "daemon-reload"],
+        vec!["systemctl", "enable", "--now", &runner.service_name],
+    ];
+
+    let result: Result<()> = steps.iter().try_for_each(|step| {
+        let output = cmd.run("sudo", step)?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let context = format!(
+            "Failed to install service for {} ({})",
+            runner.display_name(),
+            step.join(" ")
+        );
+        Err(CommandFailure::new(context, format!("sudo {}", step.join(" ")), &output).into())
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    result?;
+
+    Ok(format!(
+        "Created and started {} for {}",
+        unit_file,
+        runner.display_name()
+    ))
+}
+
+/// Create a fresh directory under the temp dir that only this user can enter.
+fn private_temp_dir() -> Result<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let dir =
+        std::env::temp_dir().join(format!("runner-dashboard-{}-{}", std::process::id(), nanos));
+    // Fails rather than reusing whatever is already there, symlinks included
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    Ok(dir)
+}
+
+/// systemd unit running the runner's run.sh as `user`, restarted whenever it exits
+fn systemd_unit_contents(runner: &Runner, user: &str) -> String {
+    let path = runner.path.to_string_lossy();
+    format!(
+        "[Unit]
+Description=GitHub Actions Runner ({name})
+After=network.target
+
+[Service]
+ExecStart={path}/run.sh
+User={user}
+WorkingDirectory={path}
+KillMode=process
+KillSignal=SIGTERM
+TimeoutStopSec=5min
+Restart=always
+
+[Install]
+WantedBy=multi-user.target
+",
+        name = runner.display_name(),
+    )
+}
+
+/// Disable a systemd unit and delete its unit file
+fn remove_systemd_unit(cmd: &dyn CommandRunner, runner: &Runner) -> Result<()> {
+    let output = cmd.run(
+        "systemctl",
+        &[
+            "show",
+            "--property=FragmentPath",
+            "--value",
+            &runner.service_name,
+        ],
+    )?;
+    let unit_file = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let mut steps = vec![vec!["systemctl", "disable", "--now", &runner.service_name]];
+    // An empty FragmentPath means there is no unit file to delete
+    if !unit_file.is_empty() {
+        steps.push(vec!["rm", "-f", &unit_file]);
+    }
+    steps.push(vec!["systemctl", "daemon-reload"]);
+
+    for step in steps {
+        let output = cmd.run("sudo", &step)?;
+        if !output.status.success() {
+            let context = format!(
+                "Failed to uninstall service for {} ({})",
+                runner.display_name(),
+                step.join(" ")
+            );
+            let command = format!("sudo {}", step.join(" "));
+            return Err(CommandFailure::new(context, command, &output).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a LaunchAgent that keeps the runner's run.sh alive, then load it
+fn install_launchd_agent(cmd: &dyn CommandRunner, runner: &Runner) -> Result<String> {
+    validate_path(&runner.path)?;
+
+    let plist_path = launchd_plist_path(&runner.service_name);
+    if let Some(dir) = plist_path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&plist_path, launchd_plist_contents(runner))
+        .with_context(|| format!("Failed to write {}", plist_path.display()))?;
+
+    let output = cmd.run("launchctl", &["load", &plist_path.to_string_lossy()])?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "Created {} but failed to load it: {}",
+            plist_path.display(),
+            stderr.trim()
+        ));
+    }
+
+    Ok(format!(
+        "Created and loaded {} for {}",
+        plist_path.display(),
+        runner.display_name()
+    ))
+}
+
+/// LaunchAgent definition running the runner's run.sh, restarted whenever it exits
+fn launchd_plist_contents(runner: &Runner) -> String {
+    let label = xml_escape(&runner.service_name);
+    let run_script = xml_escape(&runner.path.join("run.sh").to_string_lossy());
+    let working_dir = xml_escape(&runner.path.to_string_lossy());
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{run_script}</string>
+    </array>
+    <key>WorkingDirectory</key>
+    <string>{working_dir}</string>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+</dict>
+</plist>
+"#
+    )
+}
+
+/// Escape text for use inside an XML element
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Unload a LaunchAgent and delete its plist
+fn remove_launchd_service(cmd: &dyn CommandRunner, runner: &Runner) -> Result<()> {
+    let plist_path = launchd_plist_path(&runner.service_name);
+
+    // Unloading fails if the agent isn't loaded, which is fine for removal
+    let _ = cmd.run("launchctl", &["unload", &plist_path.to_string_lossy()]);
+
+    std::fs::remove_file(&plist_path).with_context(|| {
+        format!(
+            "Failed to remove {} for {}",
+            plist_path.display(),
+            runner.display_name()
+        )
+    })
+}
+
+/// Control runner directly using run.sh script and process management
+fn control_runner_direct(cmd: &dyn CommandRunner, runner: &Runner, action: &str) -> Result<String> {
+    validate_path(&runner.path)?;
+
+    let run_script = runner.path.join("run.sh");
+    let run_script_str = run_script
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in path: {:?}", run_script))?;
+
+    match action {
+        "start" => {
+            Command::new("nohup")
+                .arg(run_script_str)
+                .current_dir(&runner.path)
+                .spawn()
+                .with_context(|| format!("Failed to start runner {}", runner.display_name()))?;
+            Ok(format!("Started {}", runner.display_name()))
+        }
+        "stop" => {
+            stop_runner_process(cmd, runner)?;
+            Ok(format!("Stopped {}", runner.display_name()))
+        }
+        "restart" => {
+            restart_runner_process(cmd, runner, run_script_str)?;
+            Ok(format!("Restarted {}", runner.display_name()))
+        }
+        _ => Err(anyhow::anyhow!("Invalid action: {}", action)),
+    }
+}
+
+/// Stop runner process using pkill
+fn stop_runner_process(cmd: &dyn CommandRunner, runner: &Runner) -> Result<()> {
+    // Validate path to prevent command injection via pkill pattern
+    validate_path(&runner.path)?;
+
+    let path_pattern = path_boundary_pattern(&runner.path.to_string_lossy());
+    cmd.run("pkill", &["-f", &format!("Runner.*{}", path_pattern)])
+        .with_context(|| format!("Failed to stop runner {}", runner.display_name()))?;
+    Ok(())
+}
+
+/// Restart runner process by stopping, waiting for termination, and starting again
+fn restart_runner_process(
+    cmd: &dyn CommandRunner,
+    runner: &Runner,
+    run_script_str: &str,
+) -> Result<()> {
+    // Validate path to prevent command injection via pkill pattern
+    validate_path(&runner.path)?;
+
+    let path_pattern = path_boundary_pattern(&runner.path.to_string_lossy());
+    let _ = cmd.run("pkill", &["-f", &format!("Runner.*{}", path_pattern)]);
+
+    // Poll for process termination (up to 5 seconds)
+    let timeout = std::time::Duration::from_secs(5);
+    let start = std::time::Instant::now();
+    while is_runner_process_running(cmd, &runner.path) {
+        if start.elapsed() > timeout {
+            return Err(anyhow::anyhow!(
+                "Timeout waiting for runner {} to stop",
+                runner.display_name()
+            ));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    Command::new("nohup")
+        .arg(run_script_str)
+        .current_dir(&runner.path)
+        .spawn()
+        .with_context(|| format!("Failed to restart runner {}", runner.display_name()))?;
+
+    Ok(())
+}
+
+/// Control runner on macOS using launchctl or direct script
+fn control_runner_macos(cmd: &dyn CommandRunner, runner: &Runner, action: &str) -> Result<String> {
+    // Try launchctl first
+    if let Some(result) = try_launchctl_control(cmd, runner, action)? {
+        return Ok(result);
+    }
+
+    // Fallback to svc.sh script (without sudo on macOS)
+    if let Some(result) = try_svc_script_control(runner, action, false)? {
+        return Ok(result);
+    }
+
+    // Final fallback: direct run.sh control
+    control_runner_direct(cmd, runner, action)
+}
+
+/// Attempt to control runner using launchctl, returns None if service doesn't exist
+fn try_launchctl_control(
+    cmd: &dyn CommandRunner,
+    runner: &Runner,
+    action: &str,
+) -> Result<Option<String>> {
+    let plist_path = launchd_plist_path(&runner.service_name);
+
+    if !plist_path.exists() {
+        return Ok(None);
+    }
+    let expanded_plist = plist_path.to_string_lossy();
+
+    let target = format!("gui/{}/{}", get_uid(), runner.service_name);
+    let args = match action {
+        "restart" => ["kickstart", "-k", target.as_str()].to_vec(),
+        "start" => ["load", expanded_plist.as_ref()].to_vec(),
+        "stop" => ["unload", expanded_plist.as_ref()].to_vec(),
+        _ => return Err(anyhow::anyhow!("Invalid action")),
+    };
+    let output = cmd.run("launchctl", &args)?;
+    let command = format!("launchctl {}", args.join(" "));
+
+    handle_control_output(output, command, action, runner)
+}
+
+/// Path of the LaunchAgent plist for a runner service.
+fn launchd_plist_path(service_name: &str) -> PathBuf {
+    let plist_path = format!("~/Library/LaunchAgents/{}.plist", service_name);
+    PathBuf::from(shellexpand::tilde(&plist_path).as_ref())
+}
+
+/// Get current user ID for launchctl service domain.
+fn get_uid() -> u32 {
+    // SAFETY: getuid() is a read-only syscall with no side effects or failure modes
+    unsafe { libc::getuid() }
+}
+
+/// What a read-only check of the backend that controls a runner found
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeReport {
+    /// Backend start/stop will go through: systemd, launchd, svc.sh or run.sh
+    pub backend: &'static str,
+    /// Whether that backend looks usable
+    pub ok: bool,
+    pub summary: String,
+    /// Each command tried, followed by what it printed
+    pub transcript: Vec<String>,
+}
+
+/// Ask the runner's backend about it without changing anything, in the same order
+/// `control_runner` tries them.
+pub fn probe_runner(cmd: &dyn CommandRunner, runner: &Runner) -> ProbeReport {
+    if cfg!(target_os = "macos") {
+        probe_runner_macos(cmd, runner)
+    } else {
+        probe_runner_linux(cmd, runner)
+    }
+}
+
+fn probe_runner_linux(cmd: &dyn CommandRunner, runner: &Runner) -> ProbeReport {
+    let mut transcript = Vec::new();
+    let service = &runner.service_name;
+
+    // 3 is "not running"; 4 is "no such unit"
+    let status = run_probe(
+        cmd,
+        &mut transcript,
+        "systemctl",
+        &["status", "--no-pager", service],
+    );
+    let (ok, summary) = match status.and_then(|output| output.status.code()) {
+        Some(0) => (true, format!("systemd unit {} is active", service)),
+        Some(3) => (
+            true,
+            format!("systemd unit {} is installed but not running", service),
+        ),
+        Some(4) | None => {
+            return probe_svc_script(
+                cmd,
+                runner,
+                true,
+                format!("No systemd unit named {}", service),
+                transcript,
+            )
+        }
+        Some(_) => (false, format!("systemctl could not report on {}", service)),
+    };
+    ProbeReport {
+        backend: "systemd",
+        ok,
+        summary,
+        transcript,
+    }
+}
+
+fn probe_runner_macos(cmd: &dyn CommandRunner, runner: &Runner) -> ProbeReport {
+    let mut transcript = Vec::new();
+    let plist_path = launchd_plist_path(&runner.service_name);
+    if !plist_path.exists() {
+        return probe_svc_script(
+            cmd,
+            runner,
+            false,
+            format!("No LaunchAgent at {}", plist_path.display()),
+            transcript,
+        );
+    }
+
+    let target = format!("gui/{}/{}", get_uid(), runner.service_name);
+    let loaded = run_probe(cmd, &mut transcript, "launchctl", &["print", &target])
+        .is_some_and(|output| output.status.success());
+    let summary = if loaded {
+        format!("LaunchAgent {} is loaded", runner.service_name)
+    } else {
+        format!(
+            "LaunchAgent {} is not loaded; start will load it",
+            runner.service_name
+        )
+    };
+    ProbeReport {
+        backend: "launchd",
+        ok: true,
+        summary,
+        transcript,
+    }
+}
+
+/// Probe the fallbacks after the service manager: svc.sh, then run.sh.
+fn probe_svc_script(
+    cmd: &dyn CommandRunner,
+    runner: &Runner,
+    use_sudo: bool,
+    missing_service: String,
+    mut transcript: Vec<String>,
+) -> ProbeReport {
+    let svc_script = runner.path.join("svc.sh");
+    if !svc_script.exists() {
+        let (backend, ok, fallback) = if runner.path.join("run.sh").exists() {
+            ("run.sh", true, "start/stop will run run.sh directly")
+        } else {
+            (
+                "none",
+                false,
+                "there is no svc.sh or run.sh to fall back on",
+            )
+        };
+        return ProbeReport {
+            backend,
+            ok,
+            summary: format!("{}; {}", missing_service, fallback),
+            transcript,
+        };
+    }
+
+    let script = svc_script.to_string_lossy();
+    // -n: fail rather than prompt for a password the TUI can't show
+    let output = if use_sudo {
+        run_probe(cmd, &mut transcript, "sudo", &["-n", &script, "status"])
+    } else {
+        run_probe(cmd, &mut transcript, &script, &["status"])
+    };
+    let (ok, result) = match output {
+        Some(output) if output.status.success() => (true, "svc.sh answered".to_string()),
+        Some(output) if String::from_utf8_lossy(&output.stderr).contains("password") => (
+            false,
+            "svc.sh needs sudo, which wants a password (run `sudo -v` first)".to_string(),
+        ),
+        _ => (false, "svc.sh status failed".to_string()),
+    };
+    ProbeReport {
+        backend: "svc.sh",
+        ok,
+        summary: format!("{}; {}", missing_service, result),
+        transcript,
+    }
+}
+
+/// Run one probe command, recording it and everything it printed in `transcript`.
+fn run_probe(
+    cmd: &dyn CommandRunner,
+    transcript: &mut Vec<String>,
+    program: &str,
+    args: &[&str],
+) -> Option<Output> {
+    let command = format!("{} {}", program, args.join(" "));
+    match cmd.run(program, args) {
+        Ok(output) => {
+            transcript.extend(command_transcript(
+                &command,
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+                output.status.code(),
+            ));
+            Some(output)
+        }
+        Err(e) => {
+            transcript.push(format!("$ {}", command));
+            transcript.push(format!("(could not run: {})", e));
+            None
+        }
+    }
+}
+
+/// `journalctl -o` output format for runner logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JournalFormat {
+    /// `May 01 12:34:56`, local time
+    Short,
+    /// `2024-05-01T12:34:56+0000`
+    #[default]
+    ShortIso,
+    /// `May 01 12:34:56.123456`, local time
+    ShortPrecise,
+    /// Messages only, no timestamp or host
+    Cat,
+}
+
+impl JournalFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JournalFormat::Short => "short",
+            JournalFormat::ShortIso => "short-iso",
+            JournalFormat::ShortPrecise => "short-precise",
+            JournalFormat::Cat => "cat",
+        }
+    }
+}
+
+/// Minimum severity of log lines to show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogPriority {
+    All,
+    Warnings,
+    Errors,
+}
+
+impl LogPriority {
+    pub fn next(self) -> Self {
+        match self {
+            LogPriority::All => LogPriority::Warnings,
+            LogPriority::Warnings => LogPriority::Errors,
+            LogPriority::Errors => LogPriority::All,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogPriority::All => "all levels",
+            LogPriority::Warnings => "warnings and errors",
+            LogPriority::Errors => "errors only",
+        }
+    }
+
+    /// Priority range for `journalctl -p`
+    fn journalctl_range(self) -> Option<&'static str> {
+        match self {
+            LogPriority::All => None,
+            LogPriority::Warnings => Some("warning..emerg"),
+            LogPriority::Errors => Some("err..emerg"),
+        }
+    }
+
+    /// Client-side check for logs without journald priorities, e.g. `[... ERR Runner] ...`
+    fn matches_line(self, line: &str) -> bool {
+        let lower = line.to_lowercase();
+        let error = lower.contains(" err ") || lower.contains("error");
+        let warning = lower.contains("warn");
+        match self {
+            LogPriority::All => true,
+            LogPriority::Warnings => error || warning,
+            LogPriority::Errors => error,
+        }
+    }
+}
+
+/// Get recent logs for a runner (cross-platform)
+///
+/// `since` is a journalctl time spec (e.g. "1 hour ago") limiting logs to that
+/// window, still capped at `lines`. The _diag files read on macOS (and on Linux
+/// when the journal has nothing), and log files configured as a runner's log
+/// source, ignore it.
+pub fn get_runner_logs(
+    runner: &Runner,
+    lines: usize,
+    since: Option<&str>,
+    priority: LogPriority,
+    format: JournalFormat,
+) -> Result<Vec<String>> {
+    if let Some(LogSource::File(path)) = &runner.log_source {
+        return tail_log_file(path, lines, priority)
+            .with_context(|| format!("Failed to read log file {}", path.display()));
+    }
+    if cfg!(target_os = "macos") && runner.log_source.is_none() {
+        get_runner_logs_diag(runner, lines, priority)
+    } else {
+        get_runner_logs_linux(runner, lines, since, priority, format)
+    }
+}
+
+/// Seconds of journal fetched on each side of a line when expanding its context
+pub const LOG_CONTEXT_SECS: i64 = 300;
+/// Lines of a `_diag` file kept on each side of a line when expanding its context
+pub const LOG_CONTEXT_LINES: usize = 500;
+/// Most lines fetched when expanding context, however busy the journal was
+const LOG_CONTEXT_MAX_LINES: usize = 5000;
+
+/// Logs surrounding `line`, one of the lines `get_runner_logs` returned.
+///
+/// On Linux this is the journal from `LOG_CONTEXT_SECS` before to after the line's
+/// timestamp, read the way `format` writes it; on macOS, `LOG_CONTEXT_LINES`
+/// either side of it in its `_diag` file.
+pub fn get_runner_log_context(
+    runner: &Runner,
+    line: &str,
+    priority: LogPriority,
+    format: JournalFormat,
+) -> Result<Vec<String>> {
+    if let Some(LogSource::File(path)) = &runner.log_source {
+        return match lines_around(path, line, priority)? {
+            Some(context) => Ok(context),
+            None => anyhow::bail!("The matched line is no longer in {}", path.display()),
+        };
+    }
+    if cfg!(target_os = "macos") && runner.log_source.is_none() {
+        return get_runner_log_context_diag(runner, line, priority);
+    }
+
+    let Some((since, until)) = journal_window(line, format, SystemTime::now()) else {
+        // Not a journal line: the logs may have come from the _diag fallback
+        if runner.path.join("_diag").is_dir() {
+            if let Ok(context) = get_runner_log_context_diag(runner, line, priority) {
+                return Ok(context);
+            }
+        }
+        if format == JournalFormat::Cat {
+            anyhow::bail!("journal_format = \"cat\" leaves out the timestamps context is found by");
+        }
+        anyhow::bail!("No timestamp on the matched line");
+    };
+    let lines = LOG_CONTEXT_MAX_LINES.to_string();
+    let [flag, name] = journal_match(runner);
+    let mut args = vec![
+        flag,
+        name,
+        "--since",
+        &since,
+        "--until",
+        &until,
+        "-n",
+        &lines,
+        "--no-pager",
+        "-o",
+        format.as_str(),
+    ];
+    if let Some(range) = priority.journalctl_range() {
+        args.extend(["-p", range]);
+    }
+    let output = Command::new("journalctl").args(&args).output()?;
+
+    let logs = String::from_utf8_lossy(&output.stdout);
+    Ok(logs.lines().map(|s| s.to_string()).collect())
+}
+
+/// `--since` and `--until` for the journal `LOG_CONTEXT_SECS` either side of a
+/// line written in `format`, or None if it has no timestamp to go by.
+///
+/// `short` and `short-precise` stamps are local time without a year, so they are
+/// handed back to journalctl as local times, in the year that puts them closest
+/// before `now`.
+fn journal_window(line: &str, format: JournalFormat, now: SystemTime) -> Option<(String, String)> {
+    match format {
+        JournalFormat::ShortIso => {
+            let at = journal_timestamp(line)?;
+            Some((
+                format!("@{}", at - LOG_CONTEXT_SECS),
+                format!("@{}", at + LOG_CONTEXT_SECS),
+            ))
+        }
+        JournalFormat::Short | JournalFormat::ShortPrecise => {
+            let at = short_timestamp(line, now)?;
+            Some((
+                format_naive_time(at - LOG_CONTEXT_SECS),
+                format_naive_time(at + LOG_CONTEXT_SECS),
+            ))
+        }
+        JournalFormat::Cat => None,
+    }
+}
+
+/// Seconds since the epoch of a `May 01 12:34:56[.123456] host ...` line's local
+/// time, read as if it were UTC. The year is `now`'s, or the one before if that
+/// would put the line more than a day in the future.
+fn short_timestamp(line: &str, now: SystemTime) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let mut fields = line.split_whitespace();
+    let month_name = fields.next()?;
+    let month = MONTHS.iter().position(|m| *m == month_name)? as i64 + 1;
+    let day: i64 = fields.next()?.parse().ok()?;
+    let time = fields.next()?;
+    let time = time.split_once('.').map_or(time, |(whole, _)| whole);
+    let mut clock = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+    if !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let now = now.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    let (year, _, _) = civil_from_days(now.div_euclid(86400));
+    let at = |year| days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+    // Local time can run up to a day ahead of UTC
+    Some(if at(year) > now + 86400 {
+        at(year - 1)
+    } else {
+        at(year)
+    })
+}
+
+/// `2024-05-01 12:34:56` for seconds since the epoch, without any time zone
+fn format_naive_time(secs: i64) -> String {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+    let time = secs.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Days since the epoch for a proleptic Gregorian date (Howard Hinnant's days_from_civil)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil` (Howard Hinnant's civil_from_days): year, month, day
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Unix time of a `journalctl -o short-iso` line, e.g. `2024-05-01T12:34:56+0000 host ...`
+fn journal_timestamp(line: &str) -> Option<i64> {
+    let stamp = line.split_whitespace().next()?;
+    let (date, time) = stamp.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    // The offset is `+0000` in older systemd and `+00:00` in newer
+    let offset_at = time.find(['+', '-']).unwrap_or(time.len());
+    let (clock, offset) = time.split_at(offset_at);
+    let mut clock = clock.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+    let offset_secs = match offset.replace(':', "").as_str() {
+        "" => 0,
+        offset if offset.len() == 5 => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let hours: i64 = offset[1..3].parse().ok()?;
+            let minutes: i64 = offset[3..5].parse().ok()?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+        _ => return None,
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    Some(days * 86400 + hour * 3600 + minute * 60 + second - offset_secs)
+}
+
+/// Lines around `line` in whichever recent `_diag` log contains it
+fn get_runner_log_context_diag(
+    runner: &Runner,
+    line: &str,
+    priority: LogPriority,
+) -> Result<Vec<String>> {
+    let diag_dir = runner.path.join("_diag");
+    for prefix in ["Worker_", "Runner_"] {
+        let Some(path) = latest_log_file(&diag_dir, prefix)? else {
+            continue;
+        };
+        if let Some(context) = lines_around(&path, line, priority)? {
+            return Ok(context);
+        }
+    }
+    anyhow::bail!("The matched line is no longer in the runner's _diag logs")
+}
+
+/// `LOG_CONTEXT_LINES` either side of the last occurrence of `line` in the log
+/// file at `path`, or None if it isn't there any more.
+fn lines_around(path: &Path, line: &str, priority: LogPriority) -> Result<Option<Vec<String>>> {
+    let content = std::fs::read_to_string(path)?;
+    let all_lines: Vec<&str> = content
+        .lines()
+        .filter(|l| priority.matches_line(l))
+        .collect();
+    Ok(all_lines.iter().rposition(|l| *l == line).map(|at| {
+        let start = at.saturating_sub(LOG_CONTEXT_LINES);
+        let end = (at + LOG_CONTEXT_LINES + 1).min(all_lines.len());
+        all_lines[start..end]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }))
+}
+
+/// journalctl arguments selecting a runner's log entries: its unit, unless a
+/// different unit or a syslog identifier is configured for it.
+fn journal_match(runner: &Runner) -> [&str; 2] {
+    match &runner.log_source {
+        Some(LogSource::Unit(unit)) => ["-u", unit],
+        Some(LogSource::Syslog(identifier)) => ["-t", identifier],
+        Some(LogSource::File(_)) | None => ["-u", &runner.service_name],
+    }
+}
+
+/// Get logs on Linux using journalctl, falling back to the runner's _diag files
+/// when the journal has nothing for it: runners launched by a script or another
+/// supervisor don't log to the journal, and some hosts have no journalctl at all.
+fn get_runner_logs_linux(
+    runner: &Runner,
+    lines: usize,
+    since: Option<&str>,
+    priority: LogPriority,
+    format: JournalFormat,
+) -> Result<Vec<String>> {
+    let lines_arg = lines.to_string();
+    let [flag, name] = journal_match(runner);
+    // -q drops the "-- No entries --" line, so an empty journal reads as empty
+    let mut args = vec![
+        "-q",
+        flag,
+        name,
+        "-n",
+        &lines_arg,
+        "--no-pager",
+        "-o",
+        format.as_str(),
+    ];
+    if let Some(since) = since {
+        args.extend(["--since", since]);
+    }
+    if let Some(range) = priority.journalctl_range() {
+        args.extend(["-p", range]);
+    }
+    let has_diag = runner.path.join("_diag").is_dir();
+    let logs: Vec<String> = match Command::new("journalctl").args(&args).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|s| s.to_string())
+            .collect(),
+        Err(_) if has_diag => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if logs.is_empty() && has_diag {
+        return get_runner_logs_diag(runner, lines, priority);
+    }
+    Ok(logs)
+}
+
+/// Get logs from the runner's _diag directory, on macOS or as the Linux fallback
+fn get_runner_logs_diag(
+    runner: &Runner,
+    lines: usize,
+    priority: LogPriority,
+) -> Result<Vec<String>> {
+    let diag_dir = runner.path.join("_diag");
+
+    if !diag_dir.exists() {
+        return Ok(vec!["No logs found (no _diag directory)".to_string()]);
+    }
+
+    // Try to find the most recent Worker log, then Runner log
+    for prefix in ["Worker_", "Runner_"] {
+        if let Some(content) = find_latest_log_file(&diag_dir, prefix, lines, priority)? {
+            return Ok(content);
+        }
+    }
+
+    Ok(vec!["No log files found in _diag".to_string()])
+}
+
+/// Find and read the most recent log file with the given prefix
+fn find_latest_log_file(
+    diag_dir: &Path,
+    prefix: &str,
+    lines: usize,
+    priority: LogPriority,
+) -> Result<Option<Vec<String>>> {
+    match latest_log_file(diag_dir, prefix)? {
+        Some(latest_log) => Ok(Some(tail_log_file(&latest_log, lines, priority)?)),
+        None => Ok(None),
+    }
+}
+
+/// The last `lines` lines of the log file at `path` that match `priority`
+fn tail_log_file(path: &Path, lines: usize, priority: LogPriority) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let all_lines: Vec<String> = content
+        .lines()
+        .filter(|line| priority.matches_line(line))
+        .map(|s| s.to_string())
+        .collect();
+    let start = all_lines.len().saturating_sub(lines);
+    Ok(all_lines[start..].to_vec())
+}
+
+/// Most recently modified file in `diag_dir` whose name starts with `prefix`
+fn latest_log_file(diag_dir: &Path, prefix: &str) -> Result<Option<PathBuf>> {
+    let mut log_files: Vec<_> = std::fs::read_dir(diag_dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
+        .collect();
+
+    log_files.sort_by_key(|e| std::cmp::Reverse(e.metadata().ok().and_then(|m| m.modified().ok())));
+
+    Ok(log_files.first().map(|e| e.path()))
+}
+
+/// An active runner installed for the `ci` account, as test modules build them.
+#[cfg(test)]
+pub(crate) fn test_runner(repo: &str, number: u32) -> Runner {
+    Runner {
+        name: format!("runner-{}", number),
+        number,
+        repo: repo.to_string(),
+        status: RunnerStatus::Active,
+        service_name: format!("actions.runner.ci.{}-runner-{}", repo, number),
+        path: PathBuf::from(format!("/nonexistent/action-runners/{}/{}", repo, number)),
+        root: PathBuf::from("/nonexistent/action-runners"),
+        service_installed: true,
+        version: None,
+        busy: false,
+        registration: None,
+        work: None,
+        service_state: None,
+        labels: None,
+        log_source: None,
+        user: "ci".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+    use std::sync::Mutex;
+
+    /// Returns canned output keyed by the full command line and records every call.
+    #[derive(Default)]
+    struct MockCommandRunner {
+        outputs: HashMap<String, (i32, String, String)>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl MockCommandRunner {
+        fn with(mut self, command: &str, code: i32, stdout: &str, stderr: &str) -> Self {
+            self.outputs.insert(
+                command.to_string(),
+                (code, stdout.to_string(), stderr.to_string()),
+            );
+            self
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl CommandRunner for MockCommandRunner {
+        fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+            let command = std::iter::once(program)
+                .chain(args.iter().copied())
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.calls.lock().unwrap().push(command.clone());
+
+            let (code, stdout, stderr) = self.outputs.get(&command).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("unexpected: {}", command))
+            })?;
+            Ok(Output {
+                status: ExitStatus::from_raw(code << 8),
+                stdout: stdout.clone().into_bytes(),
+                stderr: stderr.clone().into_bytes(),
+            })
+        }
+    }
+
+    #[test]
+    fn batched_systemctl_query_only_includes_known_units() {
+        let names = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let units = UnitCache {
+            units: ["a", "c"].iter().map(|s| s.to_string()).collect(),
+        };
+        let cmd = MockCommandRunner::default().with(
+            "systemctl show --property=ActiveState,SubState,NRestarts a c",
+            0,
+            "ActiveState=activating\nSubState=auto-restart\nNRestarts=14\n\nActiveState=failed\nSubState=failed\n",
+            "",
+        );
+
+        let statuses = get_all_systemctl_services(&cmd, &names, &units);
+
+        assert_eq!(statuses["a"].describe(), "activating (auto-restart)");
+        assert!(statuses["a"].is_transitional());
+        assert_eq!(statuses["a"].restarts, Some(14));
+        assert_eq!(statuses["c"].restarts, None);
+        assert_eq!(statuses["c"].active, "failed");
+        assert!(!statuses["c"].is_transitional());
+        assert!(!statuses.contains_key("b"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn runners_sharing_a_service_resolve_status_independently() {
+        let mut runners = vec![test_runner("acme", 1), test_runner("acme", 1)];
+        runners[1].path = PathBuf::from("/nonexistent/action-runners/acme/01");
+        let service = runners[0].service_name.clone();
+        let units = UnitCache {
+            units: [service.clone()].into_iter().collect(),
+        };
+        let cmd = MockCommandRunner::default()
+            .with(
+                "pgrep -af Runner",
+                0,
+                "100 /nonexistent/action-runners/acme/1/bin/Runner.Listener run\n",
+                "",
+            )
+            .with(
+                &format!(
+                    "systemctl show --property=ActiveState,SubState,NRestarts {}",
+                    service
+                ),
+                0,
+                "ActiveState=active\nSubState=running\n",
+                "",
+            );
+
+        let timings = refresh_runners(&cmd, &mut runners, &units);
+
+        assert_eq!(runners[0].status, RunnerStatus::Active);
+        assert_eq!(runners[1].status, RunnerStatus::NotFound);
+        assert!(runners.iter().all(|r| r.service_state.is_none()));
+        assert_eq!(timings.runners, 2);
+        assert!(timings.total >= timings.processes + timings.services);
+        assert!(runners.iter().all(|r| r.service_installed));
+        let queries = cmd
+            .calls()
+            .into_iter()
+            .filter(|c| c.starts_with("systemctl show"))
+            .count();
+        assert_eq!(queries, 1);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn service_cache_is_reused_until_processes_change() {
+        let mut runners = vec![test_runner("acme", 1)];
+        let service = runners[0].service_name.clone();
+        let units = UnitCache {
+            units: [service.clone()].into_iter().collect(),
+        };
+        let show = format!(
+            "systemctl show --property=ActiveState,SubState,NRestarts {}",
+            service
+        );
+        let running = MockCommandRunner::default()
+            .with(
+                "pgrep -af Runner",
+                0,
+                "100 /nonexistent/action-runners/acme/1/bin/Runner.Listener run\n",
+                "",
+            )
+            .with(&show, 0, "ActiveState=active\nSubState=running\n", "");
+        let mut cache = ServiceCache::new(Duration::from_secs(60));
+
+        assert!(!refresh_runners_cached(&running, &mut runners, &units, &mut cache).cached);
+        assert!(refresh_runners_cached(&running, &mut runners, &units, &mut cache).cached);
+        let queries = running
+            .calls()
+            .into_iter()
+            .filter(|c| c.starts_with("systemctl show"))
+            .count();
+        assert_eq!(queries, 1);
+        assert_eq!(runners[0].status, RunnerStatus::Active);
+        assert_eq!(
+            runners[0]
+                .service_state
+                .as_ref()
+                .map(ServiceState::describe),
+            Some("active (running)".to_string())
+        );
+
+        // The listener died: the cached "active" no longer applies
+        let died = MockCommandRunner::default()
+            .with("pgrep -af Runner", 1, "", "")
+            .with(&show, 0, "ActiveState=failed\nSubState=failed\n", "");
+        let timings = refresh_runners_cached(&died, &mut runners, &units, &mut cache);
+        assert!(!timings.cached);
+        assert_eq!(runners[0].status, RunnerStatus::Failed);
+    }
+
+    #[test]
+    fn runner_user_is_inferred_only_when_units_agree() {
+        let units =
+            |names: &[&str]| -> HashSet<String> { names.iter().map(|s| s.to_string()).collect() };
+
+        let single = units(&[
+            "actions.runner.builder.acme-runner-1",
+            "actions.runner.builder.acme-runner-2",
+        ]);
+        let template = ServiceTemplate::default();
+        assert_eq!(
+            infer_runner_user(&template, &single).as_deref(),
+            Some("builder")
+        );
+
+        let mixed = units(&[
+            "actions.runner.builder.acme-runner-1",
+            "actions.runner.ci.acme-runner-2",
+        ]);
+        assert_eq!(infer_runner_user(&template, &mixed), None);
+        assert_eq!(infer_runner_user(&template, &units(&[])), None);
+
+        let mut options = DiscoveryOptions::default();
+        assert_eq!(
+            resolve_runner_user(&options, &single),
+            ("builder".to_string(), UserSource::Installed)
+        );
+        assert_eq!(
+            resolve_runner_user(&options, &mixed).1,
+            UserSource::Environment
+        );
+        options.runner_user = Some("ci".to_string());
+        assert_eq!(
+            resolve_runner_user(&options, &single),
+            ("ci".to_string(), UserSource::Configured)
+        );
+    }
+
+    #[test]
+    fn service_name_comes_from_the_installed_unit() {
+        let installed: HashSet<String> = [
+            "actions.runner.builder.acme-runner-1",
+            "actions.runner.builder.widgets-acme-runner-2",
+            "actions.runner.ci.acme-runner-3",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let template = ServiceTemplate::default();
+        let resolve = |num| resolve_service_name(&installed, &template, "root", "acme", num, "");
+
+        assert_eq!(resolve(1), "actions.runner.builder.acme-runner-1");
+        assert_eq!(resolve(3), "actions.runner.ci.acme-runner-3");
+        // Not installed: fall back to the name setup would use
+        assert_eq!(resolve(2), "actions.runner.root.acme-runner-2");
+    }
+
+    #[test]
+    fn launchctl_status_uses_pid_column() {
+        let cmd = MockCommandRunner::default()
+            .with("launchctl list running", 0, "4242\t0\trunning\n", "")
+            .with("launchctl list stopped", 0, "-\t0\tstopped\n", "");
+
+        assert_eq!(
+            check_launchctl_exact_service(&cmd, "running"),
+            Some(RunnerStatus::Active)
+        );
+        assert_eq!(
+            check_launchctl_exact_service(&cmd, "stopped"),
+            Some(RunnerStatus::Inactive)
+        );
+        assert_eq!(check_launchctl_exact_service(&cmd, "missing"), None);
+    }
+
+    #[test]
+    fn process_check_does_not_confuse_runner_1_with_runner_10() {
+        let runner_1 = test_runner("acme", 1).path;
+        let runner_10 = test_runner("acme", 10).path;
+        let cmd = MockCommandRunner::default().with(
+            "pgrep -af Runner",
+            0,
+            "1234 /nonexistent/action-runners/acme/10/bin/Runner.Listener run\n",
+            "",
+        );
+
+        let running = batch_check_running_processes(&cmd, &[runner_1.clone(), runner_10.clone()]);
+
+        assert!(!running[&runner_1].running);
+        assert!(running[&runner_10].running);
+    }
+
+    #[test]
+    fn process_check_flags_runners_with_a_worker_as_busy() {
+        let idle = test_runner("acme", 1).path;
+        let busy = test_runner("acme", 2).path;
+        let cmd = MockCommandRunner::default().with(
+            "pgrep -af Runner",
+            0,
+            "100 /nonexistent/action-runners/acme/1/bin/Runner.Listener run\n\
+             200 /nonexistent/action-runners/acme/2/bin/Runner.Listener run\n\
+             201 /nonexistent/action-runners/acme/2/bin/Runner.Worker spawnclient 1 2\n",
+            "",
+        );
+
+        let processes = batch_check_running_processes(&cmd, &[idle.clone(), busy.clone()]);
+
+        assert_eq!(
+            processes[&idle],
+            RunnerProcesses {
+                running: true,
+                worker: false
+            }
+        );
+        assert_eq!(
+            processes[&busy],
+            RunnerProcesses {
+                running: true,
+                worker: true
+            }
+        );
+    }
+
+    #[test]
+    fn line_references_path_requires_component_boundary() {
+        let path = "/runners/acme/1";
+
+        assert!(line_references_path("1 /runners/acme/1/run.sh", path));
+        assert!(line_references_path("1 dotnet /runners/acme/1", path));
+        assert!(line_references_path("1 cd /runners/acme/1 && x", path));
+        assert!(!line_references_path("1 /runners/acme/10/run.sh", path));
+        assert!(!line_references_path("1 /runners/acme/1-old/run.sh", path));
     }
-}
 
-/// Control runner on Linux using systemctl with svc.sh/run.sh fallback
-fn control_runner_linux(runner: &Runner, action: &str) -> Result<String> {
-    // Try systemctl first
-    if let Some(result) = try_systemctl_control(runner, action)? {
-        return Ok(result);
+    #[test]
+    fn systemctl_control_reports_success() {
+        let runner = test_runner("acme", 1);
+        let cmd = MockCommandRunner::default()
+            .with("systemctl cat actions.runner.ci.acme-runner-1", 0, "", "")
+            .with(
+                "sudo systemctl restart actions.runner.ci.acme-runner-1",
+                0,
+                "",
+                "",
+            );
+
+        let message = try_systemctl_control(&cmd, &runner, "restart").unwrap();
+
+        assert_eq!(
+            message.as_deref(),
+            Some("Successfully restarted acme-runner-1")
+        );
     }
 
-    // Fallback to svc.sh script
-    if let Some(result) = try_svc_script_control(runner, action, true)? {
-        return Ok(result);
+    #[test]
+    fn systemctl_control_maps_failure_to_error_with_stderr() {
+        let runner = test_runner("acme", 1);
+        let cmd = MockCommandRunner::default()
+            .with("systemctl cat actions.runner.ci.acme-runner-1", 0, "", "")
+            .with(
+                "sudo systemctl stop actions.runner.ci.acme-runner-1",
+                1,
+                "",
+                "Access denied",
+            );
+
+        let err = try_systemctl_control(&cmd, &runner, "stop").unwrap_err();
+
+        assert!(err.to_string().contains("Failed to stop"));
+        assert!(err.to_string().contains("Access denied"));
     }
 
-    // Final fallback: direct run.sh control
-    control_runner_direct(runner, action)
-}
+    #[test]
+    fn systemctl_control_defers_when_unit_missing() {
+        let runner = test_runner("acme", 1);
+        let cmd = MockCommandRunner::default().with(
+            "systemctl cat actions.runner.ci.acme-runner-1",
+            1,
+            "",
+            "",
+        );
 
-/// Attempt to control runner using systemctl, returns None if service doesn't exist
-fn try_systemctl_control(runner: &Runner, action: &str) -> Result<Option<String>> {
-    if !systemctl_unit_exists(&runner.service_name) {
-        return Ok(None);
+        assert!(try_systemctl_control(&cmd, &runner, "start")
+            .unwrap()
+            .is_none());
     }
 
-    let output = Command::new("sudo")
-        .args(["systemctl", action, &runner.service_name])
-        .output()?;
+    #[test]
+    fn control_runner_rejects_invalid_input_without_running_commands() {
+        let cmd = MockCommandRunner::default();
 
-    handle_control_output(output, action, runner)
-}
+        assert!(control_runner(&cmd, &test_runner("acme", 1), "reboot").is_err());
 
-/// Handle output from a control command and format result
-fn handle_control_output(
-    output: std::process::Output,
-    action: &str,
-    runner: &Runner,
-) -> Result<Option<String>> {
-    if output.status.success() {
-        Ok(Some(format!(
-            "Successfully {}ed {}",
-            action,
-            runner.display_name()
-        )))
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(anyhow::anyhow!(
-            "Failed to {} {}: {}",
-            action,
-            runner.display_name(),
-            stderr
-        ))
+        let mut runner = test_runner("acme", 1);
+        runner.service_name = "actions.runner.x; rm -rf /".to_string();
+        assert!(control_runner(&cmd, &runner, "start").is_err());
+
+        runner.service_name = "sshd".to_string();
+        assert!(control_runner(&cmd, &runner, "start").is_err());
+
+        assert!(cmd.calls().is_empty());
     }
-}
 
-/// Execute a script command, optionally with sudo
-fn run_script(
-    script_path: &Path,
-    arg: &str,
-    working_dir: &Path,
-    use_sudo: bool,
-) -> Result<std::process::Output> {
-    if use_sudo {
-        Command::new("sudo")
-            .arg(script_path)
-            .arg(arg)
-            .current_dir(working_dir)
-            .output()
-            .map_err(Into::into)
-    } else {
-        Command::new(script_path)
-            .arg(arg)
-            .current_dir(working_dir)
-            .output()
-            .map_err(Into::into)
+    #[test]
+    fn parse_show_output_maps_states_by_position() {
+        let names: Vec<String> = [
+            "actions.runner.ci.acme-runner-1",
+            "actions.runner.ci.acme-runner-2",
+            "actions.runner.ci.acme-runner-10",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let stdout = "ActiveState=active\nSubState=running\n\n\
+                      SubState=failed\nActiveState=failed\n\n\
+                      ActiveState=inactive\nSubState=dead\n";
+
+        let statuses = parse_show_output(&names, stdout);
+
+        assert_eq!(statuses.len(), 3);
+        assert_eq!(statuses[&names[0]].describe(), "active (running)");
+        assert_eq!(statuses[&names[1]].describe(), "failed (failed)");
+        assert_eq!(statuses[&names[2]].describe(), "inactive (dead)");
     }
-}
 
-/// Attempt to control runner using svc.sh script, returns None if script doesn't exist
-fn try_svc_script_control(runner: &Runner, action: &str, use_sudo: bool) -> Result<Option<String>> {
-    let svc_script = runner.path.join("svc.sh");
-    if !svc_script.exists() {
-        return Ok(None);
+    #[test]
+    fn parse_unit_files_output_strips_service_suffix() {
+        let stdout = "actions.runner.ci.acme-runner-1.service  enabled  enabled\n\
+                      actions.runner.ci.acme-runner-2.service  disabled enabled\n";
+
+        let units = parse_unit_files_output(stdout);
+
+        assert_eq!(units.len(), 2);
+        assert!(units.contains("actions.runner.ci.acme-runner-1"));
+        assert!(units.contains("actions.runner.ci.acme-runner-2"));
     }
 
-    // For start action, ensure service is installed first
-    if action == "start" && needs_service_installation(&svc_script, &runner.path, use_sudo)? {
-        install_service(&svc_script, &runner.path, runner, use_sudo)?;
+    #[test]
+    fn parse_show_output_ignores_missing_blocks() {
+        let names = vec!["a.service".to_string(), "b.service".to_string()];
+
+        let statuses = parse_show_output(&names, "ActiveState=activating\nSubState=start\n");
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses["a.service"].active, "activating");
     }
 
-    let output = run_script(&svc_script, action, &runner.path, use_sudo)?;
-    handle_control_output(output, action, runner)
-}
+    #[test]
+    fn log_sources_pick_the_journal_match_or_a_file() {
+        assert!(LogSource::parse("syslog:").is_err());
+        assert!(LogSource::parse("acme-ci").is_err());
+        assert!(LogSource::parse("journal:acme-ci").is_err());
+
+        let mut runner = test_runner("acme", 1);
+        assert_eq!(journal_match(&runner), ["-u", runner.service_name.as_str()]);
+        runner.log_source = Some(LogSource::parse("syslog:acme-ci").unwrap());
+        assert_eq!(journal_match(&runner), ["-t", "acme-ci"]);
+        runner.log_source = Some(LogSource::parse("unit:ci-wrapper.service").unwrap());
+        assert_eq!(journal_match(&runner), ["-u", "ci-wrapper.service"]);
+
+        // Keyed by service name or repo/name
+        let options = DiscoveryOptions {
+            log_sources: HashMap::from([(
+                format!("{}/{}", runner.repo, runner.name),
+                LogSource::Syslog("acme-ci".to_string()),
+            )]),
+            ..DiscoveryOptions::default()
+        };
+        assert_eq!(
+            options.log_source(&runner),
+            Some(LogSource::Syslog("acme-ci".to_string()))
+        );
 
-/// Check if service needs installation by running status command
-fn needs_service_installation(
-    svc_script: &Path,
-    runner_path: &Path,
-    use_sudo: bool,
-) -> Result<bool> {
-    match run_script(svc_script, "status", runner_path, use_sudo) {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            Ok(stdout.contains("not installed") || stderr.contains("not installed"))
-        }
-        Err(_) => Ok(true),
+        let path =
+            std::env::temp_dir().join(format!("runner-dashboard-log-{}.log", std::process::id()));
+        std::fs::write(&path, "one\ntwo ERROR\nthree\n").unwrap();
+        runner.log_source = Some(LogSource::File(path.clone()));
+        let logs =
+            get_runner_logs(&runner, 2, None, LogPriority::All, JournalFormat::ShortIso).unwrap();
+        assert_eq!(logs, ["two ERROR", "three"]);
+        let errors = get_runner_logs(
+            &runner,
+            10,
+            None,
+            LogPriority::Errors,
+            JournalFormat::ShortIso,
+        )
+        .unwrap();
+        assert_eq!(errors, ["two ERROR"]);
+        let context =
+            get_runner_log_context(&runner, "three", LogPriority::All, JournalFormat::ShortIso)
+                .unwrap();
+        assert_eq!(context, ["one", "two ERROR", "three"]);
+        std::fs::remove_file(&path).unwrap();
     }
-}
 
-/// Install service using svc.sh install command
-fn install_service(
-    svc_script: &Path,
-    runner_path: &Path,
-    runner: &Runner,
-    use_sudo: bool,
-) -> Result<()> {
-    let output = run_script(svc_script, "install", runner_path, use_sudo)?;
+    #[test]
+    fn linux_logs_fall_back_to_diag_when_the_journal_is_empty() {
+        let dir =
+            std::env::temp_dir().join(format!("runner-dashboard-diag-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("_diag")).unwrap();
+        std::fs::write(
+            dir.join("_diag/Runner_20240501-120000-utc.log"),
+            "[2024-05-01 12:00:00Z INFO Listener] Listening for Jobs\n",
+        )
+        .unwrap();
+        let mut runner = test_runner("acme", 1);
+        runner.path = dir.clone();
+        runner.service_name = format!("runner-dashboard-test-{}.service", std::process::id());
+
+        // No journal entries for a unit that doesn't exist, or no journalctl at all
+        let logs =
+            get_runner_logs_linux(&runner, 10, None, LogPriority::All, JournalFormat::ShortIso)
+                .unwrap();
+        assert_eq!(
+            logs,
+            ["[2024-05-01 12:00:00Z INFO Listener] Listening for Jobs"]
+        );
+        let context =
+            get_runner_log_context(&runner, &logs[0], LogPriority::All, JournalFormat::ShortIso)
+                .unwrap();
+        assert_eq!(context, logs);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow::anyhow!(
-            "Failed to install service for {}: {}",
-            runner.display_name(),
-            stderr
-        ));
+    #[test]
+    fn discovery_options_include_listed_repos_only() {
+        assert!(DiscoveryOptions::default().includes_repo("acme"));
+
+        let options = DiscoveryOptions {
+            repos: vec!["acme".to_string(), "widgets".to_string()],
+            ..DiscoveryOptions::default()
+        };
+        assert!(options.includes_repo("widgets"));
+        assert!(!options.includes_repo("acme-legacy"));
     }
 
-    Ok(())
-}
+    #[test]
+    fn duplicate_service_names_lists_shared_units_once() {
+        let mut runners = vec![
+            test_runner("acme", 1),
+            test_runner("acme", 2),
+            test_runner("acme", 1),
+            test_runner("acme", 1),
+        ];
+        runners[2].path = PathBuf::from("/nonexistent/acme/01");
+        runners[3].path = PathBuf::from("/nonexistent/acme/001");
+
+        let duplicates = duplicate_service_names(&runners);
+
+        assert_eq!(duplicates.len(), 1);
+        assert!(duplicates.contains(&runners[0].service_name));
+    }
 
-/// Control runner directly using run.sh script and process management
-fn control_runner_direct(runner: &Runner, action: &str) -> Result<String> {
-    validate_path(&runner.path)?;
+    #[test]
+    fn launchd_plist_keeps_run_sh_alive_and_escapes_xml() {
+        let mut runner = test_runner("acme", 1);
+        runner.path = PathBuf::from("/Users/dev/action-runners/R&D/1");
+        let plist = launchd_plist_contents(&runner);
+
+        assert!(plist.contains("<string>actions.runner.ci.acme-runner-1</string>"));
+        assert!(plist.contains("<string>/Users/dev/action-runners/R&amp;D/1/run.sh</string>"));
+        assert!(plist.contains("<key>RunAtLoad</key>\n    <true/>"));
+        assert!(plist.contains("<key>KeepAlive</key>\n    <true/>"));
+        assert_eq!(xml_escape("<a b='c'>"), "&lt;a b=&apos;c&apos;&gt;");
+    }
 
-    let run_script = runner.path.join("run.sh");
-    let run_script_str = run_script
-        .to_str()
-        .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in path: {:?}", run_script))?;
+    #[test]
+    fn systemd_unit_install_stops_at_the_first_failing_step() {
+        let runner = test_runner("acme", 1);
+        // The staging directory is random, so the mock refuses the install step
+        let cmd = MockCommandRunner::default();
+
+        assert!(install_systemd_unit(&cmd, &runner).is_err());
+
+        let calls = cmd.calls();
+        assert_eq!(calls.len(), 1);
+        let staged = calls[0]
+            .strip_prefix("sudo install -m 644 ")
+            .and_then(|rest| {
+                rest.strip_suffix(" /etc/systemd/system/actions.runner.ci.acme-runner-1.service")
+            })
+            .map(PathBuf::from)
+            .unwrap();
+        let staging = staged.parent().unwrap();
+        assert!(staging.starts_with(std::env::temp_dir()));
+        assert_ne!(staging, std::env::temp_dir());
+        assert!(!staging.exists());
+    }
 
-    match action {
-        "start" => {
-            Command::new("nohup")
-                .arg(run_script_str)
-                .current_dir(&runner.path)
-                .spawn()
-                .with_context(|| format!("Failed to start runner {}", runner.display_name()))?;
-            Ok(format!("Started {}", runner.display_name()))
-        }
-        "stop" => {
-            stop_runner_process(runner)?;
-            Ok(format!("Stopped {}", runner.display_name()))
-        }
-        "restart" => {
-            restart_runner_process(runner, run_script_str)?;
-            Ok(format!("Restarted {}", runner.display_name()))
+    #[test]
+    fn systemd_unit_install_refuses_paths_systemd_would_split() {
+        let mut runner = test_runner("acme", 1);
+        let cmd = MockCommandRunner::default();
+        for path in ["/home/ci/action runners/acme/1", "/home/ci/100%/acme/1"] {
+            runner.path = PathBuf::from(path);
+            let error = install_systemd_unit(&cmd, &runner).unwrap_err().to_string();
+            assert!(error.contains("whitespace, %"), "{}", error);
         }
-        _ => Err(anyhow::anyhow!("Invalid action: {}", action)),
+
+        // The user comes from discovery, not from parsing a custom service name
+        runner.path = PathBuf::from("/nonexistent/action-runners/acme/1");
+        runner.service_name = "actions.runner.acmeorg-acme.build-box-1".to_string();
+        runner.user = "ci;reboot".to_string();
+        let error = install_systemd_unit(&cmd, &runner).unwrap_err().to_string();
+        assert!(
+            error.contains("'ci;reboot' is not a valid user name"),
+            "{}",
+            error
+        );
+        assert!(cmd.calls().is_empty());
     }
-}
 
-/// Stop runner process using pkill
-fn stop_runner_process(runner: &Runner) -> Result<()> {
-    // Validate path to prevent command injection via pkill pattern
-    validate_path(&runner.path)?;
+    #[test]
+    fn systemd_unit_runs_run_sh_as_the_service_user() {
+        let unit = systemd_unit_contents(&test_runner("acme", 2), "ci");
 
-    let path_str = runner.path.to_string_lossy();
-    Command::new("pkill")
-        .args(["-f", &format!("Runner.*{}", path_str)])
-        .output()
-        .with_context(|| format!("Failed to stop runner {}", runner.display_name()))?;
-    Ok(())
-}
+        assert!(unit.contains("ExecStart=/nonexistent/action-runners/acme/2/run.sh\n"));
+        assert!(unit.contains("User=ci\n"));
+        assert!(unit.contains("Restart=always\n"));
+    }
 
-/// Restart runner process by stopping, waiting for termination, and starting again
-fn restart_runner_process(runner: &Runner, run_script_str: &str) -> Result<()> {
-    // Validate path to prevent command injection via pkill pattern
-    validate_path(&runner.path)?;
+    #[test]
+    fn diag_lines_are_filtered_by_priority() {
+        let info = "[2025-01-01 10:00:00Z INFO Runner] Listening for Jobs";
+        let warn = "[2025-01-01 10:00:01Z WARN Runner] Retrying";
+        let err = "[2025-01-01 10:00:02Z ERR  GitHubActionsService] Connection refused";
+
+        assert!(LogPriority::All.matches_line(info));
+        assert!(!LogPriority::Warnings.matches_line(info));
+        assert!(LogPriority::Warnings.matches_line(warn));
+        assert!(!LogPriority::Errors.matches_line(warn));
+        assert!(LogPriority::Errors.matches_line(err));
+    }
 
-    let path_str = runner.path.to_string_lossy();
-    let _ = Command::new("pkill")
-        .args(["-f", &format!("Runner.*{}", path_str)])
-        .output();
+    #[test]
+    fn github_repo_comes_from_the_runner_registration() {
+        let dir = std::env::temp_dir().join(format!("runner-dashboard-gh-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |url: &str| {
+            let contents = format!("\u{feff}{{\"agentId\": 1, \"gitHubUrl\": \"{}\"}}", url);
+            std::fs::write(dir.join(".runner"), contents).unwrap();
+        };
 
-    // Poll for process termination (up to 5 seconds)
-    let timeout = std::time::Duration::from_secs(5);
-    let start = std::time::Instant::now();
-    while is_runner_process_running(&runner.path) {
-        if start.elapsed() > timeout {
-            return Err(anyhow::anyhow!(
-                "Timeout waiting for runner {} to stop",
-                runner.display_name()
-            ));
-        }
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        write("https://github.com/acmeorg/acme");
+        assert_eq!(github_repo(&dir).as_deref(), Some("acmeorg/acme"));
+        assert_eq!(agent_id(&dir), Some(1));
+        write("https://github.com/acmeorg");
+        assert_eq!(github_repo(&dir), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(github_repo(&dir), None);
     }
 
-    Command::new("nohup")
-        .arg(run_script_str)
-        .current_dir(&runner.path)
-        .spawn()
-        .with_context(|| format!("Failed to restart runner {}", runner.display_name()))?;
+    #[test]
+    fn runner_version_is_read_from_disk() {
+        let dir =
+            std::env::temp_dir().join(format!("runner-dashboard-version-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        assert_eq!(detect_runner_version(&dir), None);
+
+        std::fs::write(
+            dir.join("bin/Runner.Listener.deps.json"),
+            r#"{"targets": {".NETCoreApp,Version=v6.0/linux-x64": {
+                "Runner.Common/2.311.0": {}, "Runner.Listener/2.311.0": {}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(detect_runner_version(&dir).as_deref(), Some("2.311.0"));
+
+        std::fs::write(dir.join(".version"), "2.312.0\n").unwrap();
+        assert_eq!(detect_runner_version(&dir).as_deref(), Some("2.312.0"));
+
+        std::fs::rename(dir.join("bin"), dir.join("bin.2.313.0")).unwrap();
+        std::os::unix::fs::symlink("bin.2.313.0", dir.join("bin")).unwrap();
+        assert_eq!(detect_runner_version(&dir).as_deref(), Some("2.313.0"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
-    Ok(())
-}
+    #[test]
+    fn agent_settings_come_from_the_registration_and_package() {
+        let dir =
+            std::env::temp_dir().join(format!("runner-dashboard-agent-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        assert_eq!(agent_settings(&dir), AgentSettings::default());
+
+        std::fs::write(
+            dir.join(".runner"),
+            "\u{feff}{\"poolName\": \"Default\", \"disableUpdate\": true}",
+        )
+        .unwrap();
+        let mut elf = [0u8; 20];
+        elf[..4].copy_from_slice(b"\x7fELF");
+        elf[18] = 0xb7;
+        std::fs::write(dir.join("bin/Runner.Listener"), elf).unwrap();
+        assert_eq!(
+            agent_settings(&dir),
+            AgentSettings {
+                platform: Some("linux-arm64".to_string()),
+                runner_group: Some("Default".to_string()),
+                ephemeral: Some(false),
+                auto_update: Some(false),
+                work_folder: Some("_work".to_string()),
+            }
+        );
 
-/// Control runner on macOS using launchctl or direct script
-fn control_runner_macos(runner: &Runner, action: &str) -> Result<String> {
-    // Try launchctl first
-    if let Some(result) = try_launchctl_control(runner, action)? {
-        return Ok(result);
+        let mut macho = [0u8; 20];
+        macho[..8].copy_from_slice(&[0xcf, 0xfa, 0xed, 0xfe, 0x07, 0x00, 0x00, 0x01]);
+        assert_eq!(platform_from_header(&macho).as_deref(), Some("osx-x64"));
+        assert_eq!(platform_from_header(&[0u8; 20]), None);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    // Fallback to svc.sh script (without sudo on macOS)
-    if let Some(result) = try_svc_script_control(runner, action, false)? {
-        return Ok(result);
+    #[test]
+    fn runner_config_is_shown_with_secrets_redacted() {
+        let dir = std::env::temp_dir().join(format!("runner-dashboard-cfg-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = read_runner_config(&dir).unwrap_err();
+        assert!(format!("{:#}", missing).contains("has not been configured"));
+
+        std::fs::write(
+            dir.join(".runner"),
+            "\u{feff}{\"agentName\": \"runner-1\", \"registrationToken\": \"AAAA\", \"extra\": {\"apiKey\": \"BBBB\"}}",
+        )
+        .unwrap();
+        let lines = read_runner_config(&dir).unwrap();
+        let text = lines.join("\n");
+        assert!(lines.contains(&r#"  "agentName": "runner-1","#.to_string()));
+        assert!(text.contains(r#""registrationToken": "<redacted>""#));
+        assert!(text.contains(r#""apiKey": "<redacted>""#));
+        assert!(!text.contains("AAAA") && !text.contains("BBBB"));
+
+        // A broken file is reported, not echoed
+        std::fs::write(dir.join(".runner"), "token=CCCC").unwrap();
+        let broken = format!("{:#}", read_runner_config(&dir).unwrap_err());
+        assert!(broken.contains("not valid JSON"));
+        assert!(!broken.contains("CCCC"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    // Final fallback: direct run.sh control
-    control_runner_direct(runner, action)
-}
+    #[test]
+    fn probe_reports_the_systemd_unit_state() {
+        let runner = test_runner("acme", 1);
+        let cmd = MockCommandRunner::default().with(
+            "systemctl status --no-pager actions.runner.ci.acme-runner-1",
+            3,
+            "○ actions.runner.ci.acme-runner-1.service\n     Active: inactive (dead)\n",
+            "",
+        );
 
-/// Attempt to control runner using launchctl, returns None if service doesn't exist
-fn try_launchctl_control(runner: &Runner, action: &str) -> Result<Option<String>> {
-    let plist_path = format!("~/Library/LaunchAgents/{}.plist", runner.service_name);
-    let expanded_plist = shellexpand::tilde(&plist_path);
+        let report = probe_runner_linux(&cmd, &runner);
+
+        assert_eq!(report.backend, "systemd");
+        assert!(report.ok);
+        assert!(report.summary.contains("installed but not running"));
+        assert_eq!(
+            report.transcript,
+            [
+                "$ systemctl status --no-pager actions.runner.ci.acme-runner-1",
+                "○ actions.runner.ci.acme-runner-1.service",
+                "     Active: inactive (dead)",
+                "(exit status 3)",
+            ]
+        );
+    }
 
-    if !std::path::Path::new(expanded_plist.as_ref()).exists() {
-        return Ok(None);
+    #[test]
+    fn probe_falls_back_to_svc_sh_without_prompting_for_sudo() {
+        let dir =
+            std::env::temp_dir().join(format!("runner-dashboard-probe-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("svc.sh"), "").unwrap();
+        let runner = Runner {
+            path: dir.clone(),
+            ..test_runner("acme", 2)
+        };
+        let svc_status = format!("sudo -n {}/svc.sh status", dir.display());
+        let cmd = MockCommandRunner::default()
+            .with(
+                "systemctl status --no-pager actions.runner.ci.acme-runner-2",
+                4,
+                "",
+                "Unit actions.runner.ci.acme-runner-2.service could not be found.",
+            )
+            .with(&svc_status, 1, "", "sudo: a password is required");
+
+        let report = probe_runner_linux(&cmd, &runner);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.backend, "svc.sh");
+        assert!(!report.ok);
+        assert!(report
+            .summary
+            .starts_with("No systemd unit named actions.runner.ci.acme-runner-2;"));
+        assert!(report.summary.contains("sudo -v"));
+        assert!(report.transcript.contains(&format!("$ {}", svc_status)));
     }
 
-    let output = match action {
-        "restart" => Command::new("launchctl")
-            .args([
-                "kickstart",
-                "-k",
-                &format!("gui/{}/{}", get_uid(), runner.service_name),
-            ])
-            .output()?,
-        "start" => Command::new("launchctl")
-            .args(["load", expanded_plist.as_ref()])
-            .output()?,
-        "stop" => Command::new("launchctl")
-            .args(["unload", expanded_plist.as_ref()])
-            .output()?,
-        _ => return Err(anyhow::anyhow!("Invalid action")),
-    };
+    #[test]
+    fn failed_control_keeps_the_full_command_output() {
+        let runner = test_runner("acme", 1);
+        let cmd = MockCommandRunner::default()
+            .with("systemctl cat actions.runner.ci.acme-runner-1", 0, "", "")
+            .with(
+                "sudo systemctl start actions.runner.ci.acme-runner-1",
+                1,
+                "Job for actions.runner.ci.acme-runner-1.service failed.\n",
+                "See \"systemctl status\" for details.\n",
+            );
 
-    handle_control_output(output, action, runner)
-}
+        let err = try_systemctl_control(&cmd, &runner, "start").unwrap_err();
+        let failure = err.downcast_ref::<CommandFailure>().unwrap();
+
+        assert_eq!(failure.context, "Failed to start acme-runner-1");
+        assert_eq!(
+            failure.transcript(),
+            [
+                "$ sudo systemctl start actions.runner.ci.acme-runner-1",
+                "Job for actions.runner.ci.acme-runner-1.service failed.",
+                STDERR_HEADING,
+                "See \"systemctl status\" for details.",
+                "(exit status 1)",
+            ]
+        );
+    }
 
-/// Get current user ID for launchctl service domain.
-fn get_uid() -> u32 {
-    // SAFETY: getuid() is a read-only syscall with no side effects or failure modes
-    unsafe { libc::getuid() }
-}
+    #[test]
+    fn registration_is_matched_by_agent_name() {
+        let registered = |status: &str| {
+            vec![RegisteredRunner {
+                name: "build-box-1".to_string(),
+                status: status.to_string(),
+                labels: Vec::new(),
+            }]
+        };
+        let mut runner = test_runner("acme", 1);
+        runner.status = RunnerStatus::Active;
 
-/// Get recent logs for a runner (cross-platform)
-pub fn get_runner_logs(runner: &Runner, lines: usize) -> Result<Vec<String>> {
-    if cfg!(target_os = "macos") {
-        get_runner_logs_macos(runner, lines)
-    } else {
-        get_runner_logs_linux(runner, lines)
+        assert_eq!(
+            registration_status(&runner, "build-box-1", &registered("online")),
+            Registration::Ok { online: true }
+        );
+        assert_eq!(
+            registration_status(&runner, "build-box-2", &registered("online")),
+            Registration::NotFound
+        );
+        // Running here but GitHub never hears from it
+        assert_eq!(
+            registration_status(&runner, "build-box-1", &registered("offline")),
+            Registration::Deauthorized
+        );
+        runner.status = RunnerStatus::Inactive;
+        assert_eq!(
+            registration_status(&runner, "build-box-1", &registered("offline")),
+            Registration::Ok { online: false }
+        );
     }
-}
 
-/// Get logs on Linux using journalctl
-fn get_runner_logs_linux(runner: &Runner, lines: usize) -> Result<Vec<String>> {
-    let output = Command::new("journalctl")
-        .args([
-            "-u",
-            &runner.service_name,
-            "-n",
-            &lines.to_string(),
-            "--no-pager",
-            "-o",
-            "short-iso",
-        ])
-        .output()?;
+    #[test]
+    fn repos_found_under_several_roots_are_qualified() {
+        let in_root = |repo: &str, root: &str| Runner {
+            repo: repo.to_string(),
+            root: PathBuf::from(root),
+            ..test_runner("acme", 1)
+        };
+        let mut runners = vec![
+            in_root("acme", "/home/ci/action-runners"),
+            in_root("acme", "/srv/runners"),
+            in_root("widgets", "/srv/runners"),
+            in_root("tools", "/a/runners"),
+            in_root("tools", "/b/runners"),
+        ];
+
+        qualify_colliding_repos(&mut runners);
+
+        let repos: Vec<&str> = runners.iter().map(|r| r.repo.as_str()).collect();
+        assert_eq!(
+            repos,
+            [
+                "acme@action-runners",
+                "acme@runners",
+                "widgets",
+                "tools@/a/runners",
+                "tools@/b/runners"
+            ]
+        );
+    }
 
-    let logs = String::from_utf8_lossy(&output.stdout);
-    Ok(logs.lines().map(|s| s.to_string()).collect())
-}
+    #[test]
+    fn discovery_roots_default_to_action_runners() {
+        let roots = DiscoveryOptions::default().roots().unwrap();
+        assert_eq!(roots, vec![runners_dir().unwrap()]);
+
+        let options = DiscoveryOptions {
+            dirs: vec![
+                PathBuf::from("/srv/runners"),
+                PathBuf::from("/opt/runners"),
+                PathBuf::from("/srv/runners"),
+            ],
+            ..DiscoveryOptions::default()
+        };
+        assert_eq!(
+            options.roots().unwrap(),
+            [PathBuf::from("/srv/runners"), PathBuf::from("/opt/runners")]
+        );
+    }
 
-/// Get logs on macOS from _diag directory
-fn get_runner_logs_macos(runner: &Runner, lines: usize) -> Result<Vec<String>> {
-    let diag_dir = runner.path.join("_diag");
+    #[test]
+    fn symlinked_runner_directories_are_discovered_once() {
+        let root =
+            std::env::temp_dir().join(format!("runner-dashboard-links-{}", std::process::id()));
+        let runner_dir = root.join("acme").join("1");
+        std::fs::create_dir_all(&runner_dir).unwrap();
+        std::fs::write(runner_dir.join("run.sh"), "").unwrap();
+        std::os::unix::fs::symlink(root.join("acme"), root.join("acme-link")).unwrap();
+        // A loop back to the repo must not be mistaken for a runner or followed
+        std::os::unix::fs::symlink(root.join("acme"), runner_dir.join("loop")).unwrap();
+
+        let template = ServiceTemplate::default();
+        let service = template.render("ci", "acme", 1, "acme-runner-1");
+        let installed = HashSet::from([service.clone()]);
+        let mut seen = HashSet::new();
+        let mut discover = |repo| {
+            discover_repo_runners(
+                &root,
+                repo,
+                &template,
+                "ci",
+                &HashSet::new(),
+                &installed,
+                &mut seen,
+            )
+            .unwrap()
+        };
+        let found = discover("acme");
+        let via_link = discover("acme-link");
+        let entries = dir_entries(&root).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, runner_dir);
+        assert_eq!(found[0].service_name, service);
+        // Installation comes from the listing; status waits for the batched refresh
+        assert!(found[0].service_installed);
+        assert_eq!(found[0].status, RunnerStatus::NotFound);
+        assert!(via_link.is_empty());
+        assert_eq!(entries, [root.join("acme"), root.join("acme-link")]);
+    }
 
-    if !diag_dir.exists() {
-        return Ok(vec!["No logs found (no _diag directory)".to_string()]);
+    #[test]
+    fn journal_timestamps_parse_both_offset_styles() {
+        let line = "2024-05-01T12:34:56+0000 host Runner.Listener[42]: Running job: build";
+        assert_eq!(journal_timestamp(line), Some(1_714_566_896));
+        assert_eq!(
+            journal_timestamp("2024-05-01T14:34:56+02:00 host Runner.Listener[42]: x"),
+            Some(1_714_566_896)
+        );
+        assert_eq!(
+            journal_timestamp("2024-02-29T00:00:00-0130 host x"),
+            Some(1_709_170_200)
+        );
+        assert_eq!(journal_timestamp("-- No entries --"), None);
+        assert_eq!(journal_timestamp("2024-13-01T00:00:00+0000 host x"), None);
     }
 
-    // Try to find the most recent Worker log, then Runner log
-    for prefix in ["Worker_", "Runner_"] {
-        if let Some(content) = find_latest_log_file(&diag_dir, prefix, lines)? {
-            return Ok(content);
-        }
+    #[test]
+    fn context_windows_follow_the_journal_format() {
+        // 2024-05-01T12:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_714_564_800);
+        let iso = "2024-05-01T12:34:56+0000 host Runner.Listener[42]: x";
+        assert_eq!(
+            journal_window(iso, JournalFormat::ShortIso, now),
+            Some(("@1714566596".to_string(), "@1714567196".to_string()))
+        );
+        assert_eq!(
+            journal_window(
+                "May 01 11:58:00.123456 host x",
+                JournalFormat::ShortPrecise,
+                now
+            ),
+            Some((
+                "2024-05-01 11:53:00".to_string(),
+                "2024-05-01 12:03:00".to_string()
+            ))
+        );
+        // December lines read in early May are from last year
+        assert_eq!(
+            journal_window("Dec 31 23:58:00 host x", JournalFormat::Short, now),
+            Some((
+                "2023-12-31 23:53:00".to_string(),
+                "2024-01-01 00:03:00".to_string()
+            ))
+        );
+        assert_eq!(journal_window(iso, JournalFormat::Short, now), None);
+        assert_eq!(journal_window("x", JournalFormat::Cat, now), None);
     }
 
-    Ok(vec!["No log files found in _diag".to_string()])
-}
+    #[test]
+    fn work_scans_count_job_dirs_without_following_symlinks() {
+        let dir =
+            std::env::temp_dir().join(format!("runner-dashboard-work-{}", std::process::id()));
+        let work = dir.join("work");
+        std::fs::create_dir_all(work.join("_actions/checkout")).unwrap();
+        std::fs::create_dir_all(work.join("acme/acme/src")).unwrap();
+        std::fs::create_dir_all(work.join("widgets")).unwrap();
+        std::fs::write(work.join("_actions/checkout/action.yml"), vec![0; 500]).unwrap();
+        std::fs::write(work.join("acme/acme/src/main.rs"), vec![0; 1000]).unwrap();
+        std::fs::write(work.join("widgets/out.bin"), vec![0; 24]).unwrap();
+        std::os::unix::fs::symlink(&dir, work.join("acme/acme/loop")).unwrap();
+        std::fs::write(
+            dir.join(".runner"),
+            "\u{feff}{\"agentName\": \"acme-runner-1\", \"workFolder\": \"work\"}",
+        )
+        .unwrap();
+        let runner = Runner {
+            path: dir.clone(),
+            ..test_runner("acme", 1)
+        };
 
-/// Find and read the most recent log file with the given prefix
-fn find_latest_log_file(
-    diag_dir: &Path,
-    prefix: &str,
-    lines: usize,
-) -> Result<Option<Vec<String>>> {
-    let mut log_files: Vec<_> = std::fs::read_dir(diag_dir)?
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
-        .collect();
+        let usage = scan_work(&runner).unwrap();
+        let symlink_len = std::fs::symlink_metadata(work.join("acme/acme/loop"))
+            .unwrap()
+            .len();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(usage.folder, work);
+        assert_eq!(usage.job_dirs, 2);
+        assert_eq!(usage.bytes, 1024 + symlink_len);
+        assert_eq!(
+            work_folder(Path::new("/nonexistent/acme/1")),
+            PathBuf::from("/nonexistent/acme/1/_work")
+        );
+    }
 
-    log_files.sort_by_key(|e| std::cmp::Reverse(e.metadata().ok().and_then(|m| m.modified().ok())));
+    #[test]
+    fn pruning_work_needs_a_stopped_runner_and_reports_space_freed() {
+        let dir =
+            std::env::temp_dir().join(format!("runner-dashboard-prune-{}", std::process::id()));
+        let work = dir.join("_work");
+        std::fs::create_dir_all(work.join("acme/acme")).unwrap();
+        std::fs::create_dir_all(work.join("_actions")).unwrap();
+        std::fs::write(work.join("acme/acme/build.log"), vec![0; 2048]).unwrap();
+        std::os::unix::fs::symlink(&dir, work.join("_actions/loop")).unwrap();
+        let mut runner = Runner {
+            path: dir.clone(),
+            status: RunnerStatus::Active,
+            ..test_runner("acme", 1)
+        };
+        let cmd = MockCommandRunner::default();
 
-    if let Some(latest_log) = log_files.first() {
-        let content = std::fs::read_to_string(latest_log.path())?;
-        let all_lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-        let start = all_lines.len().saturating_sub(lines);
-        Ok(Some(all_lines[start..].to_vec()))
-    } else {
-        Ok(None)
+        let refused = control_runner(&cmd, &runner, PRUNE_WORK_ACTION).unwrap_err();
+        assert!(refused.to_string().contains("stop it first"));
+        assert!(work.join("acme/acme/build.log").exists());
+
+        runner.status = RunnerStatus::Inactive;
+        let message = control_runner(&cmd, &runner, PRUNE_WORK_ACTION).unwrap();
+        let emptied = std::fs::read_dir(&work).unwrap().count() == 0;
+        let runner_kept = dir.exists();
+
+        std::fs::write(dir.join(".runner"), r#"{"workFolder": ".."}"#).unwrap();
+        let outside = control_runner(&cmd, &runner, PRUNE_WORK_ACTION).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            message.starts_with("Pruned acme-runner-1: freed 2 KB"),
+            "{}",
+            message
+        );
+        assert!(emptied && runner_kept);
+        assert!(outside.to_string().contains("contains the runner itself"));
+        assert!(cmd.calls().is_empty());
+    }
+
+    #[test]
+    fn missing_tools_are_only_those_not_executable_on_the_path() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("runner-tools-{}", std::process::id()));
+        let bin = dir.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        for (name, mode) in [("systemctl", 0o755), ("launchctl", 0o755), ("pgrep", 0o644)] {
+            let path = bin.join(name);
+            std::fs::write(&path, "").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        }
+        let path = std::env::join_paths([dir.join("empty"), bin]).unwrap();
+
+        let missing = missing_tools(Some(&path));
+        let nothing = missing_tools(None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(missing
+            .iter()
+            .all(|m| !m.starts_with("systemctl") && !m.starts_with("launchctl")));
+        assert!(missing
+            .contains(&"pgrep not found — running and busy runners can't be detected".to_string()));
+        assert_eq!(nothing.len(), host_tools().len());
+        assert!(nothing[0].ends_with("not found — status limited to process detection"));
     }
 }
@@ -0,0 +1,184 @@
+use crate::report;
+use crate::runner::{
+    discover_runners, refresh_runners, DiscoveryOptions, Runner, SystemCommandRunner, UnitCache,
+};
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Response, Server};
+
+/// How often the exporter re-polls runner statuses
+const SERVE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Cached state is considered unhealthy if it hasn't refreshed for this long
+const HEALTH_STALE_AFTER: Duration = Duration::from_secs(3 * SERVE_REFRESH_INTERVAL.as_secs());
+
+/// How long to block waiting for a request before checking for shutdown
+const ACCEPT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Runner state shared between the refresh loop and request handlers
+struct SharedState {
+    runners: Vec<Runner>,
+    last_update: Instant,
+}
+
+/// Serve runner status over HTTP until SIGINT/SIGTERM.
+///
+/// Endpoints: `/status` (JSON), `/metrics` (Prometheus text), `/healthz`.
+///
+/// With `profile`, how long each refresh took is printed to stderr.
+pub fn serve(addr: &str, options: &DiscoveryOptions, profile: bool) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&shutdown))?;
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&shutdown))?;
+
+    let mut runners = discover_runners(options)?;
+    refresh_runners(
+        &SystemCommandRunner,
+        &mut runners,
+        &UnitCache::load(&SystemCommandRunner),
+    );
+    let state = Arc::new(Mutex::new(SharedState {
+        runners,
+        last_update: Instant::now(),
+    }));
+
+    let refresh_state = Arc::clone(&state);
+    let refresh_shutdown = Arc::clone(&shutdown);
+    let refresher =
+        std::thread::spawn(move || refresh_loop(refresh_state, refresh_shutdown, profile));
+
+    let server =
+        Server::http(addr).map_err(|e| anyhow::anyhow!("Failed to bind {}: {}", addr, e))?;
+    eprintln!("Serving runner status on http://{}", addr);
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let Some(request) = server.recv_timeout(ACCEPT_TIMEOUT)? else {
+            continue;
+        };
+
+        let response = handle_request(request.url(), &state);
+        let _ = request.respond(response);
+    }
+
+    eprintln!("Shutting down");
+    let _ = refresher.join();
+    Ok(())
+}
+
+/// Periodically refresh runner statuses into the shared state.
+fn refresh_loop(state: Arc<Mutex<SharedState>>, shutdown: Arc<AtomicBool>, profile: bool) {
+    let units = UnitCache::load(&SystemCommandRunner);
+    let mut last_refresh = Instant::now();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        if last_refresh.elapsed() < SERVE_REFRESH_INTERVAL {
+            std::thread::sleep(ACCEPT_TIMEOUT);
+            continue;
+        }
+
+        // Refresh a copy so requests aren't blocked on subprocess calls
+        let mut runners = match state.lock() {
+            Ok(state) => state.runners.clone(),
+            Err(_) => return,
+        };
+        let timings = refresh_runners(&SystemCommandRunner, &mut runners, &units);
+        if profile {
+            eprintln!("{}", timings.describe());
+        }
+
+        if let Ok(mut state) = state.lock() {
+            state.runners = runners;
+            state.last_update = Instant::now();
+        }
+        last_refresh = Instant::now();
+    }
+}
+
+fn handle_request(url: &str, state: &Mutex<SharedState>) -> Response<std::io::Cursor<Vec<u8>>> {
+    let Ok(state) = state.lock() else {
+        return text_response(500, "text/plain", "state unavailable\n".to_string());
+    };
+
+    // Ignore any query string
+    let path = url.split('?').next().unwrap_or(url);
+
+    match path {
+        "/status" => match report::to_json(&state.runners) {
+            Ok(json) => text_response(200, "application/json", json),
+            Err(e) => text_response(500, "text/plain", format!("{}\n", e)),
+        },
+        "/metrics" => text_response(
+            200,
+            "text/plain; version=0.0.4",
+            report::to_prometheus(&state.runners),
+        ),
+        "/healthz" => {
+            if state.last_update.elapsed() > HEALTH_STALE_AFTER {
+                text_response(503, "text/plain", "stale\n".to_string())
+            } else {
+                text_response(200, "text/plain", "ok\n".to_string())
+            }
+        }
+        _ => text_response(404, "text/plain", "not found\n".to_string()),
+    }
+}
+
+fn text_response(
+    status: u16,
+    content_type: &str,
+    body: String,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let mut response = Response::from_string(body).with_status_code(status);
+    if let Ok(header) = Header::from_bytes("Content-Type", content_type) {
+        response = response.with_header(header);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::test_runner;
+    use std::io::Read;
+
+    fn get(url: &str, state: &Mutex<SharedState>) -> (u16, String) {
+        let response = handle_request(url, state);
+        let status = response.status_code().0;
+        let mut body = String::new();
+        response.into_reader().read_to_string(&mut body).unwrap();
+        (status, body)
+    }
+
+    #[test]
+    fn requests_are_routed_by_path() {
+        let state = Mutex::new(SharedState {
+            runners: vec![test_runner("acme", 1)],
+            last_update: Instant::now(),
+        });
+
+        let (status, body) = get("/status?pretty", &state);
+        assert_eq!(status, 200);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["counts"]["active"], 1);
+        assert_eq!(json["runners"][0]["name"], "runner-1");
+
+        let (status, body) = get("/metrics", &state);
+        assert_eq!(status, 200);
+        assert!(body.contains("github_runner_up{repo=\"acme\",name=\"runner-1\"} 1\n"));
+
+        assert_eq!(get("/healthz", &state), (200, "ok\n".to_string()));
+        assert_eq!(get("/", &state), (404, "not found\n".to_string()));
+    }
+
+    #[test]
+    fn health_check_fails_once_the_state_is_stale() {
+        let state = Mutex::new(SharedState {
+            runners: Vec::new(),
+            last_update: Instant::now() - HEALTH_STALE_AFTER - Duration::from_secs(1),
+        });
+
+        assert_eq!(get("/healthz", &state), (503, "stale\n".to_string()));
+    }
+}
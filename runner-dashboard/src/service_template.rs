@@ -0,0 +1,227 @@
+use anyhow::{bail, Result};
+use regex::Regex;
+
+/// The service names `svc.sh install` gives runners
+pub const DEFAULT_SERVICE_TEMPLATE: &str = "actions.runner.{user}.{repo}-runner-{num}";
+
+/// What every runner service name starts with; control actions refuse others
+pub const SERVICE_PREFIX: &str = "actions.runner.";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    User,
+    Repo,
+    Num,
+    Name,
+}
+
+/// How runner service names are built, e.g. `actions.runner.{user}.{repo}-runner-{num}`.
+///
+/// `{user}` is the account that installed the service, `{repo}` the repository
+/// directory, `{num}` the runner directory number and `{name}` the agent name the
+/// runner registered with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceTemplate {
+    parts: Vec<Part>,
+}
+
+impl Default for ServiceTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_SERVICE_TEMPLATE).expect("default service template is valid")
+    }
+}
+
+impl ServiceTemplate {
+    /// Parse a template, rejecting unknown placeholders, unbalanced braces and
+    /// names control actions would refuse.
+    pub fn parse(template: &str) -> Result<Self> {
+        if !template.starts_with(SERVICE_PREFIX) {
+            bail!(
+                "Service name template '{}' must start with '{}'",
+                template,
+                SERVICE_PREFIX
+            );
+        }
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find(['{', '}']) {
+            if rest[open..].starts_with('}') {
+                bail!("Unmatched '}}' in service name template '{}'", template);
+            }
+            if open > 0 {
+                parts.push(Part::Text(rest[..open].to_string()));
+            }
+            let Some(len) = rest[open..].find('}') else {
+                bail!("Unclosed '{{' in service name template '{}'", template);
+            };
+            parts.push(match &rest[open + 1..open + len] {
+                "user" => Part::User,
+                "repo" => Part::Repo,
+                "num" => Part::Num,
+                "name" => Part::Name,
+                other => bail!(
+                    "Unknown placeholder '{{{}}}' in service name template '{}' \
+                     (expected {{user}}, {{repo}}, {{num}} or {{name}})",
+                    other,
+                    template
+                ),
+            });
+            rest = &rest[open + len + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+
+        // The same characters `control_runner` accepts in a service name
+        for part in &parts {
+            if let Part::Text(text) = part {
+                if let Some(c) = text
+                    .chars()
+                    .find(|c| !(c.is_alphanumeric() || matches!(c, '.' | '-' | '_')))
+                {
+                    bail!(
+                        "Service name template '{}' contains {:?}; only letters, digits, \
+                         '.', '-' and '_' are allowed",
+                        template,
+                        c
+                    );
+                }
+            }
+        }
+
+        // Otherwise every runner in a repo would get the same service
+        if !parts.iter().any(|p| matches!(p, Part::Num | Part::Name)) {
+            bail!(
+                "Service name template '{}' needs {{num}} or {{name}} to tell runners apart",
+                template
+            );
+        }
+        Ok(Self { parts })
+    }
+
+    /// Service name for runner `num` of `repo`, registered as `name`, installed by `user`.
+    pub fn render(&self, user: &str, repo: &str, num: u32, name: &str) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::User => user.to_string(),
+                Part::Repo => repo.to_string(),
+                Part::Num => num.to_string(),
+                Part::Name => name.to_string(),
+            })
+            .collect()
+    }
+
+    /// Whether `service_name` belongs to this runner, installed by any account.
+    pub fn matches_runner(&self, service_name: &str, repo: &str, num: u32, name: &str) -> bool {
+        self.regex(|part| match part {
+            Part::User => "[^.]+".to_string(),
+            Part::Repo => regex::escape(repo),
+            Part::Num => num.to_string(),
+            Part::Name => regex::escape(name),
+            Part::Text(text) => regex::escape(text),
+        })
+        .is_match(service_name)
+    }
+
+    /// Whether `service_name` could be a runner's under this template.
+    pub fn fits(&self, service_name: &str) -> bool {
+        self.regex(|part| match part {
+            Part::User => "[^.]+".to_string(),
+            Part::Repo | Part::Name => ".+".to_string(),
+            Part::Num => "[0-9]+".to_string(),
+            Part::Text(text) => regex::escape(text),
+        })
+        .is_match(service_name)
+    }
+
+    /// The `{user}` part of a service name following this template.
+    pub fn user_in<'a>(&self, service_name: &'a str) -> Option<&'a str> {
+        if !self.parts.contains(&Part::User) {
+            return None;
+        }
+        let regex = self.regex(|part| match part {
+            Part::User => "([^.]+)".to_string(),
+            Part::Repo | Part::Name => ".+".to_string(),
+            Part::Num => "[0-9]+".to_string(),
+            Part::Text(text) => regex::escape(text),
+        });
+        let user = regex.captures(service_name)?.get(1)?;
+        Some(user.as_str())
+    }
+
+    /// Anchored regex with each part replaced by `pattern`.
+    fn regex(&self, pattern: impl Fn(&Part) -> String) -> Regex {
+        let body: String = self.parts.iter().map(pattern).collect();
+        Regex::new(&format!("^{}$", body)).expect("escaped template is a valid regex")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_template_renders_setup_names() {
+        let template = ServiceTemplate::default();
+
+        assert_eq!(
+            template.render("ci", "acme", 2, "acme-runner-2"),
+            "actions.runner.ci.acme-runner-2"
+        );
+        assert_eq!(
+            template.user_in("actions.runner.ci.acme-runner-2"),
+            Some("ci")
+        );
+        assert!(template.matches_runner("actions.runner.builder.acme-runner-2", "acme", 2, ""));
+        assert!(!template.matches_runner(
+            "actions.runner.builder.widgets-acme-runner-2",
+            "acme",
+            2,
+            ""
+        ));
+    }
+
+    #[test]
+    fn custom_templates_use_org_and_agent_names() {
+        let template = ServiceTemplate::parse("actions.runner.acmeorg-{repo}.{name}").unwrap();
+
+        assert_eq!(
+            template.render("ci", "acme", 1, "build-box-1"),
+            "actions.runner.acmeorg-acme.build-box-1"
+        );
+        assert!(template.matches_runner(
+            "actions.runner.acmeorg-acme.build-box-1",
+            "acme",
+            1,
+            "build-box-1"
+        ));
+        assert_eq!(
+            template.user_in("actions.runner.acmeorg-acme.build-box-1"),
+            None
+        );
+        assert!(template.fits("actions.runner.acmeorg-widgets.gpu-1"));
+        assert!(!template.fits("actions.runner.ci.acme-runner-1"));
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        for (template, error) in [
+            (
+                "actions.runner.{user}.{repo}-{number}",
+                "Unknown placeholder '{number}'",
+            ),
+            ("actions.runner.{user}.{repo}-{num", "Unclosed '{'"),
+            ("actions.runner.{user}}.{num}", "Unmatched '}'"),
+            ("actions.runner.{user}.{repo}", "needs {num} or {name}"),
+            ("runners.{repo}-{num}", "must start with 'actions.runner.'"),
+            ("actions.runner.{user} {repo}-{num}", "contains ' '"),
+            ("actions.runner.{user}/{repo}-{num}", "contains '/'"),
+        ] {
+            let message = ServiceTemplate::parse(template).unwrap_err().to_string();
+            assert!(message.contains(error), "{}: {}", template, message);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::runner::RunnerStatus;
+use ratatui::symbols::bar;
+
+/// Glyphs used to draw runner state and resource bars.
+///
+/// The Unicode set shows up as boxes on some terminals, fonts and SSH sessions,
+/// so `--ascii` (or `ascii = true` in the config) swaps in plain ASCII.
+#[derive(Debug)]
+pub struct Symbols {
+    pub active: &'static str,
+    pub inactive: &'static str,
+    pub failed: &'static str,
+    pub not_found: &'static str,
+    /// Runner executing a job
+    pub busy: &'static str,
+    /// Runner with a start/stop/restart still in flight
+    pub pending: &'static str,
+    /// Runner agent behind the latest version
+    pub outdated: &'static str,
+    /// Runner sharing its service name with another
+    pub duplicate: &'static str,
+    /// Runner pinned to the top of the list
+    pub pinned: &'static str,
+    /// Runner marked for the next start/stop/restart
+    pub marked: &'static str,
+    /// Direction of the list's sort column
+    pub sort_ascending: &'static str,
+    pub sort_descending: &'static str,
+    pub bar_filled: &'static str,
+    pub bar_empty: &'static str,
+    pub receive: &'static str,
+    pub transmit: &'static str,
+    /// Levels for history sparklines
+    pub sparkline: bar::Set,
+    /// Status bar animation while a refresh is in flight
+    pub spinner: &'static [&'static str],
+}
+
+pub const UNICODE: Symbols = Symbols {
+    active: "●",
+    inactive: "○",
+    failed: "✗",
+    not_found: "?",
+    busy: "⚙",
+    pending: "↻",
+    outdated: "⚠",
+    duplicate: "‼",
+    pinned: "★",
+    marked: "☑",
+    sort_ascending: "↑",
+    sort_descending: "↓",
+    bar_filled: "█",
+    bar_empty: "░",
+    receive: "↓",
+    transmit: "↑",
+    sparkline: bar::NINE_LEVELS,
+    spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+};
+
+pub const ASCII: Symbols = Symbols {
+    active: "*",
+    inactive: "o",
+    failed: "X",
+    not_found: "?",
+    busy: "@",
+    pending: "~",
+    outdated: "!",
+    duplicate: "!!",
+    pinned: "+",
+    marked: "[x]",
+    sort_ascending: "^",
+    sort_descending: "v",
+    bar_filled: "#",
+    bar_empty: "-",
+    receive: "v",
+    transmit: "^",
+    sparkline: bar::Set {
+        full: "#",
+        seven_eighths: "#",
+        three_quarters: "=",
+        five_eighths: "=",
+        half: "-",
+        three_eighths: "-",
+        one_quarter: ".",
+        one_eighth: ".",
+        empty: " ",
+    },
+    spinner: &["|", "/", "-", "\\"],
+};
+
+impl Symbols {
+    pub fn get(ascii: bool) -> &'static Symbols {
+        if ascii {
+            &ASCII
+        } else {
+            &UNICODE
+        }
+    }
+
+    pub fn status(&self, status: &RunnerStatus) -> &'static str {
+        match status {
+            RunnerStatus::Active => self.active,
+            RunnerStatus::Inactive => self.inactive,
+            RunnerStatus::Failed => self.failed,
+            RunnerStatus::NotFound => self.not_found,
+        }
+    }
+}
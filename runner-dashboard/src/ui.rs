@@ -1,14 +1,33 @@
-use crate::app::{App, AppMode};
-use crate::runner::RunnerStatus;
+use crate::app::{
+    memory_percent, App, AppMode, ListColumn, ListDensity, LogWindow, Tab, Thresholds,
+    TYPED_CONFIRMATION,
+};
+use crate::fleet;
+use crate::github;
+use crate::runner::{self, LogPriority, Registration, Runner, RunnerStatus, STDERR_HEADING};
+use crate::symbols::Symbols;
+use anyhow::Result;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    backend::TestBackend,
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
-    Frame,
+    widgets::{
+        Block, Borders, Cell, Clear, Paragraph, Row, Sparkline, Table, TableState, Tabs, Wrap,
+    },
+    Frame, Terminal,
 };
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const BAR_WIDTH: usize = 20;
+
+/// Bounds for the CPU/MEM bars in the bottom stats line, which scale with the terminal
+const MIN_STATS_BAR_WIDTH: usize = 5;
+const MAX_STATS_BAR_WIDTH: usize = 50;
+
 const BYTES_TO_GB: f64 = 1024.0 * 1024.0 * 1024.0;
 
 /// Converts bytes to gigabytes.
@@ -16,6 +35,57 @@ fn bytes_to_gb(bytes: u64) -> f64 {
     bytes as f64 / BYTES_TO_GB
 }
 
+/// Formats a duration compactly, e.g. "42s", "3m 5s", "2h 10m".
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Coarse duration for long spans like host uptime, e.g. `4d 3h`.
+fn format_uptime(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = (secs % 86_400) / 3600;
+    let minutes = (secs % 3600) / 60;
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Format a Unix timestamp as `YYYY-MM-DD HH:MM UTC`.
+fn format_utc(unix_secs: u64) -> String {
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60
+    )
+}
+
 /// Returns the color associated with a runner status.
 fn status_color(status: &RunnerStatus) -> Color {
     match status {
@@ -26,33 +96,234 @@ fn status_color(status: &RunnerStatus) -> Color {
     }
 }
 
+/// Glyph set picked by `--ascii` / the `ascii` config key.
+fn symbols(app: &App) -> &'static Symbols {
+    Symbols::get(app.ascii)
+}
+
+/// Status symbol for a runner, with busy runners (executing a job) set apart.
+fn runner_symbol(app: &App, runner: &Runner) -> &'static str {
+    let symbols = symbols(app);
+    if app.pending_action(runner).is_some() {
+        symbols.pending
+    } else if runner.busy {
+        symbols.busy
+    } else {
+        symbols.status(&runner.status)
+    }
+}
+
+fn runner_color(app: &App, runner: &Runner) -> Color {
+    if runner.busy {
+        Color::Magenta
+    } else if app.is_flapping(runner) {
+        FLAPPING_COLOR
+    } else {
+        status_color(&runner.status)
+    }
+}
+
+/// Runners systemd keeps restarting, whatever their momentary status
+const FLAPPING_COLOR: Color = Color::LightRed;
+
+/// Short status word; active runners read as busy or idle.
+fn runner_status_text(app: &App, runner: &Runner) -> &'static str {
+    if let Some(action) = app.pending_action(runner) {
+        return pending_text(action);
+    }
+    match (&runner.status, runner.busy) {
+        (_, true) => "busy",
+        _ if app.is_flapping(runner) => "flapping",
+        (RunnerStatus::Active, false) => "idle",
+        (status, false) => status.as_str(),
+    }
+}
+
+/// What a runner is doing while `action` is in flight, short enough for the list
+fn pending_text(action: &str) -> &'static str {
+    match action {
+        "start" => "starting",
+        "stop" => "stopping",
+        "restart" => "restarting",
+        "install" => "installing",
+        _ => "working",
+    }
+}
+
+/// Smallest terminal the layout works in: header, tab bar, 10 content rows,
+/// stats and status bar, and room for the compact header
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 3 + 1 + 10 + 3 + 1;
+
 pub fn draw(frame: &mut Frame, app: &App) {
-    let chunks = Layout::default()
+    let area = frame.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        draw_too_small(frame, area);
+        return;
+    }
+
+    let chunks = screen_areas(area);
+
+    draw_header(frame, app, chunks[0]);
+    draw_tab_bar(frame, app, chunks[1]);
+
+    let main = chunks[2];
+    match app.mode {
+        AppMode::Help => draw_help(frame, app, main),
+        AppMode::Detail => draw_runner_details(frame, app, main),
+        AppMode::Normal | AppMode::Filter | AppMode::LogSearch => draw_tab(frame, app, main),
+        AppMode::Confirm => {
+            draw_tab(frame, app, main);
+            draw_confirm(frame, app, main);
+        }
+        AppMode::Welcome => {
+            draw_tab(frame, app, main);
+            draw_welcome(frame, app, main);
+        }
+        AppMode::Output => {
+            if app.previous_mode == AppMode::Detail {
+                draw_runner_details(frame, app, main);
+            } else {
+                draw_tab(frame, app, main);
+            }
+            draw_output(frame, app, main);
+        }
+        AppMode::Labels => {
+            if app.previous_mode == AppMode::Detail {
+                draw_runner_details(frame, app, main);
+            } else {
+                draw_tab(frame, app, main);
+            }
+            draw_labels(frame, app, main);
+        }
+    }
+
+    draw_system_stats(frame, app, chunks[3]);
+    draw_status_bar(frame, app, chunks[4]);
+
+    if app.idle && app.dim_when_idle {
+        for cell in frame.buffer_mut().content.iter_mut() {
+            cell.modifier.insert(Modifier::DIM);
+        }
+    }
+}
+
+/// Header, tab bar, main content, system stats and status bar, top to bottom
+fn screen_areas(area: Rect) -> Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Header
+            Constraint::Length(1), // Tab bar
             Constraint::Min(10),   // Main content
             Constraint::Length(3), // System stats
             Constraint::Length(1), // Status bar
         ])
-        .split(frame.area());
+        .split(area)
+}
 
-    draw_header(frame, app, chunks[0]);
+/// Replace the whole UI with a resize hint; the normal layout returns once it fits.
+fn draw_too_small(frame: &mut Frame, area: Rect) {
+    let message = format!(
+        "Terminal too small — need at least {}x{} (now {}x{})",
+        MIN_WIDTH, MIN_HEIGHT, area.width, area.height
+    );
+    // Vertically center; word wrapping may need one more line than a hard split
+    let lines = message.chars().count().div_ceil(area.width.max(1) as usize) + 1;
+    let lines = lines.min(area.height as usize) as u16;
+    let paragraph = Paragraph::new(Span::styled(message, Style::default().fg(Color::Yellow)))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    let top = area.y + (area.height - lines) / 2;
+    frame.render_widget(paragraph, Rect::new(area.x, top, area.width, lines));
+}
 
-    match app.mode {
-        AppMode::Help => draw_help(frame, chunks[1]),
-        AppMode::Logs => draw_logs_view(frame, app, chunks[1]),
-        AppMode::Normal => draw_runners_list(frame, app, chunks[1]),
+fn draw_tab(frame: &mut Frame, app: &App, area: Rect) {
+    match app.tab {
+        Tab::Runners => draw_runners_list(frame, app, area),
+        Tab::Logs => draw_logs_view(frame, app, area),
+        Tab::Stats => draw_stats_view(frame, app, area),
+        Tab::Events => draw_events_view(frame, app, area),
+        Tab::Fleet => draw_fleet_view(frame, app, area),
     }
+}
+
+fn draw_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
+    let titles = Tab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| format!("{} {}", i + 1, tab.title()));
 
-    draw_system_stats(frame, app, chunks[2]);
-    draw_status_bar(frame, app, chunks[3]);
+    let tabs = Tabs::new(titles)
+        .select(app.tab.index())
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        );
+
+    frame.render_widget(tabs, area);
+}
+
+/// Render a single frame into an in-memory buffer and return it as plain text.
+///
+/// Styling is dropped; wide characters occupy their cell and the blank cell after it.
+pub fn render_to_string(app: &App, width: u16, height: u16) -> Result<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|f| draw(f, app))?;
+
+    let buffer = terminal.backend().buffer();
+    let mut output = String::new();
+    for y in 0..height {
+        let mut line = String::new();
+        let mut skip = 0;
+        for x in 0..width {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let symbol = buffer[(x, y)].symbol();
+            skip = Span::raw(symbol).width().saturating_sub(1);
+            line.push_str(symbol);
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    Ok(output)
 }
 
 fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let width = block.inner(area).width as usize;
+
+    let paragraph = Paragraph::new(header_line(app, width))
+        .block(block)
+        .style(Style::default());
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Pick the most detailed header that fits in `width` columns.
+///
+/// Host uptime is dropped first; below that the header collapses to
+/// `RD ●3 ✗1 /12`, so counts are never cut off mid-word.
+fn header_line(app: &App, width: usize) -> Line<'static> {
+    let symbols = symbols(app);
     let (active, failed, total) = app.counts();
+    let failed_color = if failed > 0 {
+        Color::Red
+    } else {
+        Color::DarkGray
+    };
+    let updated = format!(
+        "Last updated: {} ago",
+        format_elapsed(app.last_update.elapsed())
+    );
 
-    let title = vec![
+    let full = vec![
         Span::styled(
             " Runner Dashboard ",
             Style::default()
@@ -61,90 +332,578 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
         ),
         Span::raw(" | "),
         Span::styled(
-            format!("● {} active", active),
+            format!("{} {} active", symbols.active, active),
             Style::default().fg(Color::Green),
         ),
         Span::raw(" | "),
         Span::styled(
-            format!("✗ {} failed", failed),
-            Style::default().fg(if failed > 0 {
-                Color::Red
-            } else {
-                Color::DarkGray
-            }),
+            format!("{} {} failed", symbols.failed, failed),
+            Style::default().fg(failed_color),
         ),
         Span::raw(" | "),
         Span::styled(
             format!("{} total", total),
             Style::default().fg(Color::White),
         ),
+        Span::raw(" | "),
+        Span::styled(updated, Style::default().fg(Color::DarkGray)),
+    ];
+    let host_up = [
+        Span::raw(" | "),
+        Span::styled(
+            format!("Host up: {}", format_uptime(app.system_stats.uptime_secs)),
+            Style::default().fg(Color::DarkGray),
+        ),
     ];
 
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+    let with_host = Line::from(full.iter().cloned().chain(host_up).collect::<Vec<_>>());
+    if with_host.width() <= width {
+        return with_host;
+    }
+    let without_host = Line::from(full);
+    if without_host.width() <= width {
+        return without_host;
+    }
 
-    let paragraph = Paragraph::new(Line::from(title))
-        .block(block)
-        .style(Style::default());
+    Line::from(vec![
+        Span::styled(
+            "RD",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(" {}{}", symbols.active, active),
+            Style::default().fg(Color::Green),
+        ),
+        Span::styled(
+            format!(" {}{}", symbols.failed, failed),
+            Style::default().fg(failed_color),
+        ),
+        Span::styled(format!(" /{}", total), Style::default().fg(Color::White)),
+    ])
+}
 
-    frame.render_widget(paragraph, area);
+/// The runner list and the details pane beside it; the latter is empty while
+/// the pane is hidden.
+fn list_panes(app: &App, area: Rect) -> Rc<[Rect]> {
+    let list_ratio = if app.show_details {
+        app.list_ratio
+    } else {
+        100
+    };
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(list_ratio),
+            Constraint::Percentage(100 - list_ratio),
+        ])
+        .split(area)
 }
 
 fn draw_runners_list(frame: &mut Frame, app: &App, area: Rect) {
-    // Split into runners list and details
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
+    let chunks = list_panes(app, area);
 
     // Runners list
-    let items: Vec<ListItem> = app
-        .runners
+    let mut title = vec![Span::raw(if app.filter.is_empty() {
+        " Runners ".to_string()
+    } else {
+        format!(" Runners /{} ", app.filter)
+    })];
+    // The verbose list shows the sort in its header instead
+    if let Some(column) = app
+        .sort_by
+        .filter(|_| app.list_density != ListDensity::Verbose)
+    {
+        title.push(Span::styled(
+            format!("by {} ", column_heading(app, column)).to_lowercase(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if app.marked_count() > 0 {
+        title.push(Span::styled(
+            format!("{} marked ", app.marked_count()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let mut backlogged: Vec<&str> = app
+        .backlogs
         .iter()
-        .enumerate()
-        .map(|(i, runner)| {
-            let status_style = Style::default().fg(status_color(&runner.status));
+        .filter(|(_, alarm)| alarm.raised)
+        .map(|(repo, _)| repo.as_str())
+        .collect();
+    backlogged.sort_unstable();
+    if !backlogged.is_empty() {
+        title.push(backlog_badge());
+        title.push(Span::styled(
+            format!(" {} ", backlogged.join(", ")),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Blue));
 
-            let selected = i == app.selected;
-            let line_style = if selected {
-                Style::default()
-                    .bg(Color::DarkGray)
-                    .add_modifier(Modifier::BOLD)
-            } else {
+    let visible: Vec<(usize, &Runner)> = app
+        .visible_runners()
+        .into_iter()
+        .map(|i| (i, &app.runners[i]))
+        .collect();
+
+    if visible.is_empty() && !app.filter.is_empty() {
+        let reason = match app.filter_error() {
+            Some(error) => error.to_string(),
+            None => format!("No runner matches '{}'", app.filter),
+        };
+        let message = Paragraph::new(Span::styled(reason, Style::default().fg(Color::Yellow)))
+            .block(block)
+            .wrap(Wrap { trim: true });
+        frame.render_widget(message, chunks[0]);
+    } else if app.list_density == ListDensity::Compact {
+        draw_compact_list(frame, app, &visible, block, chunks[0]);
+    } else {
+        draw_runner_table(frame, app, &visible, block, chunks[0]);
+    }
+
+    // Runner details
+    if app.show_details {
+        draw_runner_details(frame, app, chunks[1]);
+    }
+}
+
+/// Gap between verbose list columns; other layouts use one
+const VERBOSE_COLUMN_SPACING: u16 = 2;
+
+/// Cells and column widths of the runner list, laid out once for drawing and
+/// for finding which header a click landed on
+struct RunnerTable {
+    columns: Vec<ListColumn>,
+    /// Row style and cells, status symbol first
+    rows: Vec<(Style, Vec<Line<'static>>)>,
+    /// Column headings, with the sort indicator; verbose lists only
+    header: Option<Vec<Line<'static>>>,
+    widths: Vec<Constraint>,
+    spacing: u16,
+}
+
+impl RunnerTable {
+    /// One runner per row: the status symbol, then the columns for the density.
+    ///
+    /// Normal and status lists show `repo/name`, the latter with the status text
+    /// against the right border. Verbose lists show `list_columns` under a header.
+    fn new(app: &App, visible: &[(usize, &Runner)]) -> Self {
+        let columns = match app.list_density {
+            ListDensity::Verbose => app.list_columns.clone(),
+            _ => vec![ListColumn::Name],
+        };
+        let with_status = app.list_density == ListDensity::Status;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        let rows: Vec<(Style, Vec<Line>)> = visible
+            .iter()
+            .map(|&(i, runner)| {
+                let line_style = row_style(app, runner, i == app.selected);
+                let mut cells = vec![Line::from(Span::styled(
+                    format!(" {}", runner_symbol(app, runner)),
+                    status_style(app, runner),
+                ))];
+                cells.extend(
+                    columns
+                        .iter()
+                        .map(|&column| column_cell(app, runner, column, &columns, now)),
+                );
+
+                // Row markers follow the name, or the first column without one
+                let markers = row_markers(app, runner);
+                let at = columns
+                    .iter()
+                    .position(|&c| c == ListColumn::Name)
+                    .unwrap_or(0)
+                    + 1;
+                if let Some(cell) = cells.get_mut(at) {
+                    cell.spans.extend(markers);
+                }
+
+                if with_status {
+                    // Kept a space off the border
+                    cells.push(
+                        Line::from(vec![
+                            Span::styled(
+                                runner_status_text(app, runner),
+                                status_style(app, runner),
+                            ),
+                            Span::raw(" "),
+                        ])
+                        .alignment(Alignment::Right),
+                    );
+                }
+                (line_style, cells)
+            })
+            .collect();
+
+        let header = (app.list_density == ListDensity::Verbose).then(|| {
+            std::iter::once(Line::default())
+                .chain(columns.iter().map(|&c| column_heading(app, c)))
+                .collect::<Vec<_>>()
+        });
+        let cell_count = 1 + columns.len() + usize::from(with_status);
+        let mut widths: Vec<Constraint> = (0..cell_count)
+            .map(|n| {
+                let content = rows.iter().map(|(_, cells)| cells[n].width()).max();
+                let heading = header.as_ref().and_then(|h| h.get(n)).map(Line::width);
+                Constraint::Length(content.max(heading).unwrap_or(0) as u16)
+            })
+            .collect();
+        if with_status {
+            // The name takes up the slack so the status lands against the border
+            widths[1] = Constraint::Fill(1);
+        }
+
+        let spacing = if header.is_some() {
+            VERBOSE_COLUMN_SPACING
+        } else {
+            1
+        };
+        Self {
+            columns,
+            rows,
+            header,
+            widths,
+            spacing,
+        }
+    }
+
+    /// The columns' positions across `inner`, the list's area inside its border,
+    /// the same way the table widget lays them out
+    fn column_areas(&self, inner: Rect) -> Rc<[Rect]> {
+        Layout::horizontal(self.widths.clone())
+            .flex(Flex::Start)
+            .spacing(self.spacing)
+            .split(inner)
+    }
+}
+
+/// A column heading, with an arrow when the list is sorted by it
+fn column_heading(app: &App, column: ListColumn) -> Line<'static> {
+    let mut heading = column.heading().to_string();
+    if app.sort_by == Some(column) {
+        let symbols = symbols(app);
+        heading.push(' ');
+        heading.push_str(if app.sort_descending {
+            symbols.sort_descending
+        } else {
+            symbols.sort_ascending
+        });
+    }
+    Line::from(heading)
+}
+
+/// Draw the runner list as a table that scrolls to keep the selection in view,
+/// under a header that stays put.
+fn draw_runner_table(
+    frame: &mut Frame,
+    app: &App,
+    visible: &[(usize, &Runner)],
+    block: Block,
+    area: Rect,
+) {
+    let RunnerTable {
+        rows,
+        header,
+        widths,
+        spacing,
+        ..
+    } = RunnerTable::new(app, visible);
+
+    let position = visible.iter().position(|&(i, _)| i == app.selected);
+    let mut state = TableState::default().with_selected(position);
+    let mut table = Table::new(
+        rows.into_iter()
+            .map(|(style, cells)| Row::new(cells).style(style)),
+        widths,
+    )
+    .block(block)
+    .column_spacing(spacing);
+    if let Some(header) = header {
+        table = table.header(
+            Row::new(header).style(
                 Style::default()
-            };
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+/// The list column whose header is at (`x`, `y`) on a screen of `area`, so a
+/// click there can sort by it. Only the verbose list has a header.
+pub fn sort_column_at(app: &App, area: Rect, x: u16, y: u16) -> Option<ListColumn> {
+    if app.mode != AppMode::Normal
+        || app.tab != Tab::Runners
+        || app.list_density != ListDensity::Verbose
+        || area.width < MIN_WIDTH
+        || area.height < MIN_HEIGHT
+    {
+        return None;
+    }
+    let list = list_panes(app, screen_areas(area)[2])[0];
+    let inner = Block::default().borders(Borders::ALL).inner(list);
+    if y != inner.y {
+        return None;
+    }
+
+    let visible: Vec<(usize, &Runner)> = app
+        .visible_runners()
+        .into_iter()
+        .map(|i| (i, &app.runners[i]))
+        .collect();
+    let table = RunnerTable::new(app, &visible);
+    // The first area is the status symbol's
+    table
+        .column_areas(inner)
+        .iter()
+        .skip(1)
+        .zip(&table.columns)
+        .find(|(cell, _)| x >= cell.x && x < cell.right())
+        .map(|(_, &column)| column)
+}
+
+/// One list cell for `runner`; `columns` is the whole row, for context
+fn column_cell(
+    app: &App,
+    runner: &Runner,
+    column: ListColumn,
+    columns: &[ListColumn],
+    now: u64,
+) -> Line<'static> {
+    let usage = app.runner_usage.get(&runner.path);
+    let text = match column {
+        ListColumn::Status => {
+            return Line::from(Span::styled(
+                runner_status_text(app, runner),
+                status_style(app, runner),
+            ));
+        }
+        ListColumn::Repo => runner.repo.clone(),
+        ListColumn::Name if columns.contains(&ListColumn::Repo) => runner.name.clone(),
+        ListColumn::Name => format!("{}/{}", runner.repo, runner.name),
+        ListColumn::Uptime => usage.map_or_else(
+            || "-".to_string(),
+            |u| format_uptime(now.saturating_sub(u.started)),
+        ),
+        ListColumn::Version => runner.version.clone().unwrap_or_else(|| "-".to_string()),
+        ListColumn::Cpu => usage.map_or_else(|| "-".to_string(), |u| format!("{:.0}%", u.cpu)),
+        ListColumn::Mem => usage.map_or_else(|| "-".to_string(), |u| runner::format_size(u.memory)),
+        ListColumn::Busy => if runner.busy { "yes" } else { "no" }.to_string(),
+    };
+    Line::from(text)
+}
+
+/// Markers after a runner's name: marked, pinned, outdated, shared service
+fn row_markers(app: &App, runner: &Runner) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if app.is_marked(runner) {
+        spans.push(Span::styled(
+            format!(" {}", symbols(app).marked),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if app.is_pinned(runner) {
+        spans.push(Span::styled(
+            format!(" {}", symbols(app).pinned),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    if app.is_outdated(runner) {
+        spans.push(Span::styled(
+            format!(" {}", symbols(app).outdated),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if app.has_duplicate_service(runner) {
+        spans.push(duplicate_marker(app));
+    }
+    spans
+}
+
+/// Selection highlight, or a flash in the status colour just after the status changed
+fn row_style(app: &App, runner: &Runner, selected: bool) -> Style {
+    if app.is_flashing(runner) {
+        let style = status_style(app, runner);
+        if selected {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
+    } else if selected {
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    }
+}
+
+/// Status symbol colour, inverted while the row flashes so it stays readable
+fn status_style(app: &App, runner: &Runner) -> Style {
+    if app.pending_action(runner).is_some() {
+        Style::default().fg(Color::Cyan)
+    } else if app.is_flashing(runner) {
+        Style::default()
+            .fg(Color::Black)
+            .bg(runner_color(app, runner))
+    } else {
+        Style::default().fg(runner_color(app, runner))
+    }
+}
+
+/// Row marker for runners whose service name is shared with another runner
+fn duplicate_marker(app: &App) -> Span<'static> {
+    Span::styled(
+        format!(" {}", symbols(app).duplicate),
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    )
+}
 
-            let content = Line::from(vec![
-                Span::styled(format!(" {} ", runner.status.symbol()), status_style),
-                Span::styled(format!("{}/{}", runner.repo, runner.name), line_style),
-            ]);
+/// Short label used by the compact list, e.g. `acme/3`.
+fn compact_label(runner: &Runner) -> String {
+    format!("{}/{}", runner.repo, runner.number)
+}
+
+/// Pack runners into as many columns as fit, filled row by row.
+fn draw_compact_list(
+    frame: &mut Frame,
+    app: &App,
+    visible: &[(usize, &Runner)],
+    block: Block,
+    area: Rect,
+) {
+    let inner = block.inner(area);
+    let label_width = visible
+        .iter()
+        .map(|(_, r)| compact_label(r).chars().count())
+        .max()
+        .unwrap_or(0);
+    // " ● " prefix, label, and a gap as wide as the duplicate marker
+    let gap = " ".repeat(1 + symbols(app).duplicate.chars().count());
+    let cell_width = label_width + 3 + gap.len();
+    let columns = (inner.width as usize / cell_width).max(1);
 
-            ListItem::new(content).style(line_style)
+    let lines: Vec<Line> = visible
+        .chunks(columns)
+        .map(|runners| {
+            let spans = runners.iter().flat_map(|&(i, runner)| {
+                let mut line_style = row_style(app, runner, i == app.selected);
+                // No room for a marker column, so marked labels are underlined instead
+                if app.is_marked(runner) {
+                    line_style = line_style.add_modifier(Modifier::UNDERLINED);
+                }
+                [
+                    Span::styled(
+                        format!(" {} ", runner_symbol(app, runner)),
+                        status_style(app, runner),
+                    ),
+                    Span::styled(
+                        format!("{:<width$}", compact_label(runner), width = label_width),
+                        line_style,
+                    ),
+                    // The marker takes the place of the gap so columns stay aligned
+                    if app.has_duplicate_service(runner) {
+                        duplicate_marker(app)
+                    } else {
+                        Span::raw(gap.clone())
+                    },
+                ]
+            });
+            Line::from(spans.collect::<Vec<_>>())
         })
         .collect();
 
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .title(" Runners ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Blue)),
-        )
-        .highlight_style(Style::default().bg(Color::DarkGray));
+    // Keep the selected row on screen
+    let position = visible
+        .iter()
+        .position(|&(i, _)| i == app.selected)
+        .unwrap_or(0);
+    let selected_row = position / columns;
+    let scroll = selected_row.saturating_sub(inner.height.saturating_sub(1) as usize);
 
-    frame.render_widget(list, chunks[0]);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((scroll as u16, 0));
+    frame.render_widget(paragraph, area);
+}
 
-    // Runner details
-    draw_runner_details(frame, app, chunks[1]);
+/// A path for display, with the home directory shortened to `~` unless full paths are on.
+fn display_path(app: &App, path: &Path) -> String {
+    if app.full_paths {
+        path.display().to_string()
+    } else {
+        abbreviate_home(path, dirs::home_dir().as_deref())
+    }
+}
+
+fn abbreviate_home(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
 }
 
 fn draw_runner_details(frame: &mut Frame, app: &App, area: Rect) {
-    let details = if let Some(runner) = app.selected_runner() {
-        let color = status_color(&runner.status);
+    let mut details = if let Some(runner) = app.selected_runner() {
+        let color = runner_color(app, runner);
         let display_name = runner.display_name();
-        let status_text = format!("{} {}", runner.status.symbol(), runner.status.as_str());
-        let path_str = runner.path.to_string_lossy().to_string();
+        let status_text = match (&runner.status, runner.busy) {
+            _ if app.pending_action(runner).is_some() => format!(
+                "{} {}...",
+                runner_symbol(app, runner),
+                runner_status_text(app, runner)
+            ),
+            (_, true) => format!("{} running a job", runner_symbol(app, runner)),
+            (status, false) if app.is_flapping(runner) => format!(
+                "{} {}, flapping: systemd keeps restarting it",
+                symbols(app).status(status),
+                status.as_str()
+            ),
+            (RunnerStatus::Active, false) => format!("{} active, idle", runner_symbol(app, runner)),
+            (status, false) => format!("{} {}", symbols(app).status(status), status.as_str()),
+        };
+        let path_str = display_path(app, &runner.path);
+        let (installed_text, installed_color) = if runner.service_installed {
+            ("yes", Color::Green)
+        } else {
+            ("no (press i to install)", Color::Yellow)
+        };
+        let (sudo_text, sudo_color) = if runner.control_requires_sudo() {
+            ("yes", Color::Yellow)
+        } else {
+            ("no", Color::Green)
+        };
+        let newest = app.reference_version();
+        let version_span = match runner.version.as_deref() {
+            Some(version) if app.is_outdated(runner) => Span::styled(
+                format!(
+                    "{} {} Update available ({})",
+                    version,
+                    symbols(app).outdated,
+                    newest.unwrap_or("?")
+                ),
+                Style::default().fg(Color::Red),
+            ),
+            Some(version) if runner.differs_from_version(newest) => Span::styled(
+                format!("{} (newest: {})", version, newest.unwrap_or("?")),
+                Style::default().fg(Color::Yellow),
+            ),
+            Some(version) => Span::raw(version.to_string()),
+            None => Span::styled("unknown", Style::default().fg(Color::DarkGray)),
+        };
 
         vec![
             Line::from(vec![
@@ -159,14 +918,54 @@ fn draw_runner_details(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled("Status: ", Style::default().fg(Color::Cyan)),
                 Span::styled(status_text, Style::default().fg(color)),
             ]),
+            Line::from(vec![
+                Span::styled("State: ", Style::default().fg(Color::Cyan)),
+                service_state_span(runner),
+            ]),
+            Line::from(vec![
+                Span::styled("Restarts: ", Style::default().fg(Color::Cyan)),
+                restarts_span(app, runner),
+            ]),
             Line::from(vec![
                 Span::styled("Service: ", Style::default().fg(Color::Cyan)),
                 Span::raw(runner.service_name.clone()),
+                if app.has_duplicate_service(runner) {
+                    Span::styled(
+                        " (shared with another runner)",
+                        Style::default().fg(Color::Red),
+                    )
+                } else {
+                    Span::raw("")
+                },
+            ]),
+            Line::from(vec![
+                Span::styled("Installed: ", Style::default().fg(Color::Cyan)),
+                Span::styled(installed_text, Style::default().fg(installed_color)),
+            ]),
+            Line::from(vec![
+                Span::styled("Requires sudo: ", Style::default().fg(Color::Cyan)),
+                Span::styled(sudo_text, Style::default().fg(sudo_color)),
+            ]),
+            Line::from(vec![
+                Span::styled("Version: ", Style::default().fg(Color::Cyan)),
+                version_span,
             ]),
             Line::from(vec![
                 Span::styled("Path: ", Style::default().fg(Color::Cyan)),
                 Span::raw(path_str),
             ]),
+            Line::from(vec![
+                Span::styled("GitHub: ", Style::default().fg(Color::Cyan)),
+                registration_span(runner.registration.as_ref()),
+            ]),
+            Line::from(vec![
+                Span::styled("Labels: ", Style::default().fg(Color::Cyan)),
+                labels_span(runner),
+            ]),
+            Line::from(vec![
+                Span::styled("_work: ", Style::default().fg(Color::Cyan)),
+                work_span(runner),
+            ]),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Actions: ",
@@ -175,10 +974,15 @@ fn draw_runner_details(frame: &mut Frame, app: &App, area: Rect) {
             Line::from(vec![Span::raw(
                 "  [s] Start  [x] Stop  [r] Restart  [l] Logs",
             )]),
+            Line::from(vec![Span::raw(
+                "  [i] Install service  [u] Uninstall service  [D] Deregister",
+            )]),
+            Line::from(vec![Span::raw("  [P] Prune _work")]),
         ]
     } else {
         vec![Line::from("No runner selected")]
     };
+    details.extend(recent_activity_lines(app));
 
     let block = Block::default()
         .title(" Details ")
@@ -190,9 +994,110 @@ fn draw_runner_details(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+/// systemd's ActiveState and SubState, highlighted while the unit is in flux
+fn service_state_span(runner: &Runner) -> Span<'static> {
+    match &runner.service_state {
+        Some(state) if state.is_transitional() => {
+            Span::styled(state.describe(), Style::default().fg(Color::Yellow))
+        }
+        Some(state) => Span::raw(state.describe()),
+        None => Span::styled("unknown", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+/// systemd's automatic restart count, with the recent rate once it's rising
+fn restarts_span(app: &App, runner: &Runner) -> Span<'static> {
+    let Some(count) = runner.service_state.as_ref().and_then(|s| s.restarts) else {
+        return Span::styled("unknown", Style::default().fg(Color::DarkGray));
+    };
+    let recent = app.recent_restarts(runner);
+    if recent == 0 {
+        return Span::raw(count.to_string());
+    }
+    let rate = app
+        .restart_rate(runner)
+        .map(|per_hour| format!(", {:.0}/h", per_hour))
+        .unwrap_or_default();
+    let text = format!("{} ({} recently{})", count, recent, rate);
+    let color = if app.is_flapping(runner) {
+        FLAPPING_COLOR
+    } else {
+        Color::Yellow
+    };
+    Span::styled(text, Style::default().fg(color))
+}
+
+/// Number of action log entries shown under the runner details
+const RECENT_ACTIVITY_LINES: usize = 3;
+
+/// The most recent action log entries, newest first.
+fn recent_activity_lines(app: &App) -> Vec<Line<'_>> {
+    if app.action_log.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Recent activity: ",
+            Style::default().fg(Color::Yellow),
+        )]),
+    ];
+    lines.extend(
+        app.action_log
+            .iter()
+            .rev()
+            .take(RECENT_ACTIVITY_LINES)
+            .map(|entry| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("  {:>7} ago  ", format_elapsed(entry.at.elapsed())),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(entry.message.as_str()),
+                ])
+            }),
+    );
+    lines
+}
+
 fn draw_logs_view(frame: &mut Frame, app: &App, area: Rect) {
     let title = if let Some(runner) = app.selected_runner() {
-        format!(" Logs: {} ", runner.display_name())
+        let follow = if app.log_context.is_some() {
+            " (context around match)"
+        } else if app.log_follow {
+            " (following)"
+        } else {
+            ""
+        };
+        let search = if app.log_search.is_empty() {
+            String::new()
+        } else {
+            let matches = app.logs.iter().filter(|l| app.is_log_match(l)).count();
+            format!(" [/{}: {} matches]", app.log_search, matches)
+        };
+        let window = match app.log_window {
+            LogWindow::Tail => String::new(),
+            window => format!(" [{}]", window.as_str()),
+        };
+        let priority = match app.log_priority {
+            LogPriority::All => String::new(),
+            priority => format!(" [{}]", priority.as_str()),
+        };
+        let source = runner
+            .log_source
+            .as_ref()
+            .map(|source| format!(" [{}]", source.describe()))
+            .unwrap_or_default();
+        format!(
+            " Logs: {}{}{}{}{}{} ",
+            runner.display_name(),
+            source,
+            window,
+            priority,
+            search,
+            follow
+        )
     } else {
         " Logs ".to_string()
     };
@@ -200,16 +1105,22 @@ fn draw_logs_view(frame: &mut Frame, app: &App, area: Rect) {
     let logs: Vec<Line> = app
         .logs
         .iter()
+        .enumerate()
         .skip(app.log_scroll)
-        .map(|log| {
+        .map(|(index, log)| {
             let log_lower = log.to_lowercase();
-            let style = if log_lower.contains("error") {
+            let mut style = if log_lower.contains("error") {
                 Style::default().fg(Color::Red)
             } else if log_lower.contains("warn") {
                 Style::default().fg(Color::Yellow)
             } else {
                 Style::default()
             };
+            if app.log_match == Some(index) {
+                style = style.add_modifier(Modifier::REVERSED);
+            } else if app.is_log_match(log) {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
             Line::styled(log.as_str(), style)
         })
         .collect();
@@ -224,83 +1135,1045 @@ fn draw_logs_view(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
-fn draw_help(frame: &mut Frame, area: Rect) {
-    let help_text = vec![
-        Line::from(vec![Span::styled(
-            "Navigation",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  ↑/k      Move up"),
-        Line::from("  ↓/j      Move down"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Actions",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  s        Start selected runner"),
-        Line::from("  x        Stop selected runner"),
-        Line::from("  r        Restart selected runner"),
-        Line::from("  l        Toggle logs view"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "General",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  ?/h      Toggle this help"),
-        Line::from("  q        Quit"),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "In Logs View",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from("  ↑/k      Scroll up"),
-        Line::from("  ↓/j      Scroll down"),
-        Line::from("  l/Esc    Exit logs view"),
+/// Expanded host resources alongside a per-repository breakdown of runner statuses.
+fn draw_stats_view(frame: &mut Frame, app: &App, area: Rect) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let stats = &app.system_stats;
+
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5), // CPU history
+            Constraint::Min(3),    // Per-core usage
+            Constraint::Length(5), // Memory and swap
+        ])
+        .split(columns[0]);
+
+    let cpu_title = format!(" CPU {:.1}% ", stats.cpu_usage);
+    let symbols = symbols(app);
+    draw_history(
+        frame,
+        &cpu_title,
+        &app.cpu_history,
+        Some(100),
+        symbols,
+        left[0],
+    );
+    draw_cores(frame, app, left[1]);
+    draw_memory(frame, app, left[2]);
+
+    let fleet = fleet_lines(app);
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Uptime
+            Constraint::Length(fleet.len() as u16 + 2),
+            Constraint::Length(5), // Load history
+            Constraint::Length(stats.disks.len().max(1) as u16 + 2),
+            Constraint::Min(3), // Network
+        ])
+        .split(columns[1]);
+
+    let host = Line::from(vec![
+        Span::styled("Host up: ", Style::default().fg(Color::Cyan)),
+        Span::raw(format_uptime(stats.uptime_secs)),
+        Span::styled("  Booted: ", Style::default().fg(Color::Cyan)),
+        Span::raw(format_utc(stats.boot_time)),
+    ]);
+    frame.render_widget(Paragraph::new(host).block(stats_block(" Host ")), right[0]);
+    frame.render_widget(
+        Paragraph::new(fleet).block(stats_block(" Fleet ")),
+        right[1],
+    );
+    let load_title = format!(
+        " Load {:.2} {:.2} {:.2} ",
+        stats.load_avg[0], stats.load_avg[1], stats.load_avg[2]
+    );
+    draw_history(
+        frame,
+        &load_title,
+        &app.load_history,
+        None,
+        symbols,
+        right[2],
+    );
+    draw_disks(frame, app, right[3]);
+    draw_networks(frame, app, right[4]);
+}
+
+fn stats_block(title: &str) -> Block<'_> {
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Blue))
+}
+
+/// A sparkline of recent samples, newest on the right.
+fn draw_history(
+    frame: &mut Frame,
+    title: &str,
+    history: &VecDeque<u64>,
+    max: Option<u64>,
+    symbols: &Symbols,
+    area: Rect,
+) {
+    let block = stats_block(title);
+    // Show only as many samples as fit, so the newest is always visible
+    let width = block.inner(area).width as usize;
+    let data: Vec<u64> = history
+        .iter()
+        .skip(history.len().saturating_sub(width))
+        .copied()
+        .collect();
+
+    let mut sparkline = Sparkline::default()
+        .block(block)
+        .data(&data)
+        .bar_set(symbols.sparkline.clone())
+        .style(Style::default().fg(Color::Cyan));
+    if let Some(max) = max {
+        sparkline = sparkline.max(max);
+    }
+    frame.render_widget(sparkline, area);
+}
+
+fn draw_cores(frame: &mut Frame, app: &App, area: Rect) {
+    let stats = &app.system_stats;
+    let lines: Vec<Line> = stats
+        .cpu_cores
+        .iter()
+        .enumerate()
+        .map(|(i, usage)| {
+            Line::from(
+                [
+                    vec![Span::raw(format!("{:>3} ", i))],
+                    usage_bar(app, *usage as f64, BAR_WIDTH, app.cpu_thresholds),
+                    vec![Span::raw(format!(" {:5.1}%", usage))],
+                ]
+                .concat(),
+            )
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).block(stats_block(" Cores ")), area);
+}
+
+fn draw_memory(frame: &mut Frame, app: &App, area: Rect) {
+    let stats = &app.system_stats;
+    let swap_percent = if stats.swap_total > 0 {
+        (stats.swap_used as f64 / stats.swap_total as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    let lines = vec![
+        Line::from(
+            [
+                vec![Span::styled("Used   ", Style::default().fg(Color::Cyan))],
+                usage_bar(app, memory_percent(stats), BAR_WIDTH, app.mem_thresholds),
+                vec![Span::raw(format!(
+                    " {:.1}/{:.1} GB",
+                    bytes_to_gb(stats.memory_used),
+                    bytes_to_gb(stats.memory_total)
+                ))],
+            ]
+            .concat(),
+        ),
+        Line::from(vec![
+            Span::styled("Avail  ", Style::default().fg(Color::Cyan)),
+            Span::raw(format!("{:.1} GB", bytes_to_gb(stats.memory_available))),
+        ]),
+        Line::from(
+            [
+                vec![Span::styled("Swap   ", Style::default().fg(Color::Cyan))],
+                usage_bar(app, swap_percent, BAR_WIDTH, app.mem_thresholds),
+                vec![Span::raw(format!(
+                    " {:.1}/{:.1} GB",
+                    bytes_to_gb(stats.swap_used),
+                    bytes_to_gb(stats.swap_total)
+                ))],
+            ]
+            .concat(),
+        ),
     ];
 
+    frame.render_widget(Paragraph::new(lines).block(stats_block(" Memory ")), area);
+}
+
+fn draw_disks(frame: &mut Frame, app: &App, area: Rect) {
+    let stats = &app.system_stats;
+    let mount_width = stats
+        .disks
+        .iter()
+        .map(|d| d.mount_point.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = stats
+        .disks
+        .iter()
+        .map(|disk| {
+            let percent = if disk.total > 0 {
+                (disk.used as f64 / disk.total as f64) * 100.0
+            } else {
+                0.0
+            };
+            Line::from(
+                [
+                    vec![Span::raw(format!(
+                        "{:<width$} ",
+                        disk.mount_point,
+                        width = mount_width
+                    ))],
+                    usage_bar(app, percent, BAR_WIDTH, app.mem_thresholds),
+                    vec![Span::raw(format!(
+                        " {:.1}/{:.1} GB",
+                        bytes_to_gb(disk.used),
+                        bytes_to_gb(disk.total)
+                    ))],
+                ]
+                .concat(),
+            )
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).block(stats_block(" Disks ")), area);
+}
+
+fn draw_networks(frame: &mut Frame, app: &App, area: Rect) {
+    let stats = &app.system_stats;
+    let symbols = symbols(app);
+    let name_width = stats
+        .networks
+        .iter()
+        .map(|n| n.interface.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = stats
+        .networks
+        .iter()
+        .map(|net| {
+            Line::from(vec![
+                Span::raw(format!("{:<width$}  ", net.interface, width = name_width)),
+                Span::styled(
+                    format!("{} ", symbols.receive),
+                    Style::default().fg(Color::Green),
+                ),
+                Span::raw(format!("{:>10}  ", format_rate(net.rx_bytes_per_sec))),
+                Span::styled(
+                    format!("{} ", symbols.transmit),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!("{:>10}", format_rate(net.tx_bytes_per_sec))),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).block(stats_block(" Network ")), area);
+}
+
+/// Human-readable throughput, e.g. `1.2 MB/s`.
+fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Status counts per repository, in discovery order.
+/// Red marker for a repo whose queued runs have outnumbered its idle runners for a while
+fn backlog_badge() -> Span<'static> {
+    Span::styled(
+        "BACKLOG",
+        Style::default()
+            .fg(Color::White)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+fn fleet_lines(app: &App) -> Vec<Line<'_>> {
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+
+    let mut repos: Vec<(&str, [usize; 4])> = Vec::new();
+    for runner in &app.runners {
+        let column = match runner.status {
+            RunnerStatus::Active => 0,
+            RunnerStatus::Inactive => 1,
+            RunnerStatus::Failed => 2,
+            RunnerStatus::NotFound => 3,
+        };
+        match repos.iter_mut().find(|(repo, _)| *repo == runner.repo) {
+            Some((_, counts)) => counts[column] += 1,
+            None => {
+                let mut counts = [0; 4];
+                counts[column] = 1;
+                repos.push((runner.repo.as_str(), counts));
+            }
+        }
+    }
+
+    let repo_width = repos.iter().map(|(r, _)| r.len()).max().unwrap_or(0).max(4);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:<width$}  {:>6} {:>8} {:>6} {:>9}",
+            "Repo",
+            "active",
+            "inactive",
+            "failed",
+            "not-found",
+            width = repo_width
+        ),
+        heading,
+    ))];
+    for (repo, counts) in repos {
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:<width$}  ", repo, width = repo_width)),
+            Span::styled(
+                format!("{:>6} ", counts[0]),
+                Style::default().fg(status_color(&RunnerStatus::Active)),
+            ),
+            Span::styled(
+                format!("{:>8} ", counts[1]),
+                Style::default().fg(status_color(&RunnerStatus::Inactive)),
+            ),
+            Span::styled(
+                format!("{:>6} ", counts[2]),
+                Style::default().fg(status_color(&RunnerStatus::Failed)),
+            ),
+            Span::styled(
+                format!("{:>9}", counts[3]),
+                Style::default().fg(status_color(&RunnerStatus::NotFound)),
+            ),
+        ]));
+        // On a line of its own, as the counts already fill a narrow column
+        if app.is_backlogged(repo) {
+            let queued = app.queued_runs.get(repo).copied().unwrap_or(0);
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                backlog_badge(),
+                Span::styled(
+                    format!(" {} queued, {} idle", queued, app.idle_runners(repo)),
+                    Style::default().fg(Color::Red),
+                ),
+            ]));
+        }
+    }
+    lines
+}
+
+/// The full action log, newest first.
+fn draw_events_view(frame: &mut Frame, app: &App, area: Rect) {
+    let events: Vec<Line> = if app.action_log.is_empty() {
+        vec![Line::styled(
+            "No events yet",
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        app.action_log
+            .iter()
+            .rev()
+            .map(|entry| {
+                let style = if entry.message.starts_with("Error") {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:>7} ago  ", format_elapsed(entry.at.elapsed())),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(entry.message.as_str(), style),
+                ])
+            })
+            .collect()
+    };
+
     let block = Block::default()
+        .title(" Events ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+
+    frame.render_widget(Paragraph::new(events).block(block), area);
+}
+
+/// Widest a Fleet tab column grows; longer values (label lists) are cut off
+const FLEET_MAX_COLUMN: usize = 28;
+const FLEET_COLUMN_SPACING: u16 = 2;
+
+/// Settings down the side, one column per listed runner from `fleet_scroll` on,
+/// as many as fit. Values that differ from the fleet's majority are red.
+fn draw_fleet_view(frame: &mut Frame, app: &App, area: Rect) {
+    let runners: Vec<&Runner> = app
+        .visible_runners()
+        .into_iter()
+        .map(|i| &app.runners[i])
+        .collect();
+    let rows = fleet::compare(&runners, &app.agent_settings);
+    let drifting = rows.iter().filter(|row| row.drift_count() > 0).count();
+    let title = match drifting {
+        0 => " Fleet: no drift ".to_string(),
+        1 => " Fleet: 1 setting differs ".to_string(),
+        n => format!(" Fleet: {} settings differ ", n),
+    };
+    let mut block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(if drifting > 0 {
+            Color::Yellow
+        } else {
+            Color::Magenta
+        }));
+    if runners.is_empty() {
+        let empty = Paragraph::new(Line::styled(
+            "No runners to compare",
+            Style::default().fg(Color::DarkGray),
+        ));
+        frame.render_widget(empty.block(block), area);
+        return;
+    }
+
+    let inner = block.inner(area);
+    let label_width = rows.iter().map(|row| row.setting.len()).max().unwrap_or(0) as u16;
+    let first = app.fleet_scroll.min(runners.len() - 1);
+    let mut widths = vec![Constraint::Length(label_width)];
+    let mut used = label_width;
+    let mut shown = 0;
+    for (index, runner) in runners.iter().enumerate().skip(first) {
+        let width = rows
+            .iter()
+            .filter_map(|row| row.values[index].as_deref())
+            .chain([runner.display_name().as_str()])
+            .map(|value| value.chars().count())
+            .max()
+            .unwrap_or(1)
+            .min(FLEET_MAX_COLUMN) as u16;
+        // Always show one runner, even if it gets cut off
+        if shown > 0 && used + FLEET_COLUMN_SPACING + width > inner.width {
+            break;
+        }
+        used += FLEET_COLUMN_SPACING + width;
+        widths.push(Constraint::Length(width));
+        shown += 1;
+    }
+    let columns = first..first + shown;
+    if shown < runners.len() {
+        block = block.title(
+            Line::from(format!(
+                " runners {}-{} of {} (←/→) ",
+                first + 1,
+                first + shown,
+                runners.len()
+            ))
+            .alignment(Alignment::Right),
+        );
+    }
+
+    let header = Row::new(
+        std::iter::once(Cell::from("")).chain(
+            runners[columns.clone()]
+                .iter()
+                .map(|r| Cell::from(r.display_name())),
+        ),
+    )
+    .style(
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    let table_rows = rows.iter().map(|row| {
+        let cells = columns
+            .clone()
+            .map(|index| match row.values[index].as_deref() {
+                None => Cell::from(Span::styled("?", Style::default().fg(Color::DarkGray))),
+                Some(value) if row.differs(index) => Cell::from(Span::styled(
+                    value.to_string(),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )),
+                Some(value) => Cell::from(value.to_string()),
+            });
+        Row::new(
+            std::iter::once(Cell::from(Span::styled(
+                row.setting,
+                Style::default().fg(Color::Cyan),
+            )))
+            .chain(cells),
+        )
+    });
+    let table = Table::new(table_rows, widths)
+        .header(header)
+        .block(block)
+        .column_spacing(FLEET_COLUMN_SPACING);
+    frame.render_widget(table, area);
+}
+
+/// Help screen contents as (heading, lines) sections, in their default order.
+const HELP_SECTIONS: &[(&str, &[&str])] = &[
+    (
+        "Navigation",
+        &[
+            "  ↑/k      Move up",
+            "  ↓/j      Move down",
+            "  ]/[      Next/previous failed or missing runner",
+            "  *        Pin/unpin the selected runner at the top",
+            "  Space    Mark/unmark it; s/x/r then act on all marked (Esc clears)",
+            "  Enter/d  Fullscreen details (Esc to return)",
+            "  Tab/S-Tab, 1-5  Switch tabs",
+            "  /        Filter runners (Enter keeps, Esc clears)",
+        ],
+    ),
+    (
+        "Actions",
+        &[
+            "  s        Start selected runner",
+            "  x        Stop selected runner",
+            "  r        Restart selected runner",
+            "  R        Restart all failed runners in the list, in waves",
+            "  i        Install service for selected runner",
+            "  u        Uninstall service for selected runner",
+            "  D        Deregister it from GitHub (config.sh remove)",
+            "  P        Empty its work folder (_work) once it is stopped",
+            "  T        Test the connection to its service backend",
+            "  C        Show its .runner config (secrets redacted)",
+            "  g        Check its registration with GitHub",
+            "  L        Add or remove its GitHub labels",
+            "  l        Toggle logs view",
+            "  X        Export runner list to CSV",
+            "  m        Cycle list layout (normal/status/compact/verbose)",
+            "  S        Sort by the next column (or click its header)",
+            "  I        Invert the sort order",
+            "  </>      Narrow/widen the runner list",
+            "  |        Hide/show the details pane",
+            "  O        Open runner directory in the file manager",
+            "  Ctrl-O   Shell in runner directory (exit to return)",
+            "  F5       Refresh now (also Ctrl-R)",
+        ],
+    ),
+    (
+        "General",
+        &[
+            "  ?/h      Toggle this help",
+            "  Ctrl-Z   Suspend to a shell (or suspend_command)",
+            "  q        Quit",
+        ],
+    ),
+    (
+        "In Logs Tab",
+        &[
+            "  ↑/k      Scroll up",
+            "  ↓/j      Scroll down",
+            "  r        Reload logs now",
+            "  t        Show last lines / 5m / 1h / today",
+            "  p        Show all / warnings and up / errors only",
+            "  /        Search the logs; n/N next/previous match",
+            "  e        Fetch more context around the match (again: live logs)",
+            "  l/Esc    Back to runners",
+        ],
+    ),
+    (
+        "In Fleet Tab",
+        &[
+            "  ←/→      Scroll through the runners",
+            "  r        Re-read runner settings",
+            "  Esc      Back to runners",
+        ],
+    ),
+    (
+        "In Details View",
+        &[
+            "  ↑/k ↓/j  Previous/next runner",
+            "  s/x/r/i  Start, stop, restart or install it",
+            "  T        Test the connection to its service backend",
+            "  C        Show its .runner config (secrets redacted)",
+            "  g        Check its registration with GitHub",
+            "  L        Add or remove its GitHub labels",
+            "  P        Prune its work folder",
+            "  d/Esc    Back to the list",
+        ],
+    ),
+    (
+        "On This Screen",
+        &[
+            "  ↑↓/jk    Scroll",
+            "  PgUp/PgDn  Scroll a page",
+            "  Esc/q/?/h  Close",
+        ],
+    ),
+];
+
+/// Number of lines on the help screen, for clamping its scroll position.
+pub fn help_line_count() -> usize {
+    help_lines(None).len()
+}
+
+/// The help section for whatever was on screen when help was opened.
+fn current_help_section(app: &App) -> Option<&'static str> {
+    if app.previous_mode == AppMode::Detail {
+        return Some("In Details View");
+    }
+    match app.tab {
+        Tab::Runners => Some("Actions"),
+        Tab::Logs => Some("In Logs Tab"),
+        Tab::Fleet => Some("In Fleet Tab"),
+        Tab::Stats | Tab::Events => None,
+    }
+}
+
+/// Help lines with the `current` section moved to the top and marked.
+fn help_lines(current: Option<&str>) -> Vec<Line<'static>> {
+    let mut sections: Vec<_> = HELP_SECTIONS.iter().collect();
+    if let Some(position) = sections
+        .iter()
+        .position(|(title, _)| Some(*title) == current)
+    {
+        let section = sections.remove(position);
+        sections.insert(0, section);
+    }
+
+    let mut lines = Vec::new();
+    for (i, (title, keys)) in sections.into_iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::from(""));
+        }
+        let heading = Style::default().add_modifier(Modifier::BOLD);
+        lines.push(if Some(*title) == current {
+            Line::from(vec![
+                Span::styled(*title, heading.fg(Color::Yellow)),
+                Span::styled(" (current view)", Style::default().fg(Color::DarkGray)),
+            ])
+        } else {
+            Line::from(Span::styled(*title, heading.fg(Color::Cyan)))
+        });
+        lines.extend(keys.iter().map(|key| Line::from(*key)));
+    }
+    lines
+}
+
+fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
+    let help_text = help_lines(current_help_section(app));
+    let total = help_text.len();
+    let visible = area.height.saturating_sub(2) as usize;
+    let scroll = app.help_scroll.min(total.saturating_sub(1));
+
+    // Say where we are once the help no longer fits
+    let mut block = Block::default()
         .title(" Help ")
+        .title_bottom(Line::from(" Esc/q to close ").alignment(Alignment::Right))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Yellow));
+    if scroll > 0 || total > visible {
+        let last = (scroll + visible).min(total);
+        block = block.title(
+            Line::from(format!(" {}-{} of {} ", scroll + 1, last, total))
+                .alignment(Alignment::Right),
+        );
+    }
 
-    let paragraph = Paragraph::new(help_text).block(block);
+    let paragraph = Paragraph::new(help_text)
+        .block(block)
+        .scroll((scroll as u16, 0));
 
     frame.render_widget(paragraph, area);
 }
 
-fn draw_system_stats(frame: &mut Frame, app: &App, area: Rect) {
-    let stats = &app.system_stats;
+/// The runner's GitHub labels, once a registration check or edit has fetched them.
+fn labels_span(runner: &Runner) -> Span<'static> {
+    match runner.labels.as_deref() {
+        None => Span::styled(
+            "not fetched (press g)",
+            Style::default().fg(Color::DarkGray),
+        ),
+        Some([]) => Span::styled("none", Style::default().fg(Color::DarkGray)),
+        Some(labels) => Span::raw(labels.join(", ")),
+    }
+}
+
+/// The last registration check, colored by how worrying it is.
+fn registration_span(registration: Option<&Registration>) -> Span<'static> {
+    let color = match registration {
+        None => {
+            return Span::styled(
+                "not checked (press g)",
+                Style::default().fg(Color::DarkGray),
+            )
+        }
+        Some(Registration::Ok { .. }) => Color::Green,
+        Some(Registration::Unknown(_)) => Color::Yellow,
+        Some(Registration::NotFound | Registration::Deauthorized) => Color::Red,
+    };
+    let text = registration.map(Registration::describe).unwrap_or_default();
+    Span::styled(text, Style::default().fg(color))
+}
 
-    let cpu_bar = create_bar(stats.cpu_usage as f64, 100.0, BAR_WIDTH);
-    let mem_percent = if stats.memory_total > 0 {
-        (stats.memory_used as f64 / stats.memory_total as f64) * 100.0
+/// Job directories left in the work folder at the last scan.
+fn work_span(runner: &Runner) -> Span<'static> {
+    let Some(work) = runner.work.as_ref() else {
+        return Span::styled("scanning...", Style::default().fg(Color::DarkGray));
+    };
+    if work.job_dirs == 0 {
+        return Span::styled("clean", Style::default().fg(Color::Green));
+    }
+    let jobs = if work.job_dirs == 1 { "job" } else { "jobs" };
+    // The running job's directory is expected while busy
+    let (state, style) = if runner.busy {
+        ("", Style::default())
     } else {
-        0.0
+        ("stale ", Style::default().fg(Color::Yellow))
+    };
+    let text = format!(
+        "{} {}{}, {:.1} GB",
+        work.job_dirs,
+        state,
+        jobs,
+        bytes_to_gb(work.bytes)
+    );
+    Span::styled(text, style)
+}
+
+/// Color a line of `systemctl status`/`svc.sh`/`launchctl` output by what it says.
+fn output_line_style(line: &str) -> Style {
+    let trimmed = line.trim_start();
+    let lower = line.to_lowercase();
+    if trimmed.starts_with("$ ") {
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD)
+    } else if trimmed == STDERR_HEADING || (trimmed.starts_with('(') && trimmed.ends_with(')')) {
+        Style::default().fg(Color::DarkGray)
+    } else if trimmed.starts_with("Active: active") {
+        Style::default().fg(Color::Green)
+    } else if trimmed.starts_with("Active: inactive") {
+        Style::default().fg(Color::Yellow)
+    } else if [
+        "error",
+        "fail",
+        "denied",
+        "not found",
+        "could not",
+        "password",
+    ]
+    .iter()
+    .any(|word| lower.contains(word))
+    {
+        Style::default().fg(Color::Red)
+    } else if lower.contains("warn") {
+        Style::default().fg(Color::Yellow)
+    } else if trimmed.starts_with(['●', '○', '×']) {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    }
+}
+
+/// Overlay with the output of a command, its verdict pinned above it.
+fn draw_output(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(output) = &app.output else {
+        return;
     };
-    let mem_bar = create_bar(mem_percent, 100.0, BAR_WIDTH);
 
-    let mem_used_gb = bytes_to_gb(stats.memory_used);
-    let mem_total_gb = bytes_to_gb(stats.memory_total);
+    let popup = centered_rect(90, area.height.saturating_sub(2), area);
+    let total = output.lines.len();
+    let visible = popup.height.saturating_sub(4) as usize;
+    let scroll = output.scroll.min(total.saturating_sub(1));
 
-    let content = Line::from(vec![
-        Span::styled(" CPU: ", Style::default().fg(Color::Cyan)),
-        Span::styled(
-            cpu_bar,
-            Style::default().fg(cpu_color(stats.cpu_usage as f64)),
+    let color = if output.ok { Color::Green } else { Color::Red };
+    let mut block = Block::default()
+        .title(output.title.as_str())
+        .title_bottom(Line::from(" Esc/q to close ").alignment(Alignment::Right))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+    if scroll > 0 || total > visible {
+        let last = (scroll + visible).min(total);
+        block = block.title(
+            Line::from(format!(" {}-{} of {} ", scroll + 1, last, total))
+                .alignment(Alignment::Right),
+        );
+    }
+
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+    let summary = Line::from(Span::styled(
+        output.summary.as_str(),
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    ));
+    frame.render_widget(Paragraph::new(summary), chunks[0]);
+
+    let lines: Vec<Line> = output
+        .lines
+        .iter()
+        .map(|line| Line::styled(line.as_str(), output_line_style(line)))
+        .collect();
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), chunks[1]);
+}
+
+/// Label edit input over the current view: the runner's labels, what's typed,
+/// and why it can't be sent, if it can't.
+fn draw_labels(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(runner) = app.selected_runner() else {
+        return;
+    };
+    let popup = centered_rect(60, 8, area);
+    let hint = Style::default().fg(Color::DarkGray);
+    let text = vec![
+        Line::from(vec![
+            Span::styled("Labels: ", Style::default().fg(Color::Cyan)),
+            labels_span(runner),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("> "),
+            Span::styled(
+                format!("{}_", app.label_input),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        match app.label_error.as_deref() {
+            Some(error) => Line::styled(error, Style::default().fg(Color::Red)),
+            None => Line::styled("label adds it, -label removes it", hint),
+        },
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Apply  "),
+            Span::styled("[Esc]", hint),
+            Span::raw(" Cancel"),
+        ]),
+    ];
+    let block = Block::default()
+        .title(format!(" Labels: {} ", runner.display_name()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Affected runners listed at once in a bulk action's confirmation
+const CONFIRM_TARGET_ROWS: usize = 10;
+
+fn draw_confirm(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(pending) = &app.pending_confirm else {
+        return;
+    };
+
+    // Bulk actions list the runners they will hit, a screenful at a time
+    let targets = pending.targets.as_deref().unwrap_or_default();
+    let shown = targets.len().min(CONFIRM_TARGET_ROWS);
+    let extra = if targets.is_empty() { 0 } else { shown + 2 };
+    let popup = centered_rect(60, 7 + extra as u16, area);
+
+    let (title, color, keys) = match &pending.typed {
+        // Aborting a running job or changing GitHub: make the user spell it out
+        Some(typed) => (
+            if pending.word == TYPED_CONFIRMATION {
+                " Job in progress "
+            } else {
+                " Cannot be undone "
+            },
+            Color::Red,
+            Line::from(vec![
+                Span::raw(format!("Type '{}' and press Enter: ", pending.word)),
+                Span::styled(
+                    format!("{}_", typed),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("  [Esc] Cancel", Style::default().fg(Color::DarkGray)),
+            ]),
         ),
-        Span::raw(format!(" {:5.1}%", stats.cpu_usage)),
+        None => (
+            " Confirm ",
+            Color::Yellow,
+            Line::from(vec![
+                Span::styled("[y]", Style::default().fg(Color::Green)),
+                Span::raw(" Confirm  "),
+                Span::styled("[any other key]", Style::default().fg(Color::DarkGray)),
+                Span::raw(" Cancel"),
+            ]),
+        ),
+    };
+    let mut text = vec![Line::from(pending.prompt.as_str()), Line::from("")];
+    if !targets.is_empty() {
+        let first = pending.scroll.min(targets.len() - shown);
+        let heading = if targets.len() > shown {
+            format!(
+                "Affects {}-{} of {} runners (↑/↓ to scroll):",
+                first + 1,
+                first + shown,
+                targets.len()
+            )
+        } else {
+            format!("Affects {} runners:", targets.len())
+        };
+        text.push(Line::styled(heading, Style::default().fg(Color::Cyan)));
+        for runner in targets[first..first + shown]
+            .iter()
+            .filter_map(|&i| app.runners.get(i))
+        {
+            text.push(Line::from(vec![
+                Span::styled(
+                    format!("  {} ", runner_symbol(app, runner)),
+                    Style::default().fg(runner_color(app, runner)),
+                ),
+                Span::raw(format!("{}/{}", runner.repo, runner.name)),
+                Span::styled(
+                    format!("  {}", runner_status_text(app, runner)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+        }
+        text.push(Line::from(""));
+    }
+    text.push(keys);
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// First-run overlay: what was found, where, and the keys to get started.
+fn draw_welcome(frame: &mut Frame, app: &App, area: Rect) {
+    let heading = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let dir = if app.runner_roots.is_empty() {
+        runner::runners_dir()
+            .map(|dir| display_path(app, &dir))
+            .unwrap_or_else(|_| "~/action-runners".to_string())
+    } else {
+        app.runner_roots
+            .iter()
+            .map(|dir| display_path(app, dir))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let repos: HashSet<&str> = app.runners.iter().map(|r| r.repo.as_str()).collect();
+
+    let found = if app.runners.is_empty() {
+        format!(
+            "No runners found in {}. Set some up with ./setup_runners.sh.",
+            dir
+        )
+    } else {
+        format!(
+            "Found {} runners in {} repositories under {}.",
+            app.runners.len(),
+            repos.len(),
+            dir
+        )
+    };
+    let token = if github::token().is_some() {
+        "GITHUB_TOKEN is set, so runner versions are checked against the latest release."
+    } else {
+        "Set GITHUB_TOKEN to also check runner versions against the latest release."
+    };
+
+    let text = vec![
+        Line::from(Span::styled("Welcome to Runner Dashboard", heading)),
+        Line::from(""),
+        Line::from(found),
+        Line::from(""),
+        Line::from(Span::styled("Getting around", heading)),
+        Line::from("  j/k      Select a runner"),
+        Line::from("  s/x/r    Start, stop or restart it"),
+        Line::from("  Enter    Details        l  Logs"),
+        Line::from("  Tab      Switch tabs    /  Filter"),
+        Line::from("  ?        All keys       q  Quit"),
+        Line::from(""),
+        Line::from(token),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Press any key to start. This won't be shown again.",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let popup = centered_rect(70, text.len() as u16 + 4, area);
+    let block = Block::default()
+        .title(" Welcome ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Flashing title naming the resources that have been over their alert level.
+fn alarm_title(app: &App) -> Span<'static> {
+    let mut names = Vec::new();
+    if app.cpu_alarm.raised {
+        names.push("CPU");
+    }
+    if app.mem_alarm.raised {
+        names.push("MEM");
+    }
+    // Alternate every second; the clock tick redraws often enough to animate it
+    let since = [app.cpu_alarm.over_since, app.mem_alarm.over_since]
+        .into_iter()
+        .flatten()
+        .min();
+    let flash = since.is_some_and(|since| since.elapsed().as_secs() % 2 == 0);
+    let style = if flash {
+        Style::default().bg(Color::Red).fg(Color::White)
+    } else {
+        Style::default().fg(Color::Red)
+    };
+    Span::styled(
+        format!(" {} HIGH ", names.join(" + ")),
+        style.add_modifier(Modifier::BOLD),
+    )
+}
+
+/// The CPU / MEM / Load line, with `bar_width`-wide bars or none at all.
+fn system_stats_line(app: &App, bar_width: Option<usize>) -> Line<'static> {
+    let stats = &app.system_stats;
+    let mem_percent = memory_percent(stats);
+    let bar = |percent: f64, thresholds: Thresholds| match bar_width {
+        Some(width) => {
+            let mut spans = usage_bar(app, percent, width, thresholds);
+            spans.push(Span::raw(" "));
+            spans
+        }
+        None => Vec::new(),
+    };
+
+    let mut spans = vec![Span::styled(" CPU: ", Style::default().fg(Color::Cyan))];
+    spans.extend(bar(stats.cpu_usage as f64, app.cpu_thresholds));
+    spans.extend([
+        Span::raw(format!("{:5.1}%", stats.cpu_usage)),
         Span::raw("  |  "),
         Span::styled("MEM: ", Style::default().fg(Color::Cyan)),
-        Span::styled(mem_bar, Style::default().fg(mem_color(mem_percent))),
-        Span::raw(format!(" {:.1}/{:.1} GB", mem_used_gb, mem_total_gb)),
+    ]);
+    spans.extend(bar(mem_percent, app.mem_thresholds));
+    spans.extend([
+        Span::raw(format!(
+            "{:.1}/{:.1} GB",
+            bytes_to_gb(stats.memory_used),
+            bytes_to_gb(stats.memory_total)
+        )),
         Span::raw("  |  "),
         Span::styled("Load: ", Style::default().fg(Color::Cyan)),
         Span::raw(format!(
@@ -308,10 +2181,43 @@ fn draw_system_stats(frame: &mut Frame, app: &App, area: Rect) {
             stats.load_avg[0], stats.load_avg[1], stats.load_avg[2]
         )),
     ]);
+    if let Some(jobs) = app.headroom() {
+        spans.extend([
+            Span::raw("  |  "),
+            Span::styled("Est. headroom: ", Style::default().fg(Color::Cyan)),
+            Span::raw(format!("~{} job{}", jobs, if jobs == 1 { "" } else { "s" })),
+        ]);
+    }
+    Line::from(spans)
+}
 
-    let block = Block::default()
+/// Returns a rect of the given width percentage and fixed height centered in `area`.
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+fn draw_system_stats(frame: &mut Frame, app: &App, area: Rect) {
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::DarkGray));
+    if app.alarm_raised() {
+        block = block
+            .title(alarm_title(app))
+            .border_style(Style::default().fg(Color::Red));
+    }
+
+    // Give the CPU and MEM bars whatever the labels and values leave over
+    let fixed = system_stats_line(app, None).width();
+    let spare = (block.inner(area).width as usize).saturating_sub(fixed);
+    let bar_width = (spare / 2).saturating_sub(3).min(MAX_STATS_BAR_WIDTH);
+    let content = system_stats_line(app, (bar_width >= MIN_STATS_BAR_WIDTH).then_some(bar_width));
 
     let paragraph = Paragraph::new(content).block(block);
 
@@ -319,12 +2225,56 @@ fn draw_system_stats(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
-    let message = app.status_message.as_deref().unwrap_or("");
+    let warning = app
+        .duplicate_service_warning()
+        .or_else(|| app.missing_tools_warning());
+    let message = if app.mode == AppMode::Filter {
+        Span::raw(format!("/{}_", app.filter))
+    } else if app.mode == AppMode::LogSearch {
+        Span::raw(format!("/{}_", app.log_search))
+    } else if let Some(message) = app.status_message.as_deref() {
+        Span::raw(message)
+    } else if let Some(warning) = warning.as_deref() {
+        Span::styled(warning, Style::default().fg(Color::Red))
+    } else {
+        Span::raw("")
+    };
 
     let mode_text = match app.mode {
+        _ if app.idle => "IDLE",
+        AppMode::Normal if app.tab == Tab::Logs => "LOGS",
         AppMode::Normal => "NORMAL",
-        AppMode::Logs => "LOGS",
         AppMode::Help => "HELP",
+        AppMode::Confirm => "CONFIRM",
+        AppMode::Detail => "DETAIL",
+        AppMode::Filter => "FILTER",
+        AppMode::LogSearch => "SEARCH",
+        AppMode::Welcome => "WELCOME",
+        AppMode::Output => "OUTPUT",
+        AppMode::Labels => "LABELS",
+    };
+
+    if app.is_refresh_stalled() {
+        let banner = Paragraph::new(Line::from(Span::styled(
+            format!(
+                " {} Refresh stalled — worker may be blocked (no update for {}) ",
+                symbols(app).outdated,
+                format_elapsed(app.last_update.elapsed())
+            ),
+            Style::default()
+                .bg(Color::Red)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )));
+        frame.render_widget(banner, area);
+        return;
+    }
+
+    let spinner = if app.refreshing {
+        let frames = symbols(app).spinner;
+        format!("{} ", frames[app.spinner_frame() % frames.len()])
+    } else {
+        "  ".to_string()
     };
 
     let content = Line::from(vec![
@@ -333,7 +2283,8 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Style::default().bg(Color::Blue).fg(Color::White),
         ),
         Span::raw(" "),
-        Span::raw(message),
+        Span::styled(spinner, Style::default().fg(Color::Cyan)),
+        message,
         Span::raw("  "),
         Span::styled(" ?:help q:quit ", Style::default().fg(Color::DarkGray)),
     ]);
@@ -342,10 +2293,66 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
-fn create_bar(value: f64, max: f64, width: usize) -> String {
+fn create_bar(value: f64, max: f64, width: usize, symbols: &Symbols) -> String {
     let filled = ((value / max) * width as f64).round() as usize;
     let empty = width.saturating_sub(filled);
-    format!("[{}{}]", "█".repeat(filled), "░".repeat(empty))
+    format!(
+        "[{}{}]",
+        symbols.bar_filled.repeat(filled),
+        symbols.bar_empty.repeat(empty)
+    )
+}
+
+/// A `[###--]` bar for `percent`, as spans.
+///
+/// With gradient bars each filled cell is colored for the level it stands for,
+/// green at 0% through yellow at the warn threshold to red at the alert one, so a
+/// bar creeping toward a threshold shows before it crosses it. ASCII mode (or
+/// `gradient_bars = false`) colors the whole bar by the threshold it is over.
+fn usage_bar(app: &App, percent: f64, width: usize, thresholds: Thresholds) -> Vec<Span<'static>> {
+    let symbols = symbols(app);
+    if app.ascii || !app.gradient_bars {
+        let color = usage_color(percent, thresholds.warn, thresholds.alert);
+        return vec![Span::styled(
+            create_bar(percent, 100.0, width, symbols),
+            Style::default().fg(color),
+        )];
+    }
+
+    let filled = ((percent / 100.0) * width as f64).round() as usize;
+    let filled = filled.min(width);
+    let mut spans = vec![Span::raw("[")];
+    spans.extend((0..filled).map(|cell| {
+        let level = (cell as f64 + 0.5) / width as f64 * 100.0;
+        Span::styled(
+            symbols.bar_filled,
+            Style::default().fg(gradient_color(level, thresholds)),
+        )
+    }));
+    spans.push(Span::styled(
+        symbols.bar_empty.repeat(width - filled),
+        Style::default().fg(Color::DarkGray),
+    ));
+    spans.push(Span::raw("]"));
+    spans
+}
+
+/// Green at 0%, blending to yellow at `warn` and to red at `alert` and above.
+fn gradient_color(level: f64, thresholds: Thresholds) -> Color {
+    const GREEN: (f64, f64, f64) = (0.0, 200.0, 0.0);
+    const YELLOW: (f64, f64, f64) = (230.0, 200.0, 0.0);
+    const RED: (f64, f64, f64) = (220.0, 0.0, 0.0);
+    let blend = |from: (f64, f64, f64), to: (f64, f64, f64), t: f64| {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+        Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    };
+    if level <= thresholds.warn {
+        blend(GREEN, YELLOW, level / thresholds.warn.max(1.0))
+    } else {
+        let span = (thresholds.alert - thresholds.warn).max(1.0);
+        blend(YELLOW, RED, (level - thresholds.warn) / span)
+    }
 }
 
 /// Returns a color based on percentage usage and thresholds.
@@ -360,10 +2367,774 @@ fn usage_color(percent: f64, medium_threshold: f64, high_threshold: f64) -> Colo
     }
 }
 
-fn cpu_color(percent: f64) -> Color {
-    usage_color(percent, 50.0, 80.0)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Alarm, DiskUsage, NetworkRate, OutputView, PendingConfirm, SystemStats};
+    use crate::runner::test_runner;
+    use ratatui::buffer::Buffer;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+
+    const WIDTH: u16 = 120;
+    const HEIGHT: u16 = 40;
+
+    /// First row of runner list items: below the 3-row header, the tab bar and the list border
+    const FIRST_ITEM_ROW: u16 = 5;
+
+    fn test_app() -> App {
+        let stats = SystemStats {
+            cpu_usage: 42.0,
+            cpu_cores: vec![40.0, 44.0],
+            memory_used: 4 * 1024 * 1024 * 1024,
+            memory_total: 16 * 1024 * 1024 * 1024,
+            load_avg: [1.5, 1.0, 0.5],
+            ..SystemStats::default()
+        };
+        let (command_tx, _) = mpsc::channel();
+        let (_, response_rx) = mpsc::channel();
+        let mut app = App::new_with(
+            vec![
+                test_runner("acme", 1),
+                Runner {
+                    status: RunnerStatus::Inactive,
+                    ..test_runner("acme", 2)
+                },
+                Runner {
+                    status: RunnerStatus::Failed,
+                    ..test_runner("widgets-frontend", 1)
+                },
+                Runner {
+                    status: RunnerStatus::NotFound,
+                    ..test_runner("widgets-frontend", 2)
+                },
+            ],
+            command_tx,
+            response_rx,
+            stats,
+        );
+        for runner in &mut app.runners {
+            runner.version = Some("2.311.0".to_string());
+        }
+        app
+    }
+
+    fn render(app: &App) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn row_text(buffer: &Buffer, y: u16) -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect()
+    }
+
+    #[test]
+    fn render_to_string_includes_header_and_runners() {
+        let screen = render_to_string(&test_app(), WIDTH, HEIGHT).unwrap();
+
+        assert_eq!(screen.lines().count(), HEIGHT as usize);
+        assert!(screen.contains("Runner Dashboard"));
+        assert!(screen.contains("acme/runner-1"));
+        assert!(screen.contains("widgets-frontend/runner-2"));
+    }
+
+    #[test]
+    fn header_is_three_rows_with_counts() {
+        let buffer = render(&test_app());
+
+        assert!(row_text(&buffer, 0).starts_with('┌'));
+        let header = row_text(&buffer, 1);
+        assert!(header.contains("Runner Dashboard"));
+        assert!(header.contains("● 1 active"));
+        assert!(header.contains("✗ 1 failed"));
+        assert!(header.contains("4 total"));
+        assert!(row_text(&buffer, 2).starts_with('└'));
+        assert!(row_text(&buffer, 3).contains("1 Runners"));
+        assert!(row_text(&buffer, 4).starts_with("┌ Runners"));
+    }
+
+    #[test]
+    fn header_adapts_to_terminal_width() {
+        let app = test_app();
+        let header_at = |width: u16| {
+            let screen = render_to_string(&app, width, HEIGHT).unwrap();
+            screen.lines().nth(1).unwrap().to_string()
+        };
+
+        let wide = header_at(200);
+        assert!(wide.contains("Runner Dashboard"));
+        assert!(wide.contains("Host up:"));
+
+        let medium = header_at(80);
+        assert!(medium.contains("Last updated: 0s ago"));
+        assert!(!medium.contains("Host up"));
+
+        let narrow = header_at(40);
+        assert!(narrow.contains("RD ●1 ✗1 /4"));
+    }
+
+    #[test]
+    fn tiny_terminal_shows_a_resize_hint_instead_of_the_layout() {
+        let app = test_app();
+
+        let screen = render_to_string(&app, 30, 10).unwrap();
+        assert!(screen.contains("Terminal too small"));
+        assert!(!screen.contains("Runner Dashboard"));
+
+        let screen = render_to_string(&app, MIN_WIDTH, MIN_HEIGHT).unwrap();
+        assert!(!screen.contains("Terminal too small"));
+        assert!(screen.contains("acme/runner-1"));
+    }
+
+    #[test]
+    fn runner_rows_show_colored_status_symbols() {
+        let buffer = render(&test_app());
+
+        for (i, (symbol, color)) in [
+            ("●", Color::Green),
+            ("○", Color::Yellow),
+            ("✗", Color::Red),
+            ("?", Color::DarkGray),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let cell = &buffer[(2, FIRST_ITEM_ROW + i as u16)];
+            assert_eq!(cell.symbol(), symbol);
+            assert_eq!(cell.fg, color);
+        }
+    }
+
+    #[test]
+    fn selected_row_is_highlighted() {
+        let mut app = test_app();
+        app.selected = 2;
+        let buffer = render(&app);
+
+        let name_x = 4;
+        let selected = &buffer[(name_x, FIRST_ITEM_ROW + 2)];
+        assert_eq!(selected.bg, Color::DarkGray);
+        assert!(selected.modifier.contains(Modifier::BOLD));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 2).contains("widgets-frontend/runner-1"));
+
+        let unselected = &buffer[(name_x, FIRST_ITEM_ROW)];
+        assert_ne!(unselected.bg, Color::DarkGray);
+    }
+
+    #[test]
+    fn compact_list_packs_several_runners_per_row() {
+        let mut app = test_app();
+        app.list_density = ListDensity::Compact;
+        app.selected = 3;
+        let buffer = render(&app);
+
+        // Labels are up to 18 wide, so the 58-column pane fits two per row
+        let first_row = row_text(&buffer, FIRST_ITEM_ROW);
+        assert!(first_row.contains("acme/1"));
+        assert!(first_row.contains("acme/2"));
+
+        let second_row = row_text(&buffer, FIRST_ITEM_ROW + 1);
+        let selected = second_row.find("widgets-frontend/2").unwrap();
+        let x = second_row[..selected].chars().count() as u16;
+        assert_eq!(buffer[(x, FIRST_ITEM_ROW + 1)].bg, Color::DarkGray);
+    }
+
+    #[test]
+    fn verbose_list_shows_status_text_and_version() {
+        let mut app = test_app();
+        app.list_density = ListDensity::Verbose;
+        let buffer = render(&app);
+
+        // A header row names the columns
+        let header = row_text(&buffer, FIRST_ITEM_ROW);
+        assert!(header.contains("NAME"));
+        assert!(header.contains("VERSION"));
+        let row = row_text(&buffer, FIRST_ITEM_ROW + 3);
+        assert!(row.contains("widgets-frontend/runner-1"));
+        assert!(row.contains("failed"));
+        assert!(row.contains("2.311.0"));
+    }
+
+    #[test]
+    fn verbose_list_shows_the_configured_columns() {
+        let mut app = test_app();
+        app.list_density = ListDensity::Verbose;
+        app.list_columns = vec![
+            ListColumn::Repo,
+            ListColumn::Name,
+            ListColumn::Busy,
+            ListColumn::Cpu,
+            ListColumn::Mem,
+        ];
+        app.runners[0].busy = true;
+        app.runner_usage.insert(
+            app.runners[0].path.clone(),
+            crate::app::ProcessUsage {
+                cpu: 87.4,
+                memory: 512 * 1024 * 1024,
+                started: 0,
+            },
+        );
+        let buffer = render(&app);
 
-fn mem_color(percent: f64) -> Color {
-    usage_color(percent, 70.0, 90.0)
+        let header = row_text(&buffer, FIRST_ITEM_ROW);
+        assert!(header.contains("REPO"));
+        assert!(header.contains("NAME"));
+        assert!(!header.contains("VERSION"));
+        // Name no longer repeats the repo once it has its own column
+        let busy = row_text(&buffer, FIRST_ITEM_ROW + 1);
+        assert!(busy.contains("runner-1"));
+        assert!(!busy.contains("acme/runner-1"));
+        assert!(busy.contains("yes"));
+        assert!(busy.contains("87%"));
+        assert!(busy.contains("512.0 MB"));
+        let stopped = row_text(&buffer, FIRST_ITEM_ROW + 2);
+        assert!(stopped.contains("no"));
+        assert!(stopped.contains("-"));
+    }
+
+    #[test]
+    fn clicking_a_verbose_header_picks_the_sort_column() {
+        let mut app = test_app();
+        app.list_density = ListDensity::Verbose;
+        app.sort_by = Some(ListColumn::Status);
+        let buffer = render(&app);
+        let area = Rect::new(0, 0, WIDTH, HEIGHT);
+
+        let header = row_text(&buffer, FIRST_ITEM_ROW);
+        assert!(header.contains("STATUS ↑"));
+        // Failed before not found before stopped before idle
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 1).contains("widgets-frontend/runner-1"));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 2).contains("widgets-frontend/runner-2"));
+
+        let x = |heading: &str| header[..header.find(heading).unwrap()].chars().count() as u16;
+        assert_eq!(
+            sort_column_at(&app, area, x("VERSION") + 1, FIRST_ITEM_ROW),
+            Some(ListColumn::Version)
+        );
+        assert_eq!(
+            sort_column_at(&app, area, x("NAME"), FIRST_ITEM_ROW),
+            Some(ListColumn::Name)
+        );
+        // Rows below the header and the gaps between headings don't sort
+        assert_eq!(
+            sort_column_at(&app, area, x("NAME"), FIRST_ITEM_ROW + 1),
+            None
+        );
+        assert_eq!(
+            sort_column_at(&app, area, x("STATUS") - 1, FIRST_ITEM_ROW),
+            None
+        );
+
+        // Other layouts have no header and note the sort in the title instead
+        app.list_density = ListDensity::Normal;
+        assert_eq!(sort_column_at(&app, area, x("NAME"), FIRST_ITEM_ROW), None);
+        assert!(render_to_string(&app, WIDTH, HEIGHT)
+            .unwrap()
+            .contains("Runners by status ↑"));
+    }
+
+    #[test]
+    fn status_list_right_aligns_the_status_text() {
+        let mut app = test_app();
+        app.list_density = ListDensity::Status;
+        let buffer = render(&app);
+
+        // The list pane ends at the detail pane's border, so the status sits just before it
+        let status_end = |y: u16| {
+            let row = row_text(&buffer, y);
+            let end = row.find(" │").unwrap();
+            (row[..end].rsplit(' ').next().unwrap().to_string(), end)
+        };
+        let failed = row_text(&buffer, FIRST_ITEM_ROW + 2);
+        assert!(failed.contains("widgets-frontend/runner-1"));
+        let (failed_status, failed_end) = status_end(FIRST_ITEM_ROW + 2);
+        let (first_status, first_end) = status_end(FIRST_ITEM_ROW);
+        assert_eq!(failed_status, "failed");
+        assert_eq!(first_status, runner_status_text(&app, &app.runners[0]));
+        assert_eq!(failed_end, first_end);
+    }
+
+    #[test]
+    fn busy_runners_are_set_apart_from_idle_ones() {
+        let mut app = test_app();
+        app.runners[0].busy = true;
+        app.list_density = ListDensity::Verbose;
+        app.runners.push(test_runner("widgets-frontend", 3));
+        let buffer = render(&app);
+
+        let cell = &buffer[(2, FIRST_ITEM_ROW + 1)];
+        assert_eq!(cell.symbol(), "⚙");
+        assert_eq!(cell.fg, Color::Magenta);
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 1).contains("busy"));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 5).contains("idle"));
+    }
+
+    #[test]
+    fn detail_mode_replaces_the_list() {
+        let mut app = test_app();
+        app.mode = AppMode::Detail;
+        app.selected = 1;
+        let buffer = render(&app);
+
+        assert!(row_text(&buffer, 4).starts_with("┌ Details"));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW).contains("Name: acme-runner-2"));
+        assert!(!(4..HEIGHT).any(|y| row_text(&buffer, y).contains("Runners")));
+    }
+
+    #[test]
+    fn stats_tab_breaks_down_statuses_per_repo() {
+        let mut app = test_app();
+        app.tab = Tab::Stats;
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+
+        assert!(screen.contains("Fleet"));
+        let acme = screen.lines().find(|l| l.contains("│acme ")).unwrap();
+        let columns: Vec<&str> = acme
+            .rsplit("│acme")
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .collect();
+        assert_eq!(columns, ["1", "1", "0", "0", "│"]);
+    }
+
+    #[test]
+    fn stats_tab_shows_memory_disks_and_network() {
+        let mut app = test_app();
+        app.system_stats.swap_total = 2 * 1024 * 1024 * 1024;
+        app.system_stats.disks = vec![DiskUsage {
+            mount_point: "/var/lib/runners".to_string(),
+            used: 30 * 1024 * 1024 * 1024,
+            total: 100 * 1024 * 1024 * 1024,
+        }];
+        app.system_stats.networks = vec![NetworkRate {
+            interface: "eth0".to_string(),
+            rx_bytes_per_sec: 1536.0,
+            tx_bytes_per_sec: 2.5 * 1024.0 * 1024.0,
+        }];
+        app.cpu_history = [10, 50, 90].into_iter().collect();
+        // Set directly: switching tabs would re-read the real disks
+        app.tab = Tab::Stats;
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+
+        assert!(screen.contains("CPU 42.0%"));
+        assert!(screen.contains("0.0/2.0 GB"));
+        assert!(screen.contains("/var/lib/runners"));
+        assert!(screen.contains("30.0/100.0 GB"));
+        assert!(screen.contains("1.5 KB/s"));
+        assert!(screen.contains("2.5 MB/s"));
+    }
+
+    #[test]
+    fn format_uptime_uses_two_largest_units() {
+        assert_eq!(format_uptime(4 * 86_400 + 3 * 3600 + 59), "4d 3h");
+        assert_eq!(format_uptime(2 * 3600 + 5 * 60), "2h 5m");
+        assert_eq!(format_uptime(59), "0m");
+    }
+
+    #[test]
+    fn format_utc_converts_unix_timestamps() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13 UTC");
+    }
+
+    #[test]
+    fn format_rate_scales_units() {
+        assert_eq!(format_rate(512.0), "512.0 B/s");
+        assert_eq!(format_rate(2048.0), "2.0 KB/s");
+        assert_eq!(format_rate(3.0 * 1024.0 * 1024.0 * 1024.0), "3.0 GB/s");
+    }
+
+    #[test]
+    fn events_tab_lists_newest_first() {
+        let mut app = test_app();
+        app.log_action("first".to_string());
+        app.log_action("second".to_string());
+        app.set_tab(Tab::Events);
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+
+        let first = screen.find("first").unwrap();
+        let second = screen.find("second").unwrap();
+        assert!(second < first);
+    }
+
+    #[test]
+    fn filter_hides_other_runners_and_reports_no_match() {
+        let mut app = test_app();
+        for c in "widgets-*/?".chars() {
+            app.push_filter_char(c);
+        }
+        let buffer = render(&app);
+
+        assert!(row_text(&buffer, FIRST_ITEM_ROW).contains("widgets-frontend/runner-1"));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 1).contains("widgets-frontend/runner-2"));
+        assert!(!row_text(&buffer, FIRST_ITEM_ROW + 2).contains("acme"));
+
+        app.push_filter_char('9');
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("No runner matches 'widgets-*/?9'"));
+    }
+
+    #[test]
+    fn system_stats_and_status_bar_fill_the_bottom_rows() {
+        let buffer = render(&test_app());
+
+        let stats = row_text(&buffer, HEIGHT - 3);
+        assert!(stats.contains("42.0%"));
+        assert!(stats.contains("1.50"));
+        assert!(!row_text(&buffer, HEIGHT - 1).trim().is_empty());
+    }
+
+    #[test]
+    fn shared_service_names_are_marked_and_warned_about() {
+        let mut runners = test_app().runners.clone();
+        runners[1].service_name = runners[0].service_name.clone();
+        let (command_tx, _) = mpsc::channel();
+        let (_, response_rx) = mpsc::channel();
+        let app = App::new_with(runners, command_tx, response_rx, SystemStats::default());
+        let buffer = render(&app);
+
+        assert!(row_text(&buffer, FIRST_ITEM_ROW).contains("acme/runner-1 ‼"));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 1).contains("acme/runner-2 ‼"));
+        assert!(!row_text(&buffer, FIRST_ITEM_ROW + 2).contains('‼'));
+        assert!(row_text(&buffer, HEIGHT - 1).contains("2 runners share a service name"));
+    }
+
+    #[test]
+    fn welcome_overlay_summarises_discovery() {
+        let mut app = test_app();
+        app.mode = AppMode::Welcome;
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+
+        assert!(screen.contains("Welcome to Runner Dashboard"));
+        assert!(screen.contains("Found 4 runners in 2 repositories"));
+        assert!(screen.contains("GITHUB_TOKEN"));
+        assert!(screen.contains("WELCOME"));
+    }
+
+    #[test]
+    fn help_scrolls_and_shows_position_when_it_does_not_fit() {
+        let mut app = test_app();
+        app.toggle_help();
+        let total = help_line_count();
+        let screen = render_to_string(&app, WIDTH, 20).unwrap();
+        assert!(screen.contains("Actions (current view)"));
+        assert!(screen.contains(&format!(" of {} ", total)));
+
+        app.scroll_help_down(total, total);
+        let screen = render_to_string(&app, WIDTH, 20).unwrap();
+        assert!(!screen.contains("Navigation"));
+        assert!(screen.contains("Esc/q/?/h  Close"));
+        assert!(screen.contains(" Esc/q to close "));
+    }
+
+    #[test]
+    fn help_starts_with_the_section_for_the_current_view() {
+        let mut app = test_app();
+        app.set_tab(Tab::Logs);
+        app.toggle_help();
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+
+        let logs = screen.find("In Logs Tab (current view)").unwrap();
+        assert!(logs < screen.find("Navigation").unwrap());
+        assert!(!screen.contains("Actions (current view)"));
+    }
+
+    #[test]
+    fn home_directory_is_abbreviated_to_tilde() {
+        let home = Path::new("/home/builder");
+
+        assert_eq!(
+            abbreviate_home(Path::new("/home/builder/action-runners/acme/1"), Some(home)),
+            "~/action-runners/acme/1"
+        );
+        assert_eq!(abbreviate_home(home, Some(home)), "~");
+        assert_eq!(
+            abbreviate_home(Path::new("/home/builder2/x"), Some(home)),
+            "/home/builder2/x"
+        );
+        assert_eq!(
+            abbreviate_home(Path::new("/opt/runners"), None),
+            "/opt/runners"
+        );
+    }
+
+    #[test]
+    fn ascii_mode_swaps_status_symbols_and_bars() {
+        let mut app = test_app();
+        app.ascii = true;
+        app.refreshing = true;
+        let buffer = render(&app);
+
+        assert!(row_text(&buffer, 1).contains("* 1 active"));
+        assert!(row_text(&buffer, 1).contains("X 1 failed"));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW).contains(" * acme/runner-1"));
+        assert!(row_text(&buffer, FIRST_ITEM_ROW + 1).contains(" o acme/runner-2"));
+        assert!(row_text(&buffer, HEIGHT - 3).contains('#'));
+        assert!(!row_text(&buffer, HEIGHT - 3).contains('█'));
+        assert!(row_text(&buffer, HEIGHT - 1).starts_with(" NORMAL  | "));
+    }
+
+    #[test]
+    fn stats_line_estimates_job_headroom_once_it_has_a_figure() {
+        let mut app = test_app();
+        let stats_row = |app: &App| row_text(&render(app), HEIGHT - 3);
+        assert!(!stats_row(&app).contains("headroom"));
+
+        // 58% of two cores is free
+        app.job_usage.cpu = 50.0;
+        assert!(stats_row(&app).contains("Est. headroom: ~2 jobs"));
+        app.job_usage.cpu = 100.0;
+        let row = stats_row(&app);
+        assert!(
+            row.contains("Est. headroom: ~1 job") && !row.contains("jobs"),
+            "{}",
+            row
+        );
+    }
+
+    #[test]
+    fn stats_bars_scale_with_the_terminal_width() {
+        let app = test_app();
+        let bar_len = |width: u16| {
+            let screen = render_to_string(&app, width, HEIGHT).unwrap();
+            let line = screen.lines().nth(HEIGHT as usize - 3).unwrap().to_string();
+            assert!(line.contains("Load: 1.50 1.00 0.50"), "{}", line);
+            line.split('[')
+                .nth(1)
+                .map_or(0, |bar| bar.chars().take_while(|&c| c != ']').count())
+        };
+
+        assert_eq!(bar_len(60), 0);
+        assert!(bar_len(100) >= MIN_STATS_BAR_WIDTH);
+        assert!(bar_len(100) < bar_len(160));
+        assert_eq!(bar_len(400), MAX_STATS_BAR_WIDTH);
+    }
+
+    #[test]
+    fn backlogged_repos_are_badged() {
+        let mut app = test_app();
+        app.queued_runs.insert("acme".to_string(), 4);
+        app.backlogs.insert(
+            "acme".to_string(),
+            Alarm {
+                over_since: Some(std::time::Instant::now()),
+                raised: true,
+            },
+        );
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("Runners BACKLOG acme"));
+
+        app.tab = Tab::Stats;
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        let lines: Vec<&str> = screen.lines().collect();
+        let acme = lines.iter().position(|l| l.contains("│acme ")).unwrap();
+        assert!(lines[acme + 1].contains("BACKLOG 4 queued, 1 idle"));
+        assert!(lines[acme + 2].contains("│widgets-frontend "));
+        assert_eq!(screen.matches("BACKLOG").count(), 1);
+    }
+
+    #[test]
+    fn output_overlay_pins_the_verdict_above_the_output() {
+        let mut app = test_app();
+        app.output = Some(OutputView {
+            title: " Connection test: acme-runner-1 ".to_string(),
+            summary: "[svc.sh] No systemd unit named x; svc.sh status failed".to_string(),
+            ok: false,
+            lines: (1..=100).map(|i| format!("line {}", i)).collect(),
+            scroll: 50,
+        });
+        app.mode = AppMode::Output;
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+
+        assert!(screen.contains("Connection test: acme-runner-1"));
+        assert!(screen.contains("[svc.sh] No systemd unit named x"));
+        assert!(screen.contains("line 51"));
+        assert!(!screen.contains("line 50 "));
+        assert!(screen.contains("51-"));
+        assert!(screen.contains("of 100"));
+    }
+
+    #[test]
+    fn output_lines_are_colored_by_what_they_say() {
+        assert_eq!(
+            output_line_style("     Active: active (running) since Mon").fg,
+            Some(Color::Green)
+        );
+        assert_eq!(
+            output_line_style("     Active: failed (Result: exit-code)").fg,
+            Some(Color::Red)
+        );
+        assert_eq!(
+            output_line_style("sudo: a password is required").fg,
+            Some(Color::Red)
+        );
+        assert_eq!(
+            output_line_style("$ systemctl status x").fg,
+            Some(Color::Cyan)
+        );
+        assert_eq!(
+            output_line_style("(exit status 3)").fg,
+            Some(Color::DarkGray)
+        );
+        assert_eq!(output_line_style("   Main PID: 4242").fg, None);
+    }
+
+    #[test]
+    fn hiding_the_details_pane_gives_the_list_the_full_width() {
+        let mut app = test_app();
+        app.list_density = ListDensity::Status;
+        assert!(render_to_string(&app, WIDTH, HEIGHT)
+            .unwrap()
+            .contains(" Details "));
+        assert!(!row_text(&render(&app), FIRST_ITEM_ROW).ends_with("idle │"));
+
+        app.show_details = false;
+        assert!(!render_to_string(&app, WIDTH, HEIGHT)
+            .unwrap()
+            .contains(" Details "));
+        let row = row_text(&render(&app), FIRST_ITEM_ROW);
+        assert_eq!(row.chars().count(), WIDTH as usize);
+        assert!(row.trim_end().ends_with("idle │"), "{}", row);
+    }
+
+    #[test]
+    fn details_show_leftover_work() {
+        let mut app = test_app();
+        app.mode = AppMode::Detail;
+        let usage = |job_dirs, bytes| runner::WorkUsage {
+            folder: PathBuf::from("/nonexistent/action-runners/acme/1/_work"),
+            job_dirs,
+            bytes,
+            scanned_at: std::time::Instant::now(),
+        };
+
+        app.runners[0].work = Some(usage(0, 0));
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("_work: clean"));
+
+        app.runners[0].work = Some(usage(3, 4_509_715_660));
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("_work: 3 stale jobs, 4.2 GB"));
+    }
+
+    #[test]
+    fn details_show_the_last_registration_check() {
+        let mut app = test_app();
+        app.mode = AppMode::Detail;
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("GitHub: not checked (press g)"));
+
+        app.runners[0].registration = Some(Registration::Deauthorized);
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("GitHub: deauthorized: running here but offline to GitHub"));
+    }
+
+    #[test]
+    fn fleet_tab_highlights_drift_and_scrolls_across_runners() {
+        let mut app = test_app();
+        app.runners[2].version = Some("2.300.0".to_string());
+        app.set_tab(Tab::Fleet);
+        let buffer = render(&app);
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("Fleet: 1 setting differs"));
+        assert!(screen.contains("acme-runner-1"));
+        assert!(screen.contains("widgets-frontend-runner-2"));
+
+        let version_row = (0..HEIGHT)
+            .find(|&y| row_text(&buffer, y).contains("Version"))
+            .unwrap();
+        let x = row_text(&buffer, version_row).find("2.300.0").unwrap() as u16;
+        assert_eq!(buffer[(x, version_row)].fg, Color::Red);
+
+        // Too narrow for all four: scroll to bring the last ones in
+        let narrow = render_to_string(&app, 60, HEIGHT).unwrap();
+        assert!(narrow.contains("runners 1-2 of 4"));
+        app.scroll_fleet_right();
+        app.scroll_fleet_right();
+        let narrow = render_to_string(&app, 60, HEIGHT).unwrap();
+        assert!(narrow.contains("runners 3-3 of 4"));
+        assert!(!narrow.contains("acme-runner-1"));
+    }
+
+    #[test]
+    fn label_editor_shows_current_labels_and_input_errors() {
+        let mut app = test_app();
+        app.runners[0].labels = Some(vec!["self-hosted".to_string(), "gpu".to_string()]);
+        app.previous_mode = AppMode::Normal;
+        app.mode = AppMode::Labels;
+        app.label_input = "a,b".to_string();
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("Labels: self-hosted, gpu"));
+        assert!(screen.contains("> a,b_"));
+        assert!(screen.contains("label adds it, -label removes it"));
+
+        app.apply_label_edit();
+        assert_eq!(app.mode, AppMode::Labels);
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("contains ','"));
+    }
+
+    #[test]
+    fn usage_bars_shade_toward_the_alert_color() {
+        let mut app = test_app();
+        let thresholds = Thresholds {
+            warn: 50.0,
+            alert: 80.0,
+        };
+        let cell_colors = |app: &App| -> Vec<Option<Color>> {
+            usage_bar(app, 90.0, 10, thresholds)
+                .iter()
+                .filter(|span| span.content == symbols(app).bar_filled)
+                .map(|span| span.style.fg)
+                .collect()
+        };
+
+        let colors = cell_colors(&app);
+        assert_eq!(colors.len(), 9);
+        assert_eq!(colors[0], Some(gradient_color(5.0, thresholds)));
+        assert_eq!(colors[8], Some(Color::Rgb(220, 0, 0)));
+        assert_ne!(colors[0], colors[4]);
+        assert_eq!(gradient_color(50.0, thresholds), Color::Rgb(230, 200, 0));
+
+        // ASCII mode falls back to one threshold color for the whole bar
+        app.ascii = true;
+        let bar = usage_bar(&app, 90.0, 10, thresholds);
+        assert_eq!(bar.len(), 1);
+        assert_eq!(bar[0].content, "[#########-]");
+        assert_eq!(bar[0].style.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn bulk_confirmation_lists_the_affected_runners() {
+        let mut app = test_app();
+        for number in 3..=14 {
+            app.runners.push(test_runner("acme", number));
+        }
+        app.pending_confirm = Some(PendingConfirm {
+            prompt: "Restart 14 marked runner(s), 4 at a time?".to_string(),
+            action: "restart".to_string(),
+            typed: None,
+            word: String::new(),
+            next: None,
+            targets: Some((0..app.runners.len()).collect()),
+            scroll: 0,
+        });
+        app.mode = AppMode::Confirm;
+
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("Affects 1-10 of 16 runners"));
+        assert!(screen.contains("widgets-frontend/runner-1  failed"));
+        assert!(!screen.contains("acme/runner-14  idle"));
+
+        app.scroll_confirm_down(100);
+        let screen = render_to_string(&app, WIDTH, HEIGHT).unwrap();
+        assert!(screen.contains("Affects 7-16 of 16 runners"));
+        assert!(screen.contains("acme/runner-14  idle"));
+    }
 }
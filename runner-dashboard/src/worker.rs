@@ -0,0 +1,700 @@
+use crate::event_socket::{Event, EventSocket};
+use crate::github::LabelEdits;
+use crate::runner::{
+    check_registration, control_runner, edit_labels, probe_runner, refresh_runners_cached,
+    scan_work, CommandFailure, ProbeReport, RefreshTimings, Registration, Runner, ServiceCache,
+    SystemCommandRunner, UnitCache, WorkUsage, PRUNE_WORK_ACTION,
+};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+/// Name prefix for background threads that recover from their own panics
+const BACKGROUND_THREAD_PREFIX: &str = "runner-";
+const WORKER_THREAD_NAME: &str = "runner-worker";
+const ACTION_THREAD_NAME: &str = "runner-action";
+
+/// Returns true on worker/action threads, whose panics are caught and reported
+/// instead of tearing down the terminal.
+pub fn is_background_thread() -> bool {
+    std::thread::current()
+        .name()
+        .is_some_and(|name| name.starts_with(BACKGROUND_THREAD_PREFIX))
+}
+
+/// Extract a readable message from a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// How bulk actions are spread out, so restarting thirty runners doesn't start
+/// thirty listeners on the host at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BulkLimits {
+    /// Runners acted on at the same time
+    pub concurrency: usize,
+    /// Pause between one wave finishing and the next starting
+    pub gap: Duration,
+}
+
+/// Messages sent from main thread to background worker
+#[derive(Debug)]
+pub enum WorkerCommand {
+    /// `force` skips cached service states, for a refresh the user asked for
+    Refresh {
+        force: bool,
+    },
+    /// Re-read installed units, e.g. after an install outside the worker, then refresh
+    ReloadUnits,
+    ControlRunner {
+        runner_index: usize,
+        action: String,
+    },
+    /// Apply one action to several runners, a wave at a time
+    ControlMany {
+        runner_indices: Vec<usize>,
+        action: String,
+        limits: BulkLimits,
+    },
+    /// Ask GitHub whether a runner is still registered, using `token`
+    CheckRegistration {
+        runner_index: usize,
+        token: String,
+    },
+    /// Count and size the job directories in a runner's work folder
+    ScanWork {
+        runner_index: usize,
+    },
+    /// Ask a runner's service backend about it without changing anything
+    Probe {
+        runner_index: usize,
+    },
+    /// Add and remove labels on a runner's GitHub registration, using `token`
+    EditLabels {
+        runner_index: usize,
+        token: String,
+        edits: LabelEdits,
+    },
+    Shutdown,
+}
+
+/// Messages sent from background worker to main thread
+#[derive(Debug)]
+pub enum WorkerResponse {
+    RunnersUpdated(Vec<Runner>),
+    ActionComplete {
+        message: String,
+        /// Full output of the command, when the action failed because one did
+        failure: Option<CommandFailure>,
+    },
+    /// A control action on the runner at this path is over, and the runners
+    /// just sent reflect it
+    ActionFinished(PathBuf),
+    /// How long the refresh behind the last `RunnersUpdated` took, phase by phase
+    RefreshTimed(RefreshTimings),
+    LatestRunnerVersion(String),
+    /// Queued workflow runs for a repo directory's GitHub repository
+    QueueDepth {
+        repo: String,
+        queued: usize,
+    },
+    /// What a connection test of the runner found
+    Probed {
+        runner: String,
+        report: ProbeReport,
+    },
+    /// A label edit is over: the runner's labels, or why GitHub refused
+    LabelsEdited {
+        runner: String,
+        result: Result<Vec<String>, String>,
+    },
+    WorkerRestarted {
+        error: String,
+    },
+}
+
+/// A control action that finished on its own thread
+struct ActionDone {
+    /// Runner the action was for; None for bulk progress reports
+    runner: Option<PathBuf>,
+    action: String,
+    message: String,
+    failure: Option<CommandFailure>,
+    /// Reload units and refresh before reporting it; a bulk run leaves that to
+    /// each wave's progress report
+    refresh: bool,
+}
+
+/// Spawn the supervised background worker thread.
+///
+/// Service query results are reused for up to `service_ttl` between refreshes.
+/// Status changes and finished actions are published on `events`, if given.
+pub fn spawn_worker(
+    runners: Vec<Runner>,
+    command_rx: Receiver<WorkerCommand>,
+    response_tx: Sender<WorkerResponse>,
+    service_ttl: Duration,
+    events: Option<EventSocket>,
+) -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name(WORKER_THREAD_NAME.to_string())
+        .spawn(move || supervise_worker(runners, command_rx, response_tx, service_ttl, events))?;
+    Ok(())
+}
+
+/// Run the worker loop, restarting it from the initial runner list if it panics.
+fn supervise_worker(
+    runners: Vec<Runner>,
+    command_rx: Receiver<WorkerCommand>,
+    response_tx: Sender<WorkerResponse>,
+    service_ttl: Duration,
+    mut events: Option<EventSocket>,
+) {
+    loop {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            worker_thread(
+                runners.clone(),
+                &command_rx,
+                &response_tx,
+                service_ttl,
+                &mut events,
+            )
+        }));
+
+        match result {
+            Ok(()) => break,
+            Err(payload) => {
+                let error = panic_message(payload.as_ref());
+                if response_tx
+                    .send(WorkerResponse::WorkerRestarted { error })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Background worker thread that handles runner refresh and control operations.
+///
+/// The worker is the single owner of the runner list. Control actions run on
+/// short-lived threads so a slow restart doesn't block status refreshes; their
+/// results are funneled back here before runners are refreshed and reported.
+fn worker_thread(
+    mut runners: Vec<Runner>,
+    command_rx: &Receiver<WorkerCommand>,
+    response_tx: &Sender<WorkerResponse>,
+    service_ttl: Duration,
+    events: &mut Option<EventSocket>,
+) {
+    let (action_done_tx, action_done_rx) = mpsc::channel::<ActionDone>();
+    let (registration_tx, registration_rx) =
+        mpsc::channel::<(usize, (Registration, Option<Vec<String>>))>();
+    let (work_tx, work_rx) = mpsc::channel::<(usize, Result<WorkUsage, String>)>();
+    let (labels_tx, labels_rx) = mpsc::channel::<(usize, Result<Vec<String>, String>)>();
+    let mut units = UnitCache::load(&SystemCommandRunner);
+    let mut services = ServiceCache::new(service_ttl);
+    if let Some(events) = events.as_mut() {
+        events.runners_updated(&runners);
+    }
+
+    loop {
+        // Report any control actions that finished since the last iteration,
+        // refreshing once for all of them
+        let finished: Vec<ActionDone> = action_done_rx.try_iter().collect();
+        if finished.iter().any(|done| done.refresh) {
+            // Install/uninstall may have changed which units exist
+            units = UnitCache::load(&SystemCommandRunner);
+
+            // Refresh runners after control action, without the cached service states
+            services.invalidate();
+            let timings =
+                refresh_runners_cached(&SystemCommandRunner, &mut runners, &units, &mut services);
+
+            let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
+            let _ = response_tx.send(WorkerResponse::RefreshTimed(timings));
+            if let Some(events) = events.as_mut() {
+                events.runners_updated(&runners);
+            }
+        }
+        for done in finished {
+            if let Some(events) = events.as_mut() {
+                if let Some(event) = action_event(&runners, &done) {
+                    events.publish(&event);
+                }
+            }
+            if let Some(path) = done.runner {
+                let _ = response_tx.send(WorkerResponse::ActionFinished(path));
+            }
+            let _ = response_tx.send(WorkerResponse::ActionComplete {
+                message: done.message,
+                failure: done.failure,
+            });
+        }
+
+        // Cache registration checks on the runners they were made for
+        while let Ok((index, (registration, labels))) = registration_rx.try_recv() {
+            let Some(runner) = runners.get_mut(index) else {
+                continue;
+            };
+            let message = format!("{}: {}", runner.display_name(), registration.describe());
+            runner.registration = Some(registration);
+            if labels.is_some() {
+                runner.labels = labels;
+            }
+            let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
+            let _ = response_tx.send(WorkerResponse::ActionComplete {
+                message,
+                failure: None,
+            });
+        }
+
+        // Same for work folder scans; only failures are worth a message
+        while let Ok((index, scan)) = work_rx.try_recv() {
+            let Some(runner) = runners.get_mut(index) else {
+                continue;
+            };
+            match scan {
+                Ok(usage) => {
+                    runner.work = Some(usage);
+                    let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
+                }
+                Err(error) => {
+                    let message = format!(
+                        "Error: could not scan the work folder of {}: {}",
+                        runner.display_name(),
+                        error
+                    );
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                }
+            }
+        }
+
+        while let Ok((index, result)) = labels_rx.try_recv() {
+            let Some(runner) = runners.get_mut(index) else {
+                continue;
+            };
+            if let Ok(labels) = &result {
+                runner.labels = Some(labels.clone());
+                let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
+            }
+            let _ = response_tx.send(WorkerResponse::LabelsEdited {
+                runner: runners[index].display_name(),
+                result,
+            });
+        }
+
+        // Wait for command with timeout to allow periodic refresh
+        match command_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(WorkerCommand::Refresh { force }) => {
+                if force {
+                    services.invalidate();
+                }
+                // Refresh all runners
+                let timings = refresh_runners_cached(
+                    &SystemCommandRunner,
+                    &mut runners,
+                    &units,
+                    &mut services,
+                );
+
+                // Send updated runners back to main thread
+                let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
+                let _ = response_tx.send(WorkerResponse::RefreshTimed(timings));
+                if let Some(events) = events.as_mut() {
+                    events.runners_updated(&runners);
+                }
+            }
+            Ok(WorkerCommand::ReloadUnits) => {
+                units = UnitCache::load(&SystemCommandRunner);
+                services.invalidate();
+                let timings = refresh_runners_cached(
+                    &SystemCommandRunner,
+                    &mut runners,
+                    &units,
+                    &mut services,
+                );
+                let _ = response_tx.send(WorkerResponse::RunnersUpdated(runners.clone()));
+                let _ = response_tx.send(WorkerResponse::RefreshTimed(timings));
+                if let Some(events) = events.as_mut() {
+                    events.runners_updated(&runners);
+                }
+            }
+            Ok(WorkerCommand::ControlRunner {
+                runner_index,
+                action,
+            }) => {
+                // Execute control action with bounds checking
+                let Some(runner) = runners.get(runner_index).cloned() else {
+                    let message = format!(
+                        "Error: Runner index {} out of bounds (have {} runners)",
+                        runner_index,
+                        runners.len()
+                    );
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                    continue;
+                };
+
+                let done_tx = action_done_tx.clone();
+                let work_done_tx = work_tx.clone();
+                let path = runner.path.clone();
+                let spawned = std::thread::Builder::new()
+                    .name(ACTION_THREAD_NAME.to_string())
+                    .spawn(move || {
+                        let (message, failure) = run_action(&runner, &action);
+                        let _ = done_tx.send(ActionDone {
+                            runner: Some(runner.path.clone()),
+                            action: action.clone(),
+                            message,
+                            failure,
+                            refresh: true,
+                        });
+                        // Show what's left rather than the size from before
+                        if action == PRUNE_WORK_ACTION {
+                            let scan = scan_work(&runner).map_err(|e| format!("{:#}", e));
+                            let _ = work_done_tx.send((runner_index, scan));
+                        }
+                    });
+                if let Err(e) = spawned {
+                    let message = format!("Error: failed to start action: {}", e);
+                    let _ = response_tx.send(WorkerResponse::ActionFinished(path));
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                }
+            }
+            Ok(WorkerCommand::ControlMany {
+                runner_indices,
+                action,
+                limits,
+            }) => {
+                let targets: Vec<Runner> = runner_indices
+                    .iter()
+                    .filter_map(|&index| runners.get(index).cloned())
+                    .collect();
+                let paths: Vec<PathBuf> = targets.iter().map(|r| r.path.clone()).collect();
+                let done_tx = action_done_tx.clone();
+                let spawned = std::thread::Builder::new()
+                    .name(ACTION_THREAD_NAME.to_string())
+                    .spawn(move || control_in_waves(&targets, &action, limits, &done_tx));
+                if let Err(e) = spawned {
+                    for path in paths {
+                        let _ = response_tx.send(WorkerResponse::ActionFinished(path));
+                    }
+                    let message = format!("Error: failed to start action: {}", e);
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                }
+            }
+            Ok(WorkerCommand::CheckRegistration {
+                runner_index,
+                token,
+            }) => {
+                let Some(runner) = runners.get(runner_index).cloned() else {
+                    continue;
+                };
+                // A network call, so off the worker like control actions
+                let done_tx = registration_tx.clone();
+                let spawned = std::thread::Builder::new()
+                    .name(ACTION_THREAD_NAME.to_string())
+                    .spawn(move || {
+                        let registration = panic::catch_unwind(AssertUnwindSafe(|| {
+                            check_registration(&runner, &token)
+                        }))
+                        .unwrap_or_else(|payload| {
+                            (Registration::Unknown(panic_message(payload.as_ref())), None)
+                        });
+                        let _ = done_tx.send((runner_index, registration));
+                    });
+                if let Err(e) = spawned {
+                    let message = format!("Error: failed to start registration check: {}", e);
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                }
+            }
+            Ok(WorkerCommand::ScanWork { runner_index }) => {
+                let Some(runner) = runners.get(runner_index).cloned() else {
+                    continue;
+                };
+                // Walking a large _work tree can take a while
+                let done_tx = work_tx.clone();
+                let spawned = std::thread::Builder::new()
+                    .name(ACTION_THREAD_NAME.to_string())
+                    .spawn(move || {
+                        let scan = panic::catch_unwind(AssertUnwindSafe(|| scan_work(&runner)))
+                            .map_err(|payload| panic_message(payload.as_ref()))
+                            .and_then(|scan| scan.map_err(|e| format!("{:#}", e)));
+                        let _ = done_tx.send((runner_index, scan));
+                    });
+                if let Err(e) = spawned {
+                    let message = format!("Error: failed to start work folder scan: {}", e);
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                }
+            }
+            Ok(WorkerCommand::Probe { runner_index }) => {
+                let Some(runner) = runners.get(runner_index).cloned() else {
+                    continue;
+                };
+                // systemctl and sudo may be slow to answer
+                let done_tx = response_tx.clone();
+                let spawned = std::thread::Builder::new()
+                    .name(ACTION_THREAD_NAME.to_string())
+                    .spawn(move || {
+                        let report = panic::catch_unwind(AssertUnwindSafe(|| {
+                            probe_runner(&SystemCommandRunner, &runner)
+                        }))
+                        .unwrap_or_else(|payload| ProbeReport {
+                            backend: "unknown",
+                            ok: false,
+                            summary: format!("test panicked: {}", panic_message(payload.as_ref())),
+                            transcript: Vec::new(),
+                        });
+                        let _ = done_tx.send(WorkerResponse::Probed {
+                            runner: runner.display_name(),
+                            report,
+                        });
+                    });
+                if let Err(e) = spawned {
+                    let message = format!("Error: failed to start connection test: {}", e);
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                }
+            }
+            Ok(WorkerCommand::EditLabels {
+                runner_index,
+                token,
+                edits,
+            }) => {
+                let Some(runner) = runners.get(runner_index).cloned() else {
+                    continue;
+                };
+                let done_tx = labels_tx.clone();
+                let spawned = std::thread::Builder::new()
+                    .name(ACTION_THREAD_NAME.to_string())
+                    .spawn(move || {
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                            edit_labels(&runner, &token, &edits)
+                        }))
+                        .map_err(|payload| panic_message(payload.as_ref()))
+                        .and_then(|labels| labels.map_err(|e| format!("{:#}", e)));
+                        let _ = done_tx.send((runner_index, result));
+                    });
+                if let Err(e) = spawned {
+                    let message = format!("Error: failed to start label edit: {}", e);
+                    let _ = response_tx.send(WorkerResponse::ActionComplete {
+                        message,
+                        failure: None,
+                    });
+                }
+            }
+            Ok(WorkerCommand::Shutdown) => {
+                // Exit worker thread
+                break;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                // No command received, continue loop
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                // Main thread dropped, exit
+                break;
+            }
+        }
+    }
+}
+
+/// Run a control action and describe the result, catching panics.
+fn run_action(runner: &Runner, action: &str) -> (String, Option<CommandFailure>) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        control_runner(&SystemCommandRunner, runner, action)
+    }));
+    match result {
+        Ok(Ok(message)) => (message, None),
+        Ok(Err(e)) => (
+            format!("Error: {}", e),
+            e.downcast_ref::<CommandFailure>().cloned(),
+        ),
+        Err(payload) => {
+            let message = format!(
+                "Error: action panicked: {}",
+                panic_message(payload.as_ref())
+            );
+            (message, None)
+        }
+    }
+}
+
+/// The event for an action that finished on one runner; bulk progress reports
+/// have none.
+fn action_event(runners: &[Runner], done: &ActionDone) -> Option<Event> {
+    let path = done.runner.as_ref()?;
+    let runner = runners.iter().find(|r| &r.path == path)?;
+    Some(Event::Action {
+        runner: format!("{}/{}", runner.repo, runner.name),
+        action: done.action.clone(),
+        ok: !done.message.starts_with("Error:"),
+        message: done.message.clone(),
+    })
+}
+
+/// Apply `action` to `runners` in waves, reporting each result and then each wave.
+///
+/// Failures are only reported as messages; one output overlay per failed runner
+/// would bury the rest.
+fn control_in_waves(
+    runners: &[Runner],
+    action: &str,
+    limits: BulkLimits,
+    done_tx: &Sender<ActionDone>,
+) {
+    let total = runners.len();
+    let mut finished = 0;
+    let mut failed = 0;
+    in_waves(
+        runners,
+        limits,
+        |runner| (runner.path.clone(), run_action(runner, action).0),
+        |runner, why| (runner.path.clone(), format!("Error: {}", why)),
+        |wave, waves, results| {
+            finished += results.len();
+            for (path, message) in results {
+                if message.starts_with("Error:") {
+                    failed += 1;
+                }
+                let _ = done_tx.send(ActionDone {
+                    runner: Some(path),
+                    action: action.to_string(),
+                    message,
+                    failure: None,
+                    refresh: false,
+                });
+            }
+            let progress = format!(
+                "Bulk {}: wave {}/{} done, {}/{} runners, {} failed",
+                action, wave, waves, finished, total, failed
+            );
+            let _ = done_tx.send(ActionDone {
+                runner: None,
+                action: action.to_string(),
+                message: progress,
+                failure: None,
+                refresh: true,
+            });
+        },
+    );
+}
+
+/// Run `run` over `items` at most `limits.concurrency` at a time, calling
+/// `after_wave(wave, waves, results)` as each wave finishes.
+///
+/// An item whose thread can't be started or panics gets `failed(item, why)` as
+/// its result, so every item has one.
+fn in_waves<T: Sync, R: Send>(
+    items: &[T],
+    limits: BulkLimits,
+    run: impl Fn(&T) -> R + Sync,
+    failed: impl Fn(&T, String) -> R,
+    mut after_wave: impl FnMut(usize, usize, Vec<R>),
+) {
+    let size = limits.concurrency.max(1);
+    let waves = items.len().div_ceil(size);
+    for (i, wave) in items.chunks(size).enumerate() {
+        if i > 0 {
+            std::thread::sleep(limits.gap);
+        }
+        let run = &run;
+        let results = std::thread::scope(|scope| {
+            let handles: Vec<_> = wave
+                .iter()
+                .map(|item| {
+                    // Named so a panic is reported rather than tearing down the terminal
+                    std::thread::Builder::new()
+                        .name(ACTION_THREAD_NAME.to_string())
+                        .spawn_scoped(scope, move || run(item))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .zip(wave)
+                .map(|(handle, item)| match handle {
+                    Ok(handle) => handle.join().unwrap_or_else(|payload| {
+                        failed(
+                            item,
+                            format!("action panicked: {}", panic_message(payload.as_ref())),
+                        )
+                    }),
+                    Err(e) => failed(item, format!("could not start action thread: {}", e)),
+                })
+                .collect()
+        });
+        after_wave(i + 1, waves, results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn bulk_work_runs_in_waves_of_limited_size() {
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+        let limits = BulkLimits {
+            concurrency: 3,
+            gap: Duration::ZERO,
+        };
+        let mut waves = Vec::new();
+
+        in_waves(
+            &(1..=7).collect::<Vec<u32>>(),
+            limits,
+            |n| {
+                if *n == 5 {
+                    panic!("runner 5 broke");
+                }
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(20));
+                running.fetch_sub(1, Ordering::SeqCst);
+                n * 10
+            },
+            |n, _| n * 100,
+            |wave, total, results| waves.push((wave, total, results)),
+        );
+
+        assert!(most.load(Ordering::SeqCst) <= 3);
+        assert_eq!(
+            waves,
+            [
+                (1, 3, vec![10, 20, 30]),
+                (2, 3, vec![40, 500, 60]),
+                (3, 3, vec![70]),
+            ]
+        );
+    }
+}